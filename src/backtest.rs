@@ -1,23 +1,38 @@
-mod trade;
+pub mod trade;
 mod trade_rule;
+pub mod order;
+pub mod position_manager;
+pub mod position_sizer;
+pub mod risk_manager;
 pub mod strategy;
+pub mod engine;
+pub mod regime_report;
+pub mod rule_strategy;
+pub mod gene_schema;
 
-use trade::Trade;
+use position_manager::PositionLimits;
+use risk_manager::{RiskLimits, RiskReport};
 use crate::candlestick;
 use candlestick::Candlestick;
 use strategy::TradingStrategy;
+use engine::{Engine, EngineConfig, ExitReason, Observer};
 use crate::utils::split_number_in_points;
-
-use self::strategy::SingleStrategy;
+use crate::indicators::cache::IndicatorCache;
+use crate::indicators::regime::{Regime, RegimeClassifier};
+use crate::indicators::traits::Next;
+use regime_report::{RegimeReport, RegimeTracker};
 
 pub struct Backtest {
     candlesticks: Vec<Candlestick>,
     training_ranges: Vec<(u32, u32)>, // ranges of candles in the candlestick vector that will be used for training
     validation_ranges: Vec<(u32, u32)>, // ranges of candles in the candlestick vector that will be used for training
     slipage_percentage: f32, // amount of price change on each trade
-    initialization_candles: u32, // number of candles to initialize the strategy with
     fee_percentage: f32, // percentage of the price change that will be charged as fee
     initial_usd_balance: f32, // initial balance in USD
+    position_limits: PositionLimits, // how many trades can be open at the same time
+    risk_limits: RiskLimits, // account level guardrails that stop new trades
+    indicator_cache: Option<IndicatorCache>, // indicator series shared by all strategies tested on the same ranges
+    regimes: Vec<Regime>, // regime of each candlestick, empty unless the reports are broken down by regime
 }
 
 #[derive(PartialEq, Eq, Copy, Clone)]
@@ -55,7 +70,10 @@ impl Backtest {
             fee_percentage,
             slipage_percentage,
             initial_usd_balance: 10_000.0,
-            initialization_candles: 250,
+            position_limits: PositionLimits::default(),
+            risk_limits: RiskLimits::default(),
+            indicator_cache: Some(IndicatorCache::new()),
+            regimes: Vec::new(),
             training_ranges: Vec::with_capacity(initial_capacity),
             validation_ranges: Vec::with_capacity(initial_capacity),
         };
@@ -71,93 +89,364 @@ impl Backtest {
         backtest_engine  
     }
 
+    /// limits the amount of trades that can be open at the same time, by default only one trade is open at a time
+    pub fn with_position_limits(mut self, position_limits: PositionLimits) -> Self {
+        self.position_limits = position_limits;
+        self
+    }
+
+    /// account level guardrails like a drawdown circuit breaker or a daily loss limit, disabled by default
+    pub fn with_risk_limits(mut self, risk_limits: RiskLimits) -> Self {
+        self.risk_limits = risk_limits;
+        self
+    }
+
+    /// replaces the cache of indicator series, e.g. to change how many series are kept in memory
+    pub fn with_indicator_cache(mut self, indicator_cache: IndicatorCache) -> Self {
+        self.indicator_cache = Some(indicator_cache);
+        self
+    }
+
+    /// strategies compute their indicators on every candle instead of taking them from the cache
+    pub fn without_indicator_cache(mut self) -> Self {
+        self.indicator_cache = None;
+        self
+    }
+
+    /// labels every candlestick with the classifier, so reports break the trades down by the regime they were entered in
+    pub fn with_regimes(mut self, mut classifier: RegimeClassifier) -> Self {
+        self.regimes = self.candlesticks.iter().map(|candle| classifier.next(candle)).collect();
+        self
+    }
+
+    /// regime of every candlestick, empty unless they were labeled with `with_regimes`
+    pub fn regimes(&self) -> &[Regime] {
+        &self.regimes
+    }
+
     // runs a backtest on the provided individual and returns the fitness score
-    pub fn run(&self, mode: RunMode, model: &mut SingleStrategy) -> f32 {
+    pub fn run<S: TradingStrategy>(&self, mode: RunMode, model: &mut S) -> f32 {
+        let report = self.run_report(mode, model);
+        if report.trade_count == 0 {
+            -self.initial_usd_balance
+        }else{
+            report.total_profit
+        }
+    }
+
+    // runs a backtest on the provided individual and returns the details of all ranges
+    pub fn run_report<S: TradingStrategy>(&self, mode: RunMode, model: &mut S) -> BacktestReport {
+        self.run_with_observers(mode, model, &mut [])
+    }
+
+    /// runs a backtest feeding every candlestick of the ranges to the event driven `Engine`,
+    /// each observer receives the events of all ranges
+    pub fn run_with_observers<S: TradingStrategy>(&self, mode: RunMode, model: &mut S, observers: &mut [&mut dyn Observer]) -> BacktestReport {
         let ranges = if mode == RunMode::Training {
             &self.training_ranges
         } else {
             &self.validation_ranges
         };
 
-        let mut trade_count = 0;
-        let mut total_profit: f32 = 0.0;
+        let mut report = BacktestReport::default();
 
         //iterating for each separate range
         for range in ranges {
-            
-            //trade control variables
-            let mut balance = self.initial_usd_balance;
-            let mut current_trade: Option<Trade> = None;
-            let mut current_stoploss: Option<f32> = None;
-            let mut current_takeprofit: Option<f32> = None;
+            // the strategy receives candles without trading until its indicators are ready
+            let warmup_end = (range.0 + model.warmup_period() as u32).min(range.1);
+            let mut regime_tracker = (!self.regimes.is_empty())
+                .then(|| RegimeTracker::new(&self.regimes[warmup_end as usize..range.1 as usize]));
+
+            let mut engine = Engine::new(self.engine_config());
+            for observer in observers.iter_mut() {
+                engine.add_observer(&mut **observer);
+            }
+            if let Some(tracker) = regime_tracker.as_mut() {
+                engine.add_observer(tracker);
+            }
 
+            if let Some(cache) = &self.indicator_cache {
+                let (start, end) = (range.0 as usize, range.1 as usize);
+                model.precompute(&cache.range(&self.candlesticks[start..end], start));
+            }
 
-            // properly initialize data for internal parameters of the strategy
-            for x in range.0..range.0+self.initialization_candles{
+            for x in range.0..warmup_end {
                 model.new_candlestick(&self.candlesticks[x as usize]);
             }
-            
-            for i in range.0+self.initialization_candles..range.1 {
-                let current_candle = &self.candlesticks[i as usize];
-                model.new_candlestick(current_candle);
-
-
-                if current_trade.is_none() { // there NO trade open
-                    let trade_start = model.should_start_trade();
-                    if trade_start.is_some() {
-                        let trade_start = trade_start.unwrap();
-                        let balance_debit = model.percentage_amount_per_trade() * balance;
-                        let units_to_trade = balance_debit  / current_candle.close;
-                        let mut new_trade = Trade::open(trade_start.0, units_to_trade,
-                            current_candle, model.leverage(), self.slipage_percentage, self.fee_percentage);
-                                                
-                        new_trade.takeprofit(trade_start.1);
-                        new_trade.stoploss(trade_start.2);
-                        current_trade = Some(new_trade);
-                        balance -= balance_debit;
-                    }
-                }
-                // there IS a trade open
-                else if current_trade.is_some() { 
-                    trade_count += 1;
-                    let trade = current_trade.as_mut().unwrap();
-                    
-                    if trade.is_liquidation_reached(current_candle) {
-                        let trade_loss = trade.close_on_liquidation(current_candle);
-                        total_profit += trade_loss;
-                        balance += trade_loss;
-                        current_trade = None;
-                    }else if trade.is_stoploss_reached(current_candle) {
-                        let trade_loss = trade.close_on_stoploss(current_candle);
-                        total_profit += trade_loss;
-                        balance += trade_loss;
-                        balance -= trade.total_fee_paid;
-                        current_trade = None;
-                    }else if trade.is_takeprofit_reached(current_candle) {
-                        let trade_profit = trade.close_on_takeprofit(current_candle);
-                        total_profit += trade_profit;
-                        balance += trade_profit;
-                        balance -= trade.total_fee_paid;
-                        current_trade = None;
-                    }                    
-                }
+
+            for i in warmup_end..range.1 {
+                engine.on_candle(model, &self.candlesticks[i as usize]);
             }
+
+            // trades still open at the end of the range are closed on the last candle so their result is counted
+            engine.close_all(model, &self.candlesticks[range.1 as usize - 1], ExitReason::EndOfRange);
             model.reset();
+
+            let mut range_report = engine.report();
+            if let Some(tracker) = &regime_tracker {
+                range_report.regimes = tracker.report().clone();
+            }
+            report.merge(&range_report);
         }
 
-        if trade_count == 0 {
-            -self.initial_usd_balance
-        }else{
-            total_profit
+        report
+    }
+
+    fn engine_config(&self) -> EngineConfig {
+        EngineConfig {
+            initial_usd_balance: self.initial_usd_balance,
+            slipage_percentage: self.slipage_percentage,
+            fee_percentage: self.fee_percentage,
+            position_limits: self.position_limits,
+            risk_limits: self.risk_limits,
         }
     }
-    
-    
+}
+
+/// Results of a backtest over all ranges of a `RunMode`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BacktestReport {
+    pub total_profit: f32, // sum of the results of all trades, fees already deducted
+    pub trade_count: u32,
+    pub max_concurrent_trades: usize,
+    pub peak_margin_usage: f32, // highest fraction of the equity used as margin at the same time
+    pub risk: RiskReport,
+    pub regimes: RegimeReport,
+}
+
+impl BacktestReport {
+    // adds the results of another range to this report
+    pub fn merge(&mut self, other: &BacktestReport) {
+        self.total_profit += other.total_profit;
+        self.trade_count += other.trade_count;
+        self.max_concurrent_trades = self.max_concurrent_trades.max(other.max_concurrent_trades);
+        self.peak_margin_usage = self.peak_margin_usage.max(other.peak_margin_usage);
+        self.risk.merge(&other.risk);
+        self.regimes.merge(&other.regimes);
+    }
 }
 
 #[cfg(test)]
     mod tests {
         use super::*;
+        use crate::backtest::risk_manager::RiskLimits;
+        use crate::backtest::trade::{Trade, TradeDirection};
+        use crate::backtest::strategy::{SingleStrategy, TradeAdjustment, STRATEGY_GENES, STRUCTURE_GENES};
+        use crate::backtest::rule_strategy::RuleStrategy;
+
+        fn candle(open: f32, high: f32, low: f32, close: f32) -> Candlestick {
+            Candlestick::new().open(open).high(high).low(low).close(close)
+        }
+
+        // opens a single long trade on the first candle and manages it with the configured rules
+        #[derive(Default)]
+        struct ScriptedStrategy {
+            trades_started: u32,
+            repeat_signal: bool, // signals a new long trade on every candle
+            scaled_out: bool,
+            scale_out_at: Option<f32>, // takes half of the position off and moves the stop to breakeven
+            close_at: Option<f32>, // closes the trade when the close reaches the given price
+            max_candles: Option<u32>,
+        }
+
+        impl TradingStrategy for ScriptedStrategy {
+            fn should_start_trade(&mut self) -> Option<(TradeDirection, f32, f32)> {
+                if self.trades_started > 0 && !self.repeat_signal {
+                    return None;
+                }
+                self.trades_started += 1;
+                Some((TradeDirection::Long, 1000.0, 1.0))
+            }
+
+            fn new_candlestick(&mut self, _candle: &Candlestick) {}
+
+            fn reset(&mut self) {}
+
+            fn position_size(&self, balance: f32, _price: f32, _stoploss: f32) -> f32 {
+                balance * 0.015
+            }
+
+            fn leverage(&self) -> u8 {
+                1
+            }
+
+            fn should_close_trade(&mut self, _trade: &Trade, candle: &Candlestick) -> bool {
+                self.close_at.is_some_and(|price| candle.close >= price)
+            }
+
+            fn max_candles_in_trade(&self) -> Option<u32> {
+                self.max_candles
+            }
+
+            fn adjust_trade(&mut self, _trade: &Trade, candle: &Candlestick) -> Vec<TradeAdjustment> {
+                match self.scale_out_at {
+                    Some(price) if !self.scaled_out && candle.close >= price => {
+                        self.scaled_out = true;
+                        vec![TradeAdjustment::ScaleOut(0.5), TradeAdjustment::StoplossToBreakeven]
+                    }
+                    _ => Vec::new(),
+                }
+            }
+        }
+
+        // candles moving from 100 up to 120 followed by a drop to 90
+        fn rally_and_drop() -> Vec<Candlestick> {
+            let mut candlesticks = vec![
+                candle(100.0, 100.0, 100.0, 100.0),
+                candle(100.0, 110.0, 100.0, 110.0),
+                candle(110.0, 120.0, 110.0, 120.0),
+                candle(120.0, 120.0, 90.0, 90.0),
+            ];
+            candlesticks.extend((0..4).map(|_| candle(90.0, 90.0, 90.0, 90.0)));
+            candlesticks
+        }
+
+        fn backtest_with(candlesticks: Vec<Candlestick>) -> Backtest {
+            Backtest::new(candlesticks, 2, 0.0, 0.0)
+        }
+
+        #[test]
+        fn partial_exit_and_breakeven_stop() {
+            let backtest_engine = backtest_with(rally_and_drop());
+            let mut strategy = ScriptedStrategy { scale_out_at: Some(110.0), ..Default::default() };
+            let profit = backtest_engine.run(RunMode::Training, &mut strategy);
+            
+            // half of the position closed at 110 and the other half stopped at the entry price
+            assert!((profit - 7.5).abs() < 1e-3, "expected a profit of 7.5 but was {}", profit);
+        }
+
+        #[test]
+        fn strategy_requested_exit() {
+            let backtest_engine = backtest_with(rally_and_drop());
+            let mut strategy = ScriptedStrategy { close_at: Some(120.0), ..Default::default() };
+            let profit = backtest_engine.run(RunMode::Training, &mut strategy);
+            assert!((profit - 30.0).abs() < 1e-3, "expected a profit of 30 but was {}", profit);
+        }
+
+        #[test]
+        fn exit_after_max_candles_in_trade() {
+            let backtest_engine = backtest_with(rally_and_drop());
+            let mut strategy = ScriptedStrategy { max_candles: Some(1), ..Default::default() };
+            let profit = backtest_engine.run(RunMode::Training, &mut strategy);
+            assert!((profit - 15.0).abs() < 1e-3, "expected a profit of 15 but was {}", profit);
+        }
+
+        #[test]
+        fn open_trade_is_closed_at_the_end_of_the_range() {
+            let backtest_engine = backtest_with(rally_and_drop());
+            let mut strategy = ScriptedStrategy::default();
+            let profit = backtest_engine.run(RunMode::Training, &mut strategy);
+            assert!((profit + 15.0).abs() < 1e-3, "expected a loss of 15 but was {}", profit);
+        }
+
+        #[test]
+        fn pyramiding_up_to_the_position_limits() {
+            let limits = PositionLimits::single().max_open_trades(3).max_trades_per_direction(3);
+            let backtest_engine = backtest_with(rally_and_drop()).with_position_limits(limits);
+            let mut strategy = ScriptedStrategy { repeat_signal: true, ..Default::default() };
+            let report = backtest_engine.run_report(RunMode::Training, &mut strategy);
+
+            assert_eq!(report.trade_count, 3);
+            assert_eq!(report.max_concurrent_trades, 3);
+            assert!((report.peak_margin_usage - 0.0443).abs() < 1e-4, "unexpected margin usage {}", report.peak_margin_usage);
+        }
+
+        #[test]
+        fn drawdown_circuit_breaker_stops_new_trades() {
+            let limits = PositionLimits::single().max_open_trades(10).max_trades_per_direction(10);
+            let mut candlesticks = vec![candle(100.0, 100.0, 100.0, 100.0)];
+            candlesticks.extend((0..7).map(|_| candle(100.0, 100.0, 10.0, 10.0)));
+            let backtest_engine = backtest_with(candlesticks)
+                .with_position_limits(limits)
+                .with_risk_limits(RiskLimits::default().max_drawdown(0.01));
+            let mut strategy = ScriptedStrategy { repeat_signal: true, ..Default::default() };
+            let report = backtest_engine.run_report(RunMode::Training, &mut strategy);
+
+            // the open trade loses more than 1% of the equity on the second candle and is closed
+            assert_eq!(report.trade_count, 1);
+            assert_eq!(report.risk.halted_ranges, 1);
+            assert_eq!(report.risk.blocked_entries, 3);
+            assert!((report.total_profit + 135.0).abs() < 1e-3, "expected a loss of 135 but was {}", report.total_profit);
+        }
+
+        // rising prices with waves
+        fn trending_candlesticks() -> Vec<Candlestick> {
+            (0..1200).map(|i| {
+                let price = 100.0 * 1.002_f32.powi(i) * (1.0 + 0.05 * (i as f32 / 3.0).sin());
+                candle(price, price + 2.0, price - 2.0, price + 1.0)
+            }).collect()
+        }
+
+        // a strategy that trades on the trend, see the paper trading tests
+        fn trending_cromossome() -> Vec<f32> {
+            let mut trending = vec![0.5; STRATEGY_GENES + STRUCTURE_GENES];
+            for (gene, value) in [(0, 0.0), (1, 0.0), (15, 1.0), (28, 1.0), (29, 1.0), (31, 0.0), (32, 0.0), (36, 0.15), (37, 0.0), (38, 0.15), (39, 0.0)] {
+                trending[gene] = value;
+            }
+            // both rules only check the distance between their sma and ema
+            trending[STRATEGY_GENES..].copy_from_slice(&[0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0]);
+            trending
+        }
+
+        #[test]
+        fn cached_indicators_give_the_same_results() {
+            let candlesticks = trending_candlesticks();
+            let cached = Backtest::new(candlesticks.clone(), 4, 0.0, 0.001);
+            let uncached = Backtest::new(candlesticks, 4, 0.0, 0.001).without_indicator_cache();
+
+            // the trending strategy and a few arbitrary ones
+            let mut cromossomes = vec![trending_cromossome()];
+            cromossomes.extend((0..8).map(|seed| (0..STRATEGY_GENES).map(|i| ((i * 7 + seed * 13) % 20) as f32 / 19.0).collect::<Vec<f32>>()));
+
+            let mut traded = false;
+            for cromossome in cromossomes {
+                let cached_report = cached.run_report(RunMode::Training, &mut SingleStrategy::decode(&cromossome));
+                let uncached_report = uncached.run_report(RunMode::Training, &mut SingleStrategy::decode(&cromossome));
+                assert_eq!(cached_report, uncached_report);
+                traded |= cached_report.trade_count > 0;
+            }
+            assert!(traded);
+            assert!(!cached.indicator_cache.as_ref().unwrap().is_empty());
+        }
+
+        #[test]
+        fn report_broken_down_by_regime() {
+            let backtest_engine = Backtest::new(trending_candlesticks(), 4, 0.0, 0.001).with_regimes(RegimeClassifier::default());
+            assert_eq!(backtest_engine.regimes().len(), 1200);
+            let report = backtest_engine.run_report(RunMode::Training, &mut SingleStrategy::decode(&trending_cromossome()));
+            assert!(report.trade_count > 0);
+
+            let by_regime: Vec<_> = Regime::ALL.iter().map(|regime| *report.regimes.get(*regime)).collect();
+            assert_eq!(by_regime.iter().map(|stats| stats.trade_count).sum::<u32>(), report.trade_count);
+            let profit: f32 = by_regime.iter().map(|stats| stats.total_profit).sum();
+            assert!((profit - report.total_profit).abs() < 1e-2, "{} != {}", profit, report.total_profit);
+
+            // without labels there is no breakdown
+            let unlabeled = Backtest::new(trending_candlesticks(), 4, 0.0, 0.001);
+            let report = unlabeled.run_report(RunMode::Training, &mut SingleStrategy::decode(&trending_cromossome()));
+            assert_eq!(report.regimes, RegimeReport::default());
+        }
+
+        #[test]
+        fn regime_filter_gates_entries() {
+            let backtest_engine = Backtest::new(trending_candlesticks(), 4, 0.0, 0.001);
+            let mut strategy = SingleStrategy::decode(&trending_cromossome()).regime_filter(&[], &[]);
+            assert_eq!(backtest_engine.run_report(RunMode::Training, &mut strategy).trade_count, 0);
+
+            let mut strategy = SingleStrategy::decode(&trending_cromossome()).regime_filter(&Regime::ALL, &Regime::ALL);
+            assert!(backtest_engine.run_report(RunMode::Training, &mut strategy).trade_count > 0);
+        }
+
+        #[test]
+        fn strategy_from_a_text_rule() {
+            let backtest_engine = Backtest::new(trending_candlesticks(), 4, 0.0, 0.001);
+            let mut strategy = RuleStrategy::from_json(r#"{"long_entry": "crosses_above(ema(5), ema(20)) and rsi(14) < 70"}"#).unwrap();
+            let report = backtest_engine.run_report(RunMode::Training, &mut strategy);
+            assert!(report.trade_count > 0);
+
+            let mut never = RuleStrategy::from_json(r#"{"long_entry": "close < 0"}"#).unwrap();
+            assert_eq!(backtest_engine.run_report(RunMode::Training, &mut never).trade_count, 0);
+        }
 
         #[test]
         fn split_candlesticks_for_validation_and_training(){
@@ -0,0 +1,439 @@
+use crate::candlestick::Candlestick;
+use crate::backtest::trade::{Trade, TradeDirection};
+use crate::backtest::order::{Fill, Order, OrderBook, OrderId, OrderKind, OrderSide};
+use crate::backtest::position_manager::{PositionId, PositionLimits, PositionManager};
+use crate::backtest::risk_manager::{RiskLimits, RiskManager};
+use crate::backtest::strategy::{TradingStrategy, TradeAdjustment};
+use crate::backtest::BacktestReport;
+use crate::backtest::regime_report::RegimeReport;
+
+/// Why a trade was closed
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum ExitReason {
+    Liquidation,
+    Stoploss,
+    Takeprofit,
+    Strategy, // requested by the strategy or by its maximum holding time
+    RiskHalt, // closed by the drawdown circuit breaker
+    EndOfRange,
+}
+
+/// Everything that happens inside the `Engine`, dispatched in order to the strategy and to every `Observer`
+#[derive(Debug)]
+pub enum Event<'a> {
+    /// a new candlestick was received, sent after the strategy interpreted it with `new_candlestick`
+    Candle(&'a Candlestick),
+    OrderSubmitted(&'a Order),
+    /// the order was cancelled, expired or had its one-cancels-other partner filled
+    OrderCancelled(OrderId),
+    OrderFilled(&'a Fill),
+    PositionOpened { id: PositionId, trade: &'a Trade },
+    /// the size, stoploss or takeprofit of the position changed
+    PositionChanged { id: PositionId, trade: &'a Trade },
+    PositionClosed { id: PositionId, trade: &'a Trade, result: f32, reason: ExitReason },
+}
+
+/// Receives the events of the engine without being able to change it, e.g. custom analytics or logging
+pub trait Observer {
+    fn on_event(&mut self, event: &Event);
+}
+
+/// Execution settings shared by every run of the engine
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EngineConfig {
+    pub initial_usd_balance: f32,
+    pub slipage_percentage: f32, // amount of price change on each trade
+    pub fee_percentage: f32, // percentage of the price change that will be charged as fee
+    pub position_limits: PositionLimits,
+    pub risk_limits: RiskLimits,
+}
+
+// entry order waiting in the order book with the takeprofit and stoploss of the trade it opens
+struct PendingEntry {
+    order_id: OrderId,
+    direction: TradeDirection,
+    takeprofit: f32,
+    stoploss: f32,
+}
+
+/// Event driven execution of a `TradingStrategy`, candlesticks are pushed one at a time with `on_candle`
+/// so the same engine runs over historical data or over a live feed
+pub struct Engine<'o> {
+    config: EngineConfig,
+    balance: f32,
+    total_profit: f32,
+    trade_count: u32,
+    positions: PositionManager,
+    order_book: OrderBook,
+    risk: RiskManager,
+    pending_entries: Vec<PendingEntry>,
+    stopped_out: bool, // a trade was closed by its stoploss or liquidated on the current candle
+    observers: Vec<&'o mut dyn Observer>,
+}
+
+// sends the event to the strategy and then to every observer
+fn dispatch<S: TradingStrategy>(model: &mut S, observers: &mut [&mut dyn Observer], event: Event) {
+    model.on_event(&event);
+    for observer in observers.iter_mut() {
+        observer.on_event(&event);
+    }
+}
+
+impl<'o> Engine<'o> {
+    pub fn new(config: EngineConfig) -> Self {
+        Self {
+            config,
+            balance: config.initial_usd_balance,
+            total_profit: 0.0,
+            trade_count: 0,
+            positions: PositionManager::new(config.position_limits),
+            order_book: OrderBook::new(),
+            risk: RiskManager::new(config.risk_limits, config.initial_usd_balance),
+            pending_entries: Vec::new(),
+            stopped_out: false,
+            observers: Vec::new(),
+        }
+    }
+
+    pub fn add_observer(&mut self, observer: &'o mut dyn Observer) {
+        self.observers.push(observer);
+    }
+
+    pub fn balance(&self) -> f32 {
+        self.balance
+    }
+
+    pub fn positions(&self) -> &PositionManager {
+        &self.positions
+    }
+
+    pub fn order_book(&self) -> &OrderBook {
+        &self.order_book
+    }
+
+    // details of everything processed by the engine so far
+    pub fn report(&self) -> BacktestReport {
+        BacktestReport {
+            total_profit: self.total_profit,
+            trade_count: self.trade_count,
+            max_concurrent_trades: self.positions.max_concurrent_trades(),
+            peak_margin_usage: self.positions.peak_margin_usage(),
+            risk: self.risk.report().clone(),
+            regimes: RegimeReport::default(),
+        }
+    }
+
+    /// processes a new candlestick: liquidations, pending orders, trade adjustments and exits, risk limits and new entries
+    pub fn on_candle<S: TradingStrategy>(&mut self, model: &mut S, candle: &Candlestick) {
+        model.new_candlestick(candle);
+        dispatch(model, &mut self.observers, Event::Candle(candle));
+
+        // liquidation happens before any order placed on the book can be filled
+        for id in self.positions.ids() {
+            let trade = &self.positions.get(id).unwrap().trade;
+            if trade.is_liquidation_reached(candle) {
+                let liquidation_price = trade.liquidation_price;
+                self.close_position(model, id, candle, liquidation_price, ExitReason::Liquidation);
+            }
+        }
+
+        // orders placed on previous candles
+        for fill in self.order_book.process(candle) {
+            dispatch(model, &mut self.observers, Event::OrderFilled(&fill));
+            if let (true, Some(id)) = (fill.reduce_only, fill.position) {
+                let reason = if fill.kind.is_stop() { ExitReason::Stoploss } else { ExitReason::Takeprofit };
+                self.close_position(model, id, candle, fill.price, reason);
+            } else if let Some(index) = self.pending_entries.iter().position(|entry| entry.order_id == fill.order_id) {
+                let entry = self.pending_entries.remove(index);
+                if self.risk.allow_entry() {
+                    self.open_trade(model, entry.direction, candle, fill.price, entry.takeprofit, entry.stoploss);
+                }
+            }
+        }
+        self.dispatch_cancelled(model);
+
+        for id in self.positions.ids() {
+            self.manage_position(model, id, candle);
+        }
+
+        // account guardrails, a drawdown circuit breaker closes every open trade
+        if self.risk.new_candlestick(candle, self.positions.equity(self.balance, candle)) {
+            self.close_all(model, candle, ExitReason::RiskHalt);
+            self.pending_entries.clear();
+        }
+        if self.stopped_out {
+            self.risk.trade_stopped_out();
+            self.stopped_out = false;
+        }
+
+        if self.pending_entries.is_empty() {
+            if let Some((direction, takeprofit, stoploss)) = model.should_start_trade() {
+                let (kind, time_in_force) = model.entry_order(direction);
+                if self.risk.allow_entry() {
+                    if kind == OrderKind::Market {
+                        self.open_trade(model, direction, candle, candle.close, takeprofit, stoploss);
+                    } else if self.positions.can_open(direction, 0.0, self.balance) {
+                        let order = Order::new(OrderSide::entry(direction), kind).time_in_force(time_in_force);
+                        let order_id = self.submit(model, order);
+                        self.pending_entries.push(PendingEntry { order_id, direction, takeprofit, stoploss });
+                    }
+                }
+            }
+        }
+
+        // entry orders that expired without being filled
+        let order_book = &self.order_book;
+        self.pending_entries.retain(|entry| order_book.get(entry.order_id).is_some());
+        self.positions.record_margin_usage(self.balance);
+    }
+
+    /// closes every open trade and cancels every pending order on the given candle
+    pub fn close_all<S: TradingStrategy>(&mut self, model: &mut S, candle: &Candlestick, reason: ExitReason) {
+        for id in self.positions.ids() {
+            self.close_position(model, id, candle, candle.close, reason);
+        }
+        self.order_book.cancel_all();
+        self.dispatch_cancelled(model);
+    }
+
+    // adjustments and exits requested by the strategy on a open trade
+    fn manage_position<S: TradingStrategy>(&mut self, model: &mut S, id: PositionId, candle: &Candlestick) {
+        let position = self.positions.get_mut(id).unwrap();
+        position.trade.candles_open += 1;
+        let adjustments = model.adjust_trade(&position.trade, candle);
+        let mut exits_changed = false;
+        let mut position_changed = false;
+        let mut close_trade = false;
+        for adjustment in adjustments {
+            let position = self.positions.get_mut(id).unwrap();
+            position_changed = true;
+            match adjustment {
+                TradeAdjustment::ScaleIn(fraction) => {
+                    let balance_debit = fraction * self.balance;
+                    position.trade.increase_position(candle, balance_debit);
+                    position.margin += balance_debit;
+                    self.balance -= balance_debit;
+                }
+                TradeAdjustment::ScaleOut(fraction) if fraction < 1.0 => {
+                    let size_decrease = position.trade.open_position_size() * fraction;
+                    position.trade.decrease_position(candle.close, size_decrease);
+                }
+                TradeAdjustment::ScaleOut(_) => {
+                    close_trade = true;
+                    break;
+                }
+                TradeAdjustment::MoveStoploss(price) => {
+                    position.trade.stoploss(price);
+                    exits_changed = true;
+                }
+                TradeAdjustment::MoveTakeprofit(price) => {
+                    position.trade.takeprofit(price);
+                    exits_changed = true;
+                }
+                TradeAdjustment::StoplossToBreakeven => {
+                    position.trade.stoploss_to_breakeven();
+                    exits_changed = true;
+                }
+            }
+        }
+
+        // exits requested by the strategy or by the maximum holding time
+        let trade = &self.positions.get(id).unwrap().trade;
+        let max_candles_reached = model.max_candles_in_trade().is_some_and(|max| trade.candles_open >= max);
+        if close_trade || max_candles_reached || model.should_close_trade(trade, candle) {
+            self.close_position(model, id, candle, candle.close, ExitReason::Strategy);
+            return;
+        }
+
+        if position_changed {
+            let trade = &self.positions.get(id).unwrap().trade;
+            dispatch(model, &mut self.observers, Event::PositionChanged { id, trade });
+        }
+        if exits_changed {
+            self.order_book.cancel_position(id);
+            self.dispatch_cancelled(model);
+            self.place_exit_orders(model, id, candle);
+        }
+    }
+
+    // opens a trade filled at the given price when the position limits allow it
+    fn open_trade<S: TradingStrategy>(&mut self, model: &mut S, direction: TradeDirection, candle: &Candlestick,
+        price: f32, takeprofit: f32, stoploss: f32) {
+        let balance_debit = model.position_size(self.balance, price, stoploss).clamp(0.0, self.balance.max(0.0));
+        if balance_debit <= 0.0 || !self.positions.can_open(direction, balance_debit, self.balance) {
+            return;
+        }
+
+        let mut new_trade = Trade::open_at_price(direction, balance_debit, candle, price,
+            model.leverage(), self.config.slipage_percentage, self.config.fee_percentage);
+        new_trade.takeprofit(takeprofit);
+        new_trade.stoploss(stoploss);
+
+        let id = self.positions.open(new_trade, balance_debit);
+        self.balance -= balance_debit;
+        self.trade_count += 1;
+        self.risk.trade_opened();
+
+        let trade = &self.positions.get(id).unwrap().trade;
+        dispatch(model, &mut self.observers, Event::PositionOpened { id, trade });
+        self.place_exit_orders(model, id, candle);
+    }
+
+    // places the takeprofit and stoploss (or trailing stop) of a position as a one-cancels-other bracket
+    fn place_exit_orders<S: TradingStrategy>(&mut self, model: &mut S, id: PositionId, candle: &Candlestick) {
+        let trade = &self.positions.get(id).unwrap().trade;
+        let side = OrderSide::exit(trade.side);
+        let stop_order = match (model.trailing_stop(trade.side), trade.stoploss_price()) {
+            (Some(callback_rate), _) => Some(Order::new(side, OrderKind::TrailingStop { callback_rate }).trailing_from(candle.close)),
+            (None, Some(stop)) => Some(Order::new(side, OrderKind::StopMarket { stop })),
+            (None, None) => None,
+        };
+        let takeprofit_order = trade.takeprofit_price().map(|price| Order::new(side, OrderKind::Limit { price }));
+
+        match (stop_order, takeprofit_order) {
+            (Some(stop_order), Some(takeprofit_order)) => {
+                let (first, second) = self.order_book.submit_oco(stop_order.closing(id), takeprofit_order.closing(id));
+                dispatch(model, &mut self.observers, Event::OrderSubmitted(self.order_book.get(first).unwrap()));
+                dispatch(model, &mut self.observers, Event::OrderSubmitted(self.order_book.get(second).unwrap()));
+            }
+            (Some(order), None) | (None, Some(order)) => {
+                self.submit(model, order.closing(id));
+            }
+            (None, None) => {}
+        }
+    }
+
+    fn submit<S: TradingStrategy>(&mut self, model: &mut S, order: Order) -> OrderId {
+        let order_id = self.order_book.submit(order);
+        dispatch(model, &mut self.observers, Event::OrderSubmitted(self.order_book.get(order_id).unwrap()));
+        order_id
+    }
+
+    // closes the position at the given price, cancels its remaining orders and returns its margin and result to the balance
+    fn close_position<S: TradingStrategy>(&mut self, model: &mut S, id: PositionId, candle: &Candlestick, price: f32, reason: ExitReason) {
+        if let Some(mut position) = self.positions.close(id) {
+            let result = position.trade.close_at_price(candle, price);
+            self.balance += position.margin + result;
+            self.total_profit += result;
+            if reason == ExitReason::Stoploss || reason == ExitReason::Liquidation {
+                self.stopped_out = true;
+            }
+
+            self.order_book.cancel_position(id);
+            self.dispatch_cancelled(model);
+            dispatch(model, &mut self.observers, Event::PositionClosed { id, trade: &position.trade, result, reason });
+        }
+    }
+
+    fn dispatch_cancelled<S: TradingStrategy>(&mut self, model: &mut S) {
+        for order_id in self.order_book.take_cancelled() {
+            dispatch(model, &mut self.observers, Event::OrderCancelled(order_id));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // opens a long trade on the first candle with a takeprofit at 110 and a stoploss at 90
+    struct SingleEntry {
+        started: bool,
+        closed_results: Vec<f32>,
+    }
+
+    impl TradingStrategy for SingleEntry {
+        fn should_start_trade(&mut self) -> Option<(TradeDirection, f32, f32)> {
+            if self.started {
+                return None;
+            }
+            self.started = true;
+            Some((TradeDirection::Long, 110.0, 90.0))
+        }
+
+        fn new_candlestick(&mut self, _candle: &Candlestick) {}
+
+        fn reset(&mut self) {}
+
+        fn position_size(&self, balance: f32, _price: f32, _stoploss: f32) -> f32 {
+            balance * 0.1
+        }
+
+        fn leverage(&self) -> u8 {
+            1
+        }
+
+        fn on_event(&mut self, event: &Event) {
+            if let Event::PositionClosed { result, .. } = event {
+                self.closed_results.push(*result);
+            }
+        }
+    }
+
+    // records the name of every event received
+    #[derive(Default)]
+    struct EventLog {
+        events: Vec<String>,
+    }
+
+    impl Observer for EventLog {
+        fn on_event(&mut self, event: &Event) {
+            let name = match event {
+                Event::Candle(_) => "candle",
+                Event::OrderSubmitted(_) => "order submitted",
+                Event::OrderCancelled(_) => "order cancelled",
+                Event::OrderFilled(_) => "order filled",
+                Event::PositionOpened { .. } => "position opened",
+                Event::PositionChanged { .. } => "position changed",
+                Event::PositionClosed { .. } => "position closed",
+            };
+            self.events.push(name.to_string());
+        }
+    }
+
+    fn config() -> EngineConfig {
+        EngineConfig {
+            initial_usd_balance: 1000.0,
+            slipage_percentage: 0.0,
+            fee_percentage: 0.0,
+            position_limits: PositionLimits::default(),
+            risk_limits: RiskLimits::default(),
+        }
+    }
+
+    #[test]
+    fn events_are_dispatched_in_order() {
+        let mut log = EventLog::default();
+        let mut strategy = SingleEntry { started: false, closed_results: Vec::new() };
+        let mut engine = Engine::new(config());
+        engine.add_observer(&mut log);
+
+        engine.on_candle(&mut strategy, &Candlestick::new().open(100.0).high(100.0).low(100.0).close(100.0));
+        engine.on_candle(&mut strategy, &Candlestick::new().open(100.0).high(111.0).low(99.0).close(109.0));
+        let report = engine.report();
+        drop(engine);
+
+        assert_eq!(log.events, vec![
+            "candle", "position opened", "order submitted", "order submitted",
+            "candle", "order filled", "order cancelled", "position closed",
+        ]);
+        assert_eq!(strategy.closed_results, vec![10.0]);
+        assert_eq!(report.trade_count, 1);
+        assert_eq!(report.total_profit, 10.0);
+    }
+
+    #[test]
+    fn close_all_settles_the_balance() {
+        let mut strategy = SingleEntry { started: false, closed_results: Vec::new() };
+        let mut engine = Engine::new(config());
+        let candle = Candlestick::new().open(100.0).high(100.0).low(100.0).close(100.0);
+        engine.on_candle(&mut strategy, &candle);
+        assert_eq!(engine.balance(), 900.0);
+        assert_eq!(engine.order_book().pending().len(), 2);
+
+        engine.close_all(&mut strategy, &Candlestick::new().close(105.0), ExitReason::EndOfRange);
+        assert_eq!(engine.balance(), 1005.0);
+        assert!(engine.positions().is_empty());
+        assert!(engine.order_book().pending().is_empty());
+    }
+}
@@ -0,0 +1,285 @@
+use std::fmt;
+
+use crate::utils::map_range;
+
+/// How a gene between 0.0 and 1.0 is mapped to the range of its value
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Scale {
+    Linear,
+    /// evenly spaced ratios instead of differences, the range must be positive
+    Logarithmic,
+    /// linear value truncated to a whole number, e.g. a period
+    Integer,
+    /// index of one of the options, each one covering the same share of the gene
+    Choice(&'static [&'static str]),
+}
+
+/// Meaning of a single gene of a cromossome
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Gene {
+    pub name: &'static str,
+    pub range: (f32, f32),
+    pub scale: Scale,
+}
+
+impl Gene {
+    pub const fn linear(name: &'static str, min: f32, max: f32) -> Self {
+        Self { name, range: (min, max), scale: Scale::Linear }
+    }
+
+    /// panics when the range isn't positive, so a schema declared as a constant with such a range doesn't compile
+    pub const fn logarithmic(name: &'static str, min: f32, max: f32) -> Self {
+        assert!(min > 0.0 && max > 0.0, "the range of a logarithmic gene must be positive");
+        Self { name, range: (min, max), scale: Scale::Logarithmic }
+    }
+
+    pub const fn integer(name: &'static str, min: f32, max: f32) -> Self {
+        Self { name, range: (min, max), scale: Scale::Integer }
+    }
+
+    pub const fn choice(name: &'static str, options: &'static [&'static str]) -> Self {
+        Self { name, range: (0.0, (options.len() - 1) as f32), scale: Scale::Choice(options) }
+    }
+
+    /// value of the gene, the index of the option for a `Scale::Choice`
+    pub fn decode(&self, gene: f32) -> f32 {
+        match self.scale {
+            Scale::Linear => map_range(self.range, gene),
+            Scale::Logarithmic => self.range.0 * (self.range.1 / self.range.0).powf(gene),
+            Scale::Integer => map_range(self.range, gene).floor(),
+            Scale::Choice(options) => ((gene * options.len() as f32) as usize).min(options.len() - 1) as f32,
+        }
+    }
+
+    fn format(&self, value: f32) -> String {
+        match self.scale {
+            Scale::Linear | Scale::Logarithmic => format!("{:.4}", value),
+            Scale::Integer => format!("{}", value),
+            Scale::Choice(options) => options[value as usize].to_string(),
+        }
+    }
+}
+
+/// Rule between the decoded values of two genes of the same section
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Constraint {
+    /// the value of the first gene is never above the second one, the two values are swapped otherwise
+    Ordered(&'static str, &'static str),
+}
+
+/// Consecutive genes decoding one part of a strategy, e.g. the long rule. Several sections can share a name
+/// when the genes of a part are spread in the cromossome, as long as their gene names are different
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Section {
+    pub name: &'static str,
+    pub genes: &'static [Gene],
+    pub constraints: &'static [Constraint],
+}
+
+impl Section {
+    pub const fn new(name: &'static str, genes: &'static [Gene]) -> Self {
+        Self { name, genes, constraints: &[] }
+    }
+
+    pub const fn constraints(mut self, constraints: &'static [Constraint]) -> Self {
+        self.constraints = constraints;
+        self
+    }
+}
+
+/// amount of genes of the sections, usable in constants
+pub const fn genes_in(sections: &[Section]) -> usize {
+    let mut total = 0;
+    let mut i = 0;
+    while i < sections.len() {
+        total += sections[i].genes.len();
+        i += 1;
+    }
+    total
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SchemaError {
+    /// the cromossome doesn't have the amount of genes of any accepted layout
+    Length { expected: Vec<usize>, found: usize },
+    /// a gene is outside of 0.0..=1.0
+    OutOfRange { gene: String, value: f32 },
+}
+
+impl fmt::Display for SchemaError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SchemaError::Length { expected, found } => {
+                let mut lengths: Vec<String> = expected.iter().map(usize::to_string).collect();
+                let last = lengths.pop().unwrap_or_default();
+                let expected = if lengths.is_empty() { last } else { format!("{} or {}", lengths.join(", "), last) };
+                write!(f, "the cromossome must have {} genes, but it had {}", expected, found)
+            }
+            SchemaError::OutOfRange { gene, value } => write!(f, "the gene {} must be between 0.0 and 1.0, but it was {}", gene, value),
+        }
+    }
+}
+
+impl std::error::Error for SchemaError {}
+
+/// Layout of a cromossome as a sequence of sections, the single description of its size, of the meaning
+/// and valid values of each gene and of how a decoded cromossome is printed
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct GeneSchema {
+    sections: Vec<Section>,
+}
+
+impl GeneSchema {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// appends the genes of the sections to the cromossome
+    pub fn sections(mut self, sections: &[Section]) -> Self {
+        self.sections.extend_from_slice(sections);
+        self
+    }
+
+    /// amount of genes of the cromossome
+    pub fn size(&self) -> usize {
+        genes_in(&self.sections)
+    }
+
+    /// checks the amount of genes and that every gene is between 0.0 and 1.0
+    pub fn validate(&self, cromossome: &[f32]) -> Result<(), SchemaError> {
+        if cromossome.len() != self.size() {
+            return Err(SchemaError::Length { expected: vec![self.size()], found: cromossome.len() });
+        }
+        let genes = self.sections.iter().flat_map(|section| section.genes.iter().map(move |gene| (section.name, gene)));
+        for ((section, gene), value) in genes.zip(cromossome) {
+            if !(0.0..=1.0).contains(value) {
+                return Err(SchemaError::OutOfRange { gene: format!("{}.{}", section, gene.name), value: *value });
+            }
+        }
+        Ok(())
+    }
+
+    /// values of the genes of a valid cromossome with the constraints of the sections applied
+    pub fn decode(&self, cromossome: &[f32]) -> Result<DecodedGenes, SchemaError> {
+        self.validate(cromossome)?;
+        let mut values = Vec::with_capacity(cromossome.len());
+        let mut genes = cromossome.iter();
+        for section in &self.sections {
+            let start = values.len();
+            values.extend(section.genes.iter().zip(genes.by_ref()).map(|(gene, value)| (section.name, *gene, gene.decode(*value))));
+            for constraint in section.constraints {
+                let Constraint::Ordered(low, high) = *constraint;
+                let position = |name: &str| start + section.genes.iter().position(|gene| gene.name == name)
+                    .unwrap_or_else(|| panic!("the constraint of the section {} refers to an unknown gene {}", section.name, name));
+                let (low, high) = (position(low), position(high));
+                if values[low].2 > values[high].2 {
+                    let value = values[low].2;
+                    values[low].2 = values[high].2;
+                    values[high].2 = value;
+                }
+            }
+        }
+        Ok(DecodedGenes { values })
+    }
+}
+
+/// Values of a decoded cromossome, read by section and gene name. Prints one gene per line, e.g. `long.rsi_period: 30`
+#[derive(Debug, Clone, PartialEq)]
+pub struct DecodedGenes {
+    values: Vec<(&'static str, Gene, f32)>,
+}
+
+impl DecodedGenes {
+    pub fn contains(&self, section: &str, gene: &str) -> bool {
+        self.values.iter().any(|(name, candidate, _)| *name == section && candidate.name == gene)
+    }
+
+    /// decoded value of a gene, asking for a gene missing from the schema is a bug of the decoder so it panics
+    pub fn value(&self, section: &str, gene: &str) -> f32 {
+        self.values.iter()
+            .find(|(name, candidate, _)| *name == section && candidate.name == gene)
+            .map(|(_, _, value)| *value)
+            .unwrap_or_else(|| panic!("the schema has no gene {}.{}", section, gene))
+    }
+
+    pub fn period(&self, section: &str, gene: &str) -> usize {
+        self.value(section, gene) as usize
+    }
+
+    /// the option picked by a `Scale::Choice` gene, `options` are in the order of the names of the gene
+    pub fn choice<T: Copy>(&self, section: &str, gene: &str, options: &[T]) -> T {
+        options[self.period(section, gene)]
+    }
+}
+
+impl fmt::Display for DecodedGenes {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let lines: Vec<String> = self.values.iter()
+            .map(|(section, gene, value)| format!("{}.{}: {}", section, gene.name, gene.format(*value)))
+            .collect();
+        write!(f, "{}", lines.join("\n"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BAND: [Gene; 3] = [
+        Gene::integer("period", 2.0, 100.0),
+        Gene::linear("low", 0.0, 100.0),
+        Gene::linear("high", 0.0, 100.0),
+    ];
+    const KIND: [Gene; 2] = [
+        Gene::choice("kind", &["sma", "ema", "wma"]),
+        Gene::linear("multiplier", 0.0, 10.0),
+    ];
+    const SECTIONS: [Section; 2] = [
+        Section::new("band", &BAND).constraints(&[Constraint::Ordered("low", "high")]),
+        Section::new("band", &KIND),
+    ];
+
+    #[test]
+    fn test_decode_scales() {
+        assert_eq!(Gene::integer("period", 2.0, 100.0).decode(0.3), 31.0);
+        assert_eq!(Gene::linear("target", -1000.0, 1000.0).decode(0.3), -400.0);
+        let multiplier = Gene::logarithmic("multiplier", 0.1, 10.0);
+        assert!((multiplier.decode(0.5) - 1.0).abs() < 1e-6);
+        assert_eq!(multiplier.format(multiplier.decode(0.5)), "1.0000");
+        let choice = Gene::choice("kind", &["sma", "ema", "wma"]);
+        assert_eq!([0.0, 0.33, 0.34, 0.99, 1.0].map(|gene| choice.decode(gene)), [0.0, 0.0, 1.0, 2.0, 2.0]);
+        assert_eq!(choice.range, (0.0, 2.0));
+    }
+
+    #[test]
+    #[should_panic(expected = "the range of a logarithmic gene must be positive")]
+    fn test_logarithmic_range() {
+        Gene::logarithmic("multiplier", 0.0, 10.0);
+    }
+
+    #[test]
+    fn test_schema() {
+        let schema = GeneSchema::new().sections(&SECTIONS);
+        assert_eq!(schema.size(), 5);
+        assert_eq!(genes_in(&SECTIONS), 5);
+
+        let genes = schema.decode(&[0.3, 0.8, 0.2, 0.5, 1.0]).unwrap();
+        assert_eq!(genes.period("band", "period"), 31);
+        // the bounds are swapped to keep the low one below the high one
+        assert_eq!((genes.value("band", "low"), genes.value("band", "high")), (20.0, 80.0));
+        assert_eq!(genes.choice("band", "kind", &['s', 'e', 'w']), 'e');
+        assert!(genes.contains("band", "multiplier"));
+        assert!(!genes.contains("other", "multiplier"));
+        assert_eq!(genes.to_string(), "band.period: 31\nband.low: 20.0000\nband.high: 80.0000\nband.kind: ema\nband.multiplier: 10.0000");
+    }
+
+    #[test]
+    fn test_validate() {
+        let schema = GeneSchema::new().sections(&SECTIONS);
+        assert_eq!(schema.validate(&[0.5; 3]), Err(SchemaError::Length { expected: vec![5], found: 3 }));
+        let error = schema.decode(&[0.5, 0.5, 0.5, 1.5, 0.5]).unwrap_err();
+        assert_eq!(error.to_string(), "the gene band.kind must be between 0.0 and 1.0, but it was 1.5");
+        let error = SchemaError::Length { expected: vec![42, 44, 56], found: 12 };
+        assert_eq!(error.to_string(), "the cromossome must have 42, 44 or 56 genes, but it had 12");
+    }
+}
@@ -0,0 +1,450 @@
+use crate::candlestick::Candlestick;
+use crate::backtest::trade::TradeDirection;
+use crate::backtest::position_manager::PositionId;
+
+pub type OrderId = u32;
+
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum OrderSide {
+    Buy,
+    Sell,
+}
+
+impl OrderSide {
+    // side of the order that opens (or increases) a trade in the given direction
+    pub fn entry(direction: TradeDirection) -> Self {
+        match direction {
+            TradeDirection::Long => OrderSide::Buy,
+            TradeDirection::Short => OrderSide::Sell,
+        }
+    }
+
+    // side of the order that closes (or reduces) a trade in the given direction
+    pub fn exit(direction: TradeDirection) -> Self {
+        match direction {
+            TradeDirection::Long => OrderSide::Sell,
+            TradeDirection::Short => OrderSide::Buy,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum OrderKind {
+    /// fills on the open of the next candle
+    Market,
+    /// fills at `price` or better
+    Limit { price: f32 },
+    /// becomes a market order once the price reaches `stop`
+    StopMarket { stop: f32 },
+    /// becomes a limit order at `limit` once the price reaches `stop`
+    StopLimit { stop: f32, limit: f32 },
+    /// stop that follows the price at a distance of `callback_rate` (0.01 = 1%) from the best price seen
+    TrailingStop { callback_rate: f32 },
+}
+
+impl OrderKind {
+    pub fn is_stop(&self) -> bool {
+        matches!(self, OrderKind::StopMarket { .. } | OrderKind::StopLimit { .. } | OrderKind::TrailingStop { .. })
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum TimeInForce {
+    GoodTillCancelled,
+    /// the order expires when it is not filled after the given amount of candles
+    GoodTillCandles(u32),
+    /// the order expires on the first candle that opens after the given timestamp
+    GoodTillTime(u64),
+}
+
+#[derive(Debug, Clone)]
+pub struct Order {
+    pub id: OrderId,
+    pub side: OrderSide,
+    pub kind: OrderKind,
+    pub time_in_force: TimeInForce,
+    pub reduce_only: bool, // the order can only close a open position
+    pub position: Option<PositionId>, // position closed by a reduce only order
+    oco_group: Option<u32>, // all orders of the same group are cancelled when one of them fills
+    candles_alive: u32,
+    triggered: bool, // the stop of a stop-limit order was reached
+    trailing_extreme: Option<f32>, // best price seen by a trailing stop since it was placed
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Fill {
+    pub order_id: OrderId,
+    pub side: OrderSide,
+    pub kind: OrderKind,
+    pub price: f32,
+    pub reduce_only: bool,
+    pub position: Option<PositionId>,
+    pub timestamp: u64,
+}
+
+impl Order {
+    pub fn new(side: OrderSide, kind: OrderKind) -> Self {
+        Self {
+            id: 0,
+            side,
+            kind,
+            time_in_force: TimeInForce::GoodTillCancelled,
+            reduce_only: false,
+            position: None,
+            oco_group: None,
+            candles_alive: 0,
+            triggered: false,
+            trailing_extreme: None,
+        }
+    }
+
+    pub fn time_in_force(mut self, time_in_force: TimeInForce) -> Self {
+        self.time_in_force = time_in_force;
+        self
+    }
+
+    pub fn reduce_only(mut self) -> Self {
+        self.reduce_only = true;
+        self
+    }
+
+    // reduce only order that closes the given position
+    pub fn closing(mut self, position: PositionId) -> Self {
+        self.reduce_only = true;
+        self.position = Some(position);
+        self
+    }
+
+    // price the trailing stop starts following, if not provided the open of the first processed candle is used
+    pub fn trailing_from(mut self, reference_price: f32) -> Self {
+        self.trailing_extreme = Some(reference_price);
+        self
+    }
+
+    // current stop price of a trailing stop order
+    pub fn trailing_stop_price(&self) -> Option<f32> {
+        match (self.kind, self.trailing_extreme) {
+            (OrderKind::TrailingStop { callback_rate }, Some(extreme)) => Some(match self.side {
+                OrderSide::Sell => extreme * (1.0 - callback_rate),
+                OrderSide::Buy => extreme * (1.0 + callback_rate),
+            }),
+            _ => None,
+        }
+    }
+
+    fn is_expired(&self, candle: &Candlestick) -> bool {
+        match self.time_in_force {
+            TimeInForce::GoodTillCancelled => false,
+            TimeInForce::GoodTillCandles(candles) => self.candles_alive >= candles,
+            TimeInForce::GoodTillTime(timestamp) => candle.open_time > timestamp,
+        }
+    }
+
+    // stop orders are checked before limit orders, when both could fill in the same candle the worst case is assumed
+    fn is_stop(&self) -> bool {
+        self.kind.is_stop()
+    }
+
+    // price the order is filled on the given candle, if it is filled at all.
+    // gaps are respected: a order that is crossed by the open is filled on the open
+    fn try_fill(&mut self, candle: &Candlestick) -> Option<f32> {
+        match self.kind {
+            OrderKind::Market => Some(candle.open),
+            OrderKind::Limit { price } => limit_fill(self.side, price, candle.open, candle),
+            OrderKind::StopMarket { stop } => stop_fill(self.side, stop, candle),
+            OrderKind::StopLimit { stop, limit } => {
+                let reference = if self.triggered {
+                    candle.open
+                } else {
+                    let trigger = stop_fill(self.side, stop, candle)?;
+                    self.triggered = true;
+                    trigger
+                };
+                limit_fill(self.side, limit, reference, candle)
+            }
+            OrderKind::TrailingStop { .. } => {
+                if self.trailing_extreme.is_none() {
+                    self.trailing_extreme = Some(candle.open);
+                }
+                let stop = self.trailing_stop_price().unwrap();
+                let fill = stop_fill(self.side, stop, candle);
+                if fill.is_none() {
+                    let extreme = self.trailing_extreme.unwrap();
+                    self.trailing_extreme = Some(match self.side {
+                        OrderSide::Sell => extreme.max(candle.high),
+                        OrderSide::Buy => extreme.min(candle.low),
+                    });
+                }
+                fill
+            }
+        }
+    }
+}
+
+// a buy limit fills when the price goes down to it, a sell limit when the price goes up to it
+fn limit_fill(side: OrderSide, price: f32, reference: f32, candle: &Candlestick) -> Option<f32> {
+    match side {
+        OrderSide::Buy if candle.low <= price => Some(price.min(reference)),
+        OrderSide::Sell if candle.high >= price => Some(price.max(reference)),
+        _ => None,
+    }
+}
+
+// a buy stop fills when the price goes up to it, a sell stop when the price goes down to it
+fn stop_fill(side: OrderSide, stop: f32, candle: &Candlestick) -> Option<f32> {
+    match side {
+        OrderSide::Buy if candle.high >= stop => Some(stop.max(candle.open)),
+        OrderSide::Sell if candle.low <= stop => Some(stop.min(candle.open)),
+        _ => None,
+    }
+}
+
+/// Simulated order book that keeps pending orders and fills them against the following candlesticks
+pub struct OrderBook {
+    pending: Vec<Order>,
+    next_id: OrderId,
+    next_oco_group: u32,
+    cancelled: Vec<OrderId>, // orders removed without a fill since the last call to `take_cancelled`
+}
+
+impl OrderBook {
+    pub fn new() -> Self {
+        Self {
+            pending: Vec::new(),
+            next_id: 1,
+            next_oco_group: 1,
+            cancelled: Vec::new(),
+        }
+    }
+
+    pub fn submit(&mut self, mut order: Order) -> OrderId {
+        order.id = self.next_id;
+        self.next_id += 1;
+        self.pending.push(order);
+        self.pending.last().unwrap().id
+    }
+
+    /// submits two orders where the fill of one cancels the other (e.g. a take profit and a stoploss bracket)
+    pub fn submit_oco(&mut self, mut first: Order, mut second: Order) -> (OrderId, OrderId) {
+        first.oco_group = Some(self.next_oco_group);
+        second.oco_group = Some(self.next_oco_group);
+        self.next_oco_group += 1;
+        (self.submit(first), self.submit(second))
+    }
+
+    pub fn cancel(&mut self, id: OrderId) -> bool {
+        let size = self.pending.len();
+        self.cancel_where(|order| order.id == id);
+        size != self.pending.len()
+    }
+
+    // cancels every order that closes the given position
+    pub fn cancel_position(&mut self, position: PositionId) {
+        self.cancel_where(|order| order.position == Some(position));
+    }
+
+    pub fn cancel_all(&mut self) {
+        self.cancel_where(|_| true);
+    }
+
+    /// ids of the orders cancelled, expired or removed by a one-cancels-other fill since the last call
+    pub fn take_cancelled(&mut self) -> Vec<OrderId> {
+        std::mem::take(&mut self.cancelled)
+    }
+
+    fn cancel_where<F: Fn(&Order) -> bool>(&mut self, predicate: F) {
+        let cancelled = &mut self.cancelled;
+        self.pending.retain(|order| {
+            if predicate(order) {
+                cancelled.push(order.id);
+                false
+            } else {
+                true
+            }
+        });
+    }
+
+    pub fn get(&self, id: OrderId) -> Option<&Order> {
+        self.pending.iter().find(|order| order.id == id)
+    }
+
+    pub fn pending(&self) -> &[Order] {
+        &self.pending
+    }
+
+    pub fn has_pending_entry(&self) -> bool {
+        self.pending.iter().any(|order| !order.reduce_only)
+    }
+
+    /// matches all pending orders against a new candlestick and returns the fills in the order they happened.
+    /// Expired orders are removed before matching and the OCO partners of filled orders are cancelled.
+    pub fn process(&mut self, candle: &Candlestick) -> Vec<Fill> {
+        self.cancel_where(|order| order.is_expired(candle));
+        self.pending.sort_by_key(|order| !order.is_stop());
+
+        let mut fills = Vec::new();
+        let mut cancelled_groups: Vec<u32> = Vec::new();
+        let mut i = 0;
+        while i < self.pending.len() {
+            let order = &mut self.pending[i];
+            if order.oco_group.is_some_and(|group| cancelled_groups.contains(&group)) {
+                let order = self.pending.remove(i);
+                self.cancelled.push(order.id);
+                continue;
+            }
+
+            if let Some(price) = order.try_fill(candle) {
+                let order = self.pending.remove(i);
+                if let Some(group) = order.oco_group {
+                    cancelled_groups.push(group);
+                }
+                fills.push(Fill {
+                    order_id: order.id,
+                    side: order.side,
+                    kind: order.kind,
+                    price,
+                    reduce_only: order.reduce_only,
+                    position: order.position,
+                    timestamp: candle.close_time,
+                });
+            } else {
+                order.candles_alive += 1;
+                i += 1;
+            }
+        }
+
+        self.cancel_where(|order| order.oco_group.is_some_and(|group| cancelled_groups.contains(&group)));
+        self.pending.sort_by_key(|order| order.id);
+        fills
+    }
+}
+
+impl Default for OrderBook {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candle(open: f32, high: f32, low: f32, close: f32) -> Candlestick {
+        Candlestick::new().open(open).high(high).low(low).close(close)
+    }
+
+    #[test]
+    fn limit_buy_fills_when_price_drops_to_it() {
+        let mut book = OrderBook::new();
+        book.submit(Order::new(OrderSide::Buy, OrderKind::Limit { price: 95.0 }));
+
+        assert!(book.process(&candle(100.0, 102.0, 96.0, 101.0)).is_empty());
+        let fills = book.process(&candle(101.0, 101.0, 94.0, 97.0));
+        assert_eq!(fills.len(), 1);
+        assert_eq!(fills[0].price, 95.0);
+        assert!(book.pending().is_empty());
+    }
+
+    #[test]
+    fn limit_buy_fills_on_the_open_after_a_gap() {
+        let mut book = OrderBook::new();
+        book.submit(Order::new(OrderSide::Buy, OrderKind::Limit { price: 95.0 }));
+        assert_eq!(book.process(&candle(90.0, 92.0, 89.0, 91.0))[0].price, 90.0);
+    }
+
+    #[test]
+    fn stop_market_sell_fills_when_price_drops_to_it() {
+        let mut book = OrderBook::new();
+        book.submit(Order::new(OrderSide::Sell, OrderKind::StopMarket { stop: 90.0 }));
+        assert!(book.process(&candle(100.0, 101.0, 91.0, 95.0)).is_empty());
+        assert_eq!(book.process(&candle(95.0, 96.0, 85.0, 86.0))[0].price, 90.0);
+    }
+
+    #[test]
+    fn stop_limit_waits_for_the_limit_after_triggering() {
+        let mut book = OrderBook::new();
+        book.submit(Order::new(OrderSide::Buy, OrderKind::StopLimit { stop: 105.0, limit: 103.0 }));
+
+        // triggered but never traded down to the limit
+        assert!(book.process(&candle(100.0, 106.0, 104.0, 105.5)).is_empty());
+        assert!(book.pending()[0].triggered);
+        assert_eq!(book.process(&candle(105.0, 105.0, 102.0, 103.0))[0].price, 103.0);
+    }
+
+    #[test]
+    fn trailing_stop_follows_the_highest_price() {
+        let mut book = OrderBook::new();
+        let id = book.submit(Order::new(OrderSide::Sell, OrderKind::TrailingStop { callback_rate: 0.1 }).trailing_from(100.0));
+        assert_eq!(book.get(id).unwrap().trailing_stop_price(), Some(90.0));
+
+        assert!(book.process(&candle(100.0, 120.0, 99.0, 118.0)).is_empty());
+        assert_eq!(book.get(id).unwrap().trailing_stop_price(), Some(108.0));
+
+        let fills = book.process(&candle(118.0, 119.0, 105.0, 106.0));
+        assert_eq!(fills[0].price, 108.0);
+    }
+
+    #[test]
+    fn oco_fill_cancels_the_other_order() {
+        let mut book = OrderBook::new();
+        let (take_profit, _) = book.submit_oco(
+            Order::new(OrderSide::Sell, OrderKind::Limit { price: 110.0 }).reduce_only(),
+            Order::new(OrderSide::Sell, OrderKind::StopMarket { stop: 90.0 }).reduce_only());
+
+        let fills = book.process(&candle(100.0, 111.0, 99.0, 109.0));
+        assert_eq!(fills.len(), 1);
+        assert_eq!(fills[0].order_id, take_profit);
+        assert!(fills[0].reduce_only);
+        assert!(book.pending().is_empty());
+    }
+
+    #[test]
+    fn oco_assumes_the_stop_fills_first_on_a_wide_candle() {
+        let mut book = OrderBook::new();
+        let (_, stoploss) = book.submit_oco(
+            Order::new(OrderSide::Sell, OrderKind::Limit { price: 110.0 }),
+            Order::new(OrderSide::Sell, OrderKind::StopMarket { stop: 90.0 }));
+
+        let fills = book.process(&candle(100.0, 111.0, 89.0, 100.0));
+        assert_eq!(fills.len(), 1);
+        assert_eq!(fills[0].order_id, stoploss);
+    }
+
+    #[test]
+    fn orders_expire_by_time_in_force() {
+        let mut book = OrderBook::new();
+        book.submit(Order::new(OrderSide::Buy, OrderKind::Limit { price: 50.0 }).time_in_force(TimeInForce::GoodTillCandles(2)));
+        book.submit(Order::new(OrderSide::Buy, OrderKind::Limit { price: 50.0 }).time_in_force(TimeInForce::GoodTillTime(1000)));
+
+        book.process(&candle(100.0, 100.0, 100.0, 100.0));
+        book.process(&candle(100.0, 100.0, 100.0, 100.0));
+        assert_eq!(book.pending().len(), 2);
+
+        let mut late_candle = candle(100.0, 100.0, 100.0, 100.0);
+        late_candle.open_time = 1001;
+        book.process(&late_candle);
+        assert!(book.pending().is_empty());
+        assert_eq!(book.take_cancelled(), vec![1, 2]);
+        assert!(book.take_cancelled().is_empty());
+    }
+
+    #[test]
+    fn cancel_orders_of_a_position() {
+        let mut book = OrderBook::new();
+        book.submit(Order::new(OrderSide::Sell, OrderKind::Limit { price: 110.0 }).closing(1));
+        book.submit(Order::new(OrderSide::Buy, OrderKind::Limit { price: 90.0 }).closing(2));
+        book.cancel_position(1);
+        assert_eq!(book.pending().len(), 1);
+        assert_eq!(book.process(&candle(95.0, 96.0, 89.0, 90.0))[0].position, Some(2));
+    }
+
+    #[test]
+    fn cancel_removes_pending_order() {
+        let mut book = OrderBook::new();
+        let id = book.submit(Order::new(OrderSide::Buy, OrderKind::Market));
+        assert!(book.has_pending_entry());
+        assert!(book.cancel(id));
+        assert!(!book.cancel(id));
+        assert!(!book.has_pending_entry());
+    }
+}
@@ -0,0 +1,216 @@
+use crate::candlestick::Candlestick;
+use crate::backtest::trade::{Trade, TradeDirection};
+
+pub type PositionId = u32;
+
+/// Limits on how many trades can be open at the same time
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PositionLimits {
+    pub max_open_trades: usize,
+    pub max_trades_per_direction: usize, // more than one trade in the same direction allows pyramiding
+    pub hedge_mode: bool, // allows long and short trades to be open at the same time
+    pub max_margin_usage: f32, // maximum fraction of the equity that can be used as margin (1.0 = 100%)
+}
+
+impl PositionLimits {
+    /// a single trade open at a time, the behaviour of the backtest before concurrent positions existed
+    pub fn single() -> Self {
+        Self {
+            max_open_trades: 1,
+            max_trades_per_direction: 1,
+            hedge_mode: false,
+            max_margin_usage: 1.0,
+        }
+    }
+
+    pub fn max_open_trades(mut self, max_open_trades: usize) -> Self {
+        self.max_open_trades = max_open_trades;
+        self
+    }
+
+    pub fn max_trades_per_direction(mut self, max_trades_per_direction: usize) -> Self {
+        self.max_trades_per_direction = max_trades_per_direction;
+        self
+    }
+
+    pub fn hedge_mode(mut self, hedge_mode: bool) -> Self {
+        self.hedge_mode = hedge_mode;
+        self
+    }
+
+    pub fn max_margin_usage(mut self, max_margin_usage: f32) -> Self {
+        self.max_margin_usage = max_margin_usage;
+        self
+    }
+}
+
+impl Default for PositionLimits {
+    fn default() -> Self {
+        Self::single()
+    }
+}
+
+pub struct Position {
+    pub id: PositionId,
+    pub trade: Trade,
+    pub margin: f32, // balance locked by the position, returned when it is closed
+}
+
+/// Holds every open trade of the backtest and enforces the configured `PositionLimits`
+pub struct PositionManager {
+    limits: PositionLimits,
+    positions: Vec<Position>,
+    next_id: PositionId,
+    max_concurrent_trades: usize,
+    peak_margin_usage: f32,
+}
+
+impl PositionManager {
+    pub fn new(limits: PositionLimits) -> Self {
+        Self {
+            limits,
+            positions: Vec::new(),
+            next_id: 1,
+            max_concurrent_trades: 0,
+            peak_margin_usage: 0.0,
+        }
+    }
+
+    /// checks if a new trade in the given direction using `margin` respects the limits, `balance` is the free balance
+    pub fn can_open(&self, direction: TradeDirection, margin: f32, balance: f32) -> bool {
+        let same_direction = self.positions.iter().filter(|p| p.trade.side == direction).count();
+        let opposite_direction = self.positions.len() - same_direction;
+
+        self.positions.len() < self.limits.max_open_trades &&
+        same_direction < self.limits.max_trades_per_direction &&
+        (self.limits.hedge_mode || opposite_direction == 0) &&
+        self.margin_used() + margin <= self.limits.max_margin_usage * (balance + self.margin_used())
+    }
+
+    pub fn open(&mut self, trade: Trade, margin: f32) -> PositionId {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.positions.push(Position { id, trade, margin });
+        self.max_concurrent_trades = self.max_concurrent_trades.max(self.positions.len());
+        id
+    }
+
+    pub fn close(&mut self, id: PositionId) -> Option<Position> {
+        let index = self.positions.iter().position(|p| p.id == id)?;
+        Some(self.positions.remove(index))
+    }
+
+    pub fn get(&self, id: PositionId) -> Option<&Position> {
+        self.positions.iter().find(|p| p.id == id)
+    }
+
+    pub fn get_mut(&mut self, id: PositionId) -> Option<&mut Position> {
+        self.positions.iter_mut().find(|p| p.id == id)
+    }
+
+    pub fn positions(&self) -> &[Position] {
+        &self.positions
+    }
+
+    pub fn ids(&self) -> Vec<PositionId> {
+        self.positions.iter().map(|p| p.id).collect()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.positions.is_empty()
+    }
+
+    // total balance locked as margin by the open positions
+    pub fn margin_used(&self) -> f32 {
+        self.positions.iter().map(|p| p.margin).sum()
+    }
+
+    // free balance plus the margin, realized and unrealized results of every open position on the current candle
+    pub fn equity(&self, balance: f32, current_candle: &Candlestick) -> f32 {
+        balance + self.positions.iter()
+            .map(|p| p.margin + p.trade.result + p.trade.check_profit(current_candle))
+            .sum::<f32>()
+    }
+
+    // fraction of the equity (free balance plus margin) used as margin
+    pub fn margin_usage(&self, balance: f32) -> f32 {
+        let margin_used = self.margin_used();
+        if margin_used == 0.0 {
+            0.0
+        } else {
+            margin_used / (balance + margin_used)
+        }
+    }
+
+    // updates the peak margin usage, should be called once per candlestick
+    pub fn record_margin_usage(&mut self, balance: f32) {
+        self.peak_margin_usage = self.peak_margin_usage.max(self.margin_usage(balance));
+    }
+
+    pub fn max_concurrent_trades(&self) -> usize {
+        self.max_concurrent_trades
+    }
+
+    pub fn peak_margin_usage(&self) -> f32 {
+        self.peak_margin_usage
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trade(direction: TradeDirection) -> Trade {
+        Trade::open(direction, 100.0, &Candlestick::new().close(100.0), 1, 0.0, 0.0)
+    }
+
+    #[test]
+    fn single_position_by_default() {
+        let mut manager = PositionManager::new(PositionLimits::default());
+        assert!(manager.can_open(TradeDirection::Long, 100.0, 1000.0));
+        manager.open(trade(TradeDirection::Long), 100.0);
+        assert!(!manager.can_open(TradeDirection::Long, 100.0, 900.0));
+        assert!(!manager.can_open(TradeDirection::Short, 100.0, 900.0));
+    }
+
+    #[test]
+    fn pyramiding_and_hedging_limits() {
+        let limits = PositionLimits::single().max_open_trades(3).max_trades_per_direction(2).hedge_mode(true);
+        let mut manager = PositionManager::new(limits);
+        manager.open(trade(TradeDirection::Long), 100.0);
+        manager.open(trade(TradeDirection::Long), 100.0);
+        assert!(!manager.can_open(TradeDirection::Long, 100.0, 800.0));
+        assert!(manager.can_open(TradeDirection::Short, 100.0, 800.0));
+
+        let mut without_hedge = PositionManager::new(limits.hedge_mode(false));
+        without_hedge.open(trade(TradeDirection::Long), 100.0);
+        assert!(!without_hedge.can_open(TradeDirection::Short, 100.0, 900.0));
+    }
+
+    #[test]
+    fn margin_usage_across_positions() {
+        let limits = PositionLimits::single().max_open_trades(5).max_trades_per_direction(5).max_margin_usage(0.5);
+        let mut manager = PositionManager::new(limits);
+        let first = manager.open(trade(TradeDirection::Long), 200.0);
+        manager.open(trade(TradeDirection::Long), 200.0);
+        manager.record_margin_usage(600.0);
+
+        assert_eq!(manager.margin_used(), 400.0);
+        assert_eq!(manager.margin_usage(600.0), 0.4);
+        assert!(!manager.can_open(TradeDirection::Long, 200.0, 600.0));
+        assert!(manager.can_open(TradeDirection::Long, 100.0, 600.0));
+
+        let closed = manager.close(first).unwrap();
+        assert_eq!(closed.margin, 200.0);
+        assert_eq!(manager.max_concurrent_trades(), 2);
+        assert_eq!(manager.peak_margin_usage(), 0.4);
+        assert!(manager.close(first).is_none());
+    }
+
+    #[test]
+    fn equity_includes_unrealized_results() {
+        let mut manager = PositionManager::new(PositionLimits::default());
+        manager.open(trade(TradeDirection::Long), 100.0);
+        assert_eq!(manager.equity(900.0, &Candlestick::new().close(150.0)), 1050.0);
+    }
+}
@@ -0,0 +1,199 @@
+use crate::candlestick::Candlestick;
+use crate::utils::map_range;
+use crate::backtest::gene_schema::{DecodedGenes, Gene};
+use crate::indicators::traits::{Indicator, Next};
+use crate::indicators::average_true_range::AverageTrueRange as ATR;
+
+/// genes of a `PositionSizer`, the parameter is mapped to the range of the chosen method
+pub const SIZING_SCHEMA: [Gene; 2] = [
+    Gene::choice("method", &["fixed fraction", "fixed notional", "volatility target", "risk per trade", "kelly"]),
+    Gene::linear("parameter", 0.0, 1.0),
+];
+
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum SizingMethod {
+    /// fraction of the balance used as margin (0.015 = 1.5%)
+    FixedFraction(f32),
+    /// value of the position in USD with leverage applied
+    FixedNotional(f32),
+    /// fraction of the balance lost or gained when the price moves one ATR
+    VolatilityTarget(f32),
+    /// fraction of the balance lost when the stoploss is reached
+    RiskPerTrade(f32),
+    /// fraction of the kelly criterion estimated from the closed trades,
+    /// `fallback` is the fraction of the balance used until `min_trades` trades were closed
+    Kelly { multiplier: f32, fallback: f32, min_trades: u32 },
+}
+
+/// Decides how much of the balance is used as margin for a new trade
+pub struct PositionSizer {
+    method: SizingMethod,
+    atr: ATR,
+    wins: u32,
+    losses: u32,
+    total_win: f32,
+    total_loss: f32,
+}
+
+impl PositionSizer {
+    pub fn new(method: SizingMethod) -> Self {
+        Self {
+            method,
+            atr: ATR::new(14),
+            wins: 0,
+            losses: 0,
+            total_win: 0.0,
+            total_loss: 0.0,
+        }
+    }
+
+    // period of the ATR used by `SizingMethod::VolatilityTarget`
+    pub fn atr_period(mut self, period: usize) -> Self {
+        self.atr = ATR::new(period);
+        self
+    }
+
+    /// reads the sizing method and its parameter from the `SIZING_SCHEMA` genes of the section
+    pub fn decode(genes: &DecodedGenes, section: &str) -> Self {
+        let parameter = genes.value(section, "parameter");
+        let method = match genes.period(section, "method") {
+            0 => SizingMethod::FixedFraction(map_range((0.001, 0.2), parameter)),
+            1 => SizingMethod::FixedNotional(map_range((100.0, 10_000.0), parameter)),
+            2 => SizingMethod::VolatilityTarget(map_range((0.001, 0.05), parameter)),
+            3 => SizingMethod::RiskPerTrade(map_range((0.001, 0.05), parameter)),
+            _ => SizingMethod::Kelly { multiplier: map_range((0.1, 1.0), parameter), fallback: 0.015, min_trades: 10 },
+        };
+        PositionSizer::new(method)
+    }
+
+    pub fn method(&self) -> SizingMethod {
+        self.method
+    }
+
+    /// candles needed until the sizing method can be used, only the volatility target waits for its ATR
+    pub fn warmup_period(&self) -> usize {
+        match self.method {
+            SizingMethod::VolatilityTarget(_) => Indicator::<&Candlestick>::warmup_period(&self.atr),
+            _ => 0,
+        }
+    }
+
+    pub fn new_candlestick(&mut self, candle: &Candlestick) {
+        self.atr.next(candle);
+    }
+
+    // result of a closed trade, used to estimate the kelly criterion
+    pub fn record_trade_result(&mut self, result: f32) {
+        if result > 0.0 {
+            self.wins += 1;
+            self.total_win += result;
+        } else {
+            self.losses += 1;
+            self.total_loss -= result;
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.atr.reset();
+        self.wins = 0;
+        self.losses = 0;
+        self.total_win = 0.0;
+        self.total_loss = 0.0;
+    }
+
+    /// margin in USD for a new trade entering at `price`, never more than the available balance
+    pub fn margin(&self, balance: f32, price: f32, stoploss: Option<f32>, leverage: u8) -> f32 {
+        let leverage = leverage.max(1) as f32;
+        let margin = match self.method {
+            SizingMethod::FixedFraction(fraction) => balance * fraction,
+            SizingMethod::FixedNotional(notional) => notional / leverage,
+            SizingMethod::VolatilityTarget(fraction) => {
+                let atr = self.atr.value();
+                if atr > 0.0 {
+                    (balance * fraction / atr) * price / leverage
+                } else {
+                    0.0
+                }
+            }
+            SizingMethod::RiskPerTrade(fraction) => match stoploss {
+                Some(stoploss) if stoploss != price => (balance * fraction / (price - stoploss).abs()) * price / leverage,
+                _ => 0.0,
+            },
+            SizingMethod::Kelly { multiplier, fallback, min_trades } => match self.kelly_fraction(min_trades) {
+                Some(kelly) => balance * kelly * multiplier,
+                None => balance * fallback,
+            },
+        };
+        margin.max(0.0).min(balance.max(0.0))
+    }
+
+    // kelly criterion W - (1 - W) / R from the closed trades, where W is the win rate and R the payoff ratio
+    fn kelly_fraction(&self, min_trades: u32) -> Option<f32> {
+        let trades = self.wins + self.losses;
+        if trades < min_trades.max(1) {
+            return None;
+        }
+        let win_rate = self.wins as f32 / trades as f32;
+        if self.wins == 0 || self.losses == 0 || self.total_loss == 0.0 {
+            return Some(win_rate);
+        }
+        let payoff_ratio = (self.total_win / self.wins as f32) / (self.total_loss / self.losses as f32);
+        Some((win_rate - (1.0 - win_rate) / payoff_ratio).clamp(0.0, 1.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backtest::gene_schema::{GeneSchema, Section};
+
+    #[test]
+    fn fixed_fraction_and_notional() {
+        assert_eq!(PositionSizer::new(SizingMethod::FixedFraction(0.015)).margin(10_000.0, 100.0, None, 10), 150.0);
+        assert_eq!(PositionSizer::new(SizingMethod::FixedNotional(5_000.0)).margin(10_000.0, 100.0, None, 10), 500.0);
+        assert_eq!(PositionSizer::new(SizingMethod::FixedNotional(50_000.0)).margin(1_000.0, 100.0, None, 1), 1_000.0);
+    }
+
+    #[test]
+    fn volatility_target_uses_atr() {
+        let mut sizer = PositionSizer::new(SizingMethod::VolatilityTarget(0.01)).atr_period(3);
+        assert_eq!(sizer.margin(10_000.0, 100.0, None, 1), 0.0);
+
+        sizer.new_candlestick(&Candlestick::new().high(102.0).low(98.0).close(100.0));
+        // a move of one ATR (4.0) on 25 units loses 1% of the balance
+        assert_eq!(sizer.margin(10_000.0, 100.0, None, 5), 500.0);
+    }
+
+    #[test]
+    fn risk_per_trade_uses_stop_distance() {
+        let sizer = PositionSizer::new(SizingMethod::RiskPerTrade(0.01));
+        // 100 USD at risk with a stop 5.0 away is 20 units
+        assert_eq!(sizer.margin(10_000.0, 100.0, Some(95.0), 2), 1_000.0);
+        assert_eq!(sizer.margin(10_000.0, 100.0, None, 2), 0.0);
+    }
+
+    #[test]
+    fn kelly_from_closed_trades() {
+        let mut sizer = PositionSizer::new(SizingMethod::Kelly { multiplier: 0.5, fallback: 0.01, min_trades: 4 });
+        assert_eq!(sizer.margin(10_000.0, 100.0, None, 1), 100.0);
+
+        sizer.record_trade_result(200.0);
+        sizer.record_trade_result(200.0);
+        sizer.record_trade_result(200.0);
+        sizer.record_trade_result(-100.0);
+        // W = 0.75, R = 2.0 -> kelly of 0.625
+        assert_eq!(sizer.margin(10_000.0, 100.0, None, 1), 3_125.0);
+
+        sizer.reset();
+        assert_eq!(sizer.margin(10_000.0, 100.0, None, 1), 100.0);
+    }
+
+    #[test]
+    fn decode_sizing_genes() {
+        let schema = GeneSchema::new().sections(&[Section::new("sizing", &SIZING_SCHEMA)]);
+        let decode = |genes: &[f32]| PositionSizer::decode(&schema.decode(genes).unwrap(), "sizing").method();
+        assert_eq!(decode(&[0.0, 0.0]), SizingMethod::FixedFraction(0.001));
+        assert_eq!(decode(&[0.5, 1.0]), SizingMethod::VolatilityTarget(0.05));
+        assert!(matches!(decode(&[1.0, 0.0]), SizingMethod::Kelly { .. }));
+    }
+}
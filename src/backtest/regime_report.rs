@@ -0,0 +1,77 @@
+use std::collections::HashMap;
+
+use crate::backtest::engine::{Event, Observer};
+use crate::backtest::position_manager::PositionId;
+use crate::indicators::regime::Regime;
+
+/// Results of the trades entered during one market regime
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct RegimeStats {
+    pub candles: u32, // candles of the backtest labeled with the regime
+    pub trade_count: u32,
+    pub total_profit: f32, // sum of the results of the trades entered in the regime, fees already deducted
+}
+
+/// Backtest results broken down by the regime of the candle each trade was entered on,
+/// empty unless the backtest labels its candles with `Backtest::with_regimes`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RegimeReport {
+    stats: [RegimeStats; Regime::ALL.len()],
+}
+
+impl RegimeReport {
+    pub fn get(&self, regime: Regime) -> &RegimeStats {
+        &self.stats[regime.index()]
+    }
+
+    pub fn merge(&mut self, other: &RegimeReport) {
+        for (stats, other) in self.stats.iter_mut().zip(other.stats.iter()) {
+            stats.candles += other.candles;
+            stats.trade_count += other.trade_count;
+            stats.total_profit += other.total_profit;
+        }
+    }
+}
+
+// follows the regime of the candles received by the engine and assigns each closed position to the regime it was opened in
+pub(crate) struct RegimeTracker<'a> {
+    regimes: &'a [Regime], // regime of each candle sent to the engine, in order
+    candles: usize,
+    entries: HashMap<PositionId, Regime>,
+    report: RegimeReport,
+}
+
+impl<'a> RegimeTracker<'a> {
+    pub fn new(regimes: &'a [Regime]) -> Self {
+        Self { regimes, candles: 0, entries: HashMap::new(), report: RegimeReport::default() }
+    }
+
+    pub fn report(&self) -> &RegimeReport {
+        &self.report
+    }
+
+    fn current(&self) -> Regime {
+        self.regimes.get(self.candles.saturating_sub(1)).copied().unwrap_or_default()
+    }
+}
+
+impl Observer for RegimeTracker<'_> {
+    fn on_event(&mut self, event: &Event) {
+        match event {
+            Event::Candle(_) => {
+                self.candles += 1;
+                self.report.stats[self.current().index()].candles += 1;
+            }
+            Event::PositionOpened { id, .. } => {
+                let regime = self.current();
+                self.entries.insert(*id, regime);
+                self.report.stats[regime.index()].trade_count += 1;
+            }
+            Event::PositionClosed { id, result, .. } => {
+                let regime = self.entries.remove(id).unwrap_or_else(|| self.current());
+                self.report.stats[regime.index()].total_profit += result;
+            }
+            _ => {}
+        }
+    }
+}
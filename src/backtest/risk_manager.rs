@@ -0,0 +1,260 @@
+use crate::candlestick::Candlestick;
+
+const MILLISECONDS_PER_DAY: u64 = 24 * 60 * 60 * 1000;
+
+/// Account level guardrails applied by the backtest, every limit is disabled by default
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct RiskLimits {
+    pub max_drawdown: Option<f32>, // fraction of the peak equity (0.2 = 20%) that halts trading for the rest of the range
+    pub max_daily_loss: Option<f32>, // fraction of the equity at the start of the day that can be lost before entries stop
+    pub max_weekly_loss: Option<f32>, // fraction of the equity at the start of the week that can be lost before entries stop
+    pub cooldown_candles: u32, // candles without new entries after a trade is stopped out, including the candle of the stop
+    pub max_trades_per_day: Option<u32>,
+}
+
+impl RiskLimits {
+    pub fn max_drawdown(mut self, max_drawdown: f32) -> Self {
+        self.max_drawdown = Some(max_drawdown);
+        self
+    }
+
+    pub fn max_daily_loss(mut self, max_daily_loss: f32) -> Self {
+        self.max_daily_loss = Some(max_daily_loss);
+        self
+    }
+
+    pub fn max_weekly_loss(mut self, max_weekly_loss: f32) -> Self {
+        self.max_weekly_loss = Some(max_weekly_loss);
+        self
+    }
+
+    pub fn cooldown_candles(mut self, cooldown_candles: u32) -> Self {
+        self.cooldown_candles = cooldown_candles;
+        self
+    }
+
+    pub fn max_trades_per_day(mut self, max_trades_per_day: u32) -> Self {
+        self.max_trades_per_day = Some(max_trades_per_day);
+        self
+    }
+}
+
+/// How often each guardrail was activated during a backtest
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RiskReport {
+    pub max_drawdown: f32, // largest drop from the peak equity seen (0.2 = 20%)
+    pub halted_ranges: u32, // ranges where the drawdown circuit breaker halted trading
+    pub daily_loss_stops: u32,
+    pub weekly_loss_stops: u32,
+    pub cooldowns: u32,
+    pub blocked_entries: u32, // entry signals ignored because of any of the limits
+}
+
+impl RiskReport {
+    pub fn merge(&mut self, other: &RiskReport) {
+        self.max_drawdown = self.max_drawdown.max(other.max_drawdown);
+        self.halted_ranges += other.halted_ranges;
+        self.daily_loss_stops += other.daily_loss_stops;
+        self.weekly_loss_stops += other.weekly_loss_stops;
+        self.cooldowns += other.cooldowns;
+        self.blocked_entries += other.blocked_entries;
+    }
+}
+
+/// Tracks the equity of a backtested range and decides when new trades are allowed
+pub struct RiskManager {
+    limits: RiskLimits,
+    peak_equity: f32,
+    halted: bool,
+    day: u64,
+    day_start_equity: f32,
+    daily_loss_reached: bool,
+    trades_today: u32,
+    week: u64,
+    week_start_equity: f32,
+    weekly_loss_reached: bool,
+    cooldown_remaining: u32,
+    report: RiskReport,
+}
+
+impl RiskManager {
+    pub fn new(limits: RiskLimits, initial_equity: f32) -> Self {
+        Self {
+            limits,
+            peak_equity: initial_equity,
+            halted: false,
+            day: 0,
+            day_start_equity: initial_equity,
+            daily_loss_reached: false,
+            trades_today: 0,
+            week: 0,
+            week_start_equity: initial_equity,
+            weekly_loss_reached: false,
+            cooldown_remaining: 0,
+            report: RiskReport::default(),
+        }
+    }
+
+    /// updates the limits with the equity (including open trades) after the candle was processed,
+    /// returns true only on the candle where the drawdown circuit breaker halts trading
+    pub fn new_candlestick(&mut self, candle: &Candlestick, equity: f32) -> bool {
+        let day = candle.open_time / MILLISECONDS_PER_DAY;
+        if day != self.day {
+            self.day = day;
+            self.day_start_equity = equity;
+            self.daily_loss_reached = false;
+            self.trades_today = 0;
+        }
+
+        // the unix epoch was on a thursday, weeks start on monday
+        let week = (day + 3) / 7;
+        if week != self.week {
+            self.week = week;
+            self.week_start_equity = equity;
+            self.weekly_loss_reached = false;
+        }
+
+        if self.cooldown_remaining > 0 {
+            self.cooldown_remaining -= 1;
+        }
+
+        if let Some(max_daily_loss) = self.limits.max_daily_loss {
+            if !self.daily_loss_reached && equity < self.day_start_equity * (1.0 - max_daily_loss) {
+                self.daily_loss_reached = true;
+                self.report.daily_loss_stops += 1;
+            }
+        }
+
+        if let Some(max_weekly_loss) = self.limits.max_weekly_loss {
+            if !self.weekly_loss_reached && equity < self.week_start_equity * (1.0 - max_weekly_loss) {
+                self.weekly_loss_reached = true;
+                self.report.weekly_loss_stops += 1;
+            }
+        }
+
+        self.peak_equity = self.peak_equity.max(equity);
+        let drawdown = if self.peak_equity > 0.0 { 1.0 - equity / self.peak_equity } else { 0.0 };
+        self.report.max_drawdown = self.report.max_drawdown.max(drawdown);
+
+        match self.limits.max_drawdown {
+            Some(max_drawdown) if !self.halted && drawdown >= max_drawdown => {
+                self.halted = true;
+                self.report.halted_ranges += 1;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    // checks if a new trade can be opened, a refused entry is counted in the report
+    pub fn allow_entry(&mut self) -> bool {
+        let trade_limit_reached = self.limits.max_trades_per_day.is_some_and(|max| self.trades_today >= max);
+        let allowed = !self.halted && !self.daily_loss_reached && !self.weekly_loss_reached &&
+            self.cooldown_remaining == 0 && !trade_limit_reached;
+        if !allowed {
+            self.report.blocked_entries += 1;
+        }
+        allowed
+    }
+
+    pub fn trade_opened(&mut self) {
+        self.trades_today += 1;
+    }
+
+    // a trade closed by its stoploss or by liquidation starts the cooldown
+    pub fn trade_stopped_out(&mut self) {
+        if self.limits.cooldown_candles > 0 {
+            self.cooldown_remaining = self.limits.cooldown_candles;
+            self.report.cooldowns += 1;
+        }
+    }
+
+    pub fn is_halted(&self) -> bool {
+        self.halted
+    }
+
+    pub fn report(&self) -> &RiskReport {
+        &self.report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candle_at(day: u64) -> Candlestick {
+        let mut candle = Candlestick::new();
+        candle.open_time = day * MILLISECONDS_PER_DAY;
+        candle
+    }
+
+    #[test]
+    fn no_limits_by_default() {
+        let mut risk = RiskManager::new(RiskLimits::default(), 1000.0);
+        assert!(!risk.new_candlestick(&candle_at(0), 10.0));
+        risk.trade_stopped_out();
+        assert!(risk.allow_entry());
+        assert_eq!(risk.report().max_drawdown, 0.99);
+    }
+
+    #[test]
+    fn drawdown_circuit_breaker() {
+        let mut risk = RiskManager::new(RiskLimits::default().max_drawdown(0.2), 1000.0);
+        assert!(!risk.new_candlestick(&candle_at(0), 1200.0));
+        assert!(!risk.new_candlestick(&candle_at(0), 1000.0));
+        assert!(risk.new_candlestick(&candle_at(0), 950.0));
+        assert!(!risk.new_candlestick(&candle_at(0), 940.0));
+        assert!(risk.is_halted());
+        assert!(!risk.allow_entry());
+        assert_eq!(risk.report().halted_ranges, 1);
+    }
+
+    #[test]
+    fn daily_loss_limit_resets_next_day() {
+        let mut risk = RiskManager::new(RiskLimits::default().max_daily_loss(0.05), 1000.0);
+        risk.new_candlestick(&candle_at(1), 1000.0);
+        risk.new_candlestick(&candle_at(1), 940.0);
+        assert!(!risk.allow_entry());
+
+        risk.new_candlestick(&candle_at(2), 940.0);
+        assert!(risk.allow_entry());
+        assert_eq!(risk.report().daily_loss_stops, 1);
+        assert_eq!(risk.report().blocked_entries, 1);
+    }
+
+    #[test]
+    fn weekly_loss_limit() {
+        let mut risk = RiskManager::new(RiskLimits::default().max_weekly_loss(0.1), 1000.0);
+        // days 4 to 10 since the epoch are the same week
+        risk.new_candlestick(&candle_at(4), 1000.0);
+        risk.new_candlestick(&candle_at(6), 920.0);
+        risk.new_candlestick(&candle_at(8), 880.0);
+        assert!(!risk.allow_entry());
+        risk.new_candlestick(&candle_at(11), 880.0);
+        assert!(risk.allow_entry());
+    }
+
+    #[test]
+    fn cooldown_after_stop_out() {
+        let mut risk = RiskManager::new(RiskLimits::default().cooldown_candles(2), 1000.0);
+        risk.new_candlestick(&candle_at(0), 1000.0);
+        risk.trade_stopped_out();
+        assert!(!risk.allow_entry());
+        risk.new_candlestick(&candle_at(0), 1000.0);
+        assert!(!risk.allow_entry());
+        risk.new_candlestick(&candle_at(0), 1000.0);
+        assert!(risk.allow_entry());
+        assert_eq!(risk.report().cooldowns, 1);
+    }
+
+    #[test]
+    fn max_trades_per_day() {
+        let mut risk = RiskManager::new(RiskLimits::default().max_trades_per_day(2), 1000.0);
+        risk.new_candlestick(&candle_at(0), 1000.0);
+        risk.trade_opened();
+        risk.trade_opened();
+        assert!(!risk.allow_entry());
+        risk.new_candlestick(&candle_at(1), 1000.0);
+        assert!(risk.allow_entry());
+    }
+}
@@ -0,0 +1,203 @@
+use serde::{Serialize, Deserialize};
+
+use crate::candlestick::Candlestick;
+use crate::backtest::trade::TradeDirection;
+use crate::backtest::strategy::TradingStrategy;
+use crate::backtest::engine::Event;
+use crate::backtest::position_sizer::{PositionSizer, SizingMethod};
+use crate::indicators::average_true_range::AverageTrueRange as ATR;
+use crate::indicators::traits::{Indicator, Next};
+use crate::rules::compiler::Rule;
+use crate::rules::RuleError;
+
+/// Text description of a `RuleStrategy`, e.g. read from a json file:
+/// `{"long_entry": "rsi(14) < 30 and crosses_above(ema(9), ema(21))", "take_profit_atr": 3.0}`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RuleStrategyConfig {
+    pub long_entry: Option<String>, // rule entering long trades, no long trades when missing
+    pub short_entry: Option<String>, // rule entering short trades, no short trades when missing
+    pub take_profit_atr: f32, // distance of the take profit from the entry in atrs
+    pub stop_loss_atr: f32, // distance of the stoploss from the entry in atrs
+    pub atr_period: usize,
+    pub leverage: u8,
+    pub balance_fraction: f32, // fraction of the balance used as margin on each trade
+}
+
+impl Default for RuleStrategyConfig {
+    fn default() -> Self {
+        Self {
+            long_entry: None,
+            short_entry: None,
+            take_profit_atr: 2.0,
+            stop_loss_atr: 1.0,
+            atr_period: 14,
+            leverage: 1,
+            balance_fraction: 0.015,
+        }
+    }
+}
+
+/// Strategy entering trades when the conditions of its rules are met, with a take profit and a stoploss
+/// at a fixed amount of atrs from the close. Both directions signaled on the same candle cancel each other
+pub struct RuleStrategy {
+    long_entry: Option<Rule>,
+    short_entry: Option<Rule>,
+    atr: ATR,
+    take_profit_atr: f32,
+    stop_loss_atr: f32,
+    leverage: u8,
+    position_sizer: PositionSizer,
+    last_close: f32,
+    start_long_trade: bool,
+    start_short_trade: bool,
+}
+
+impl RuleStrategy {
+    pub fn from_config(config: &RuleStrategyConfig) -> Result<Self, RuleError> {
+        let compile = |text: &Option<String>| text.as_deref().map(Rule::parse).transpose();
+        Ok(Self::from_rules(compile(&config.long_entry)?, compile(&config.short_entry)?, config))
+    }
+
+    /// strategy entering on rules already compiled, the entries of the config are ignored
+    pub fn from_rules(long_entry: Option<Rule>, short_entry: Option<Rule>, config: &RuleStrategyConfig) -> Self {
+        Self {
+            long_entry,
+            short_entry,
+            atr: ATR::new(config.atr_period.max(1)),
+            take_profit_atr: config.take_profit_atr,
+            stop_loss_atr: config.stop_loss_atr,
+            leverage: config.leverage.max(1),
+            position_sizer: PositionSizer::new(SizingMethod::FixedFraction(config.balance_fraction)),
+            last_close: 0.0,
+            start_long_trade: false,
+            start_short_trade: false,
+        }
+    }
+
+    /// reads the `RuleStrategyConfig` from json and compiles its rules
+    pub fn from_json(json: &str) -> Result<Self, RuleError> {
+        let config: RuleStrategyConfig = serde_json::from_str(json).map_err(|e| RuleError::Config(e.to_string()))?;
+        Self::from_config(&config)
+    }
+
+    pub fn long_entry(&self) -> Option<&Rule> {
+        self.long_entry.as_ref()
+    }
+
+    pub fn short_entry(&self) -> Option<&Rule> {
+        self.short_entry.as_ref()
+    }
+}
+
+impl TradingStrategy for RuleStrategy {
+    fn should_start_trade(&mut self) -> Option<(TradeDirection, f32, f32)> {
+        let atr = self.atr.value();
+        match (self.start_long_trade, self.start_short_trade) {
+            (true, false) => Some((TradeDirection::Long,
+                self.last_close + atr * self.take_profit_atr,
+                self.last_close - atr * self.stop_loss_atr)),
+            (false, true) => Some((TradeDirection::Short,
+                self.last_close - atr * self.take_profit_atr,
+                self.last_close + atr * self.stop_loss_atr)),
+            _ => None,
+        }
+    }
+
+    fn new_candlestick(&mut self, candle: &Candlestick) {
+        self.start_long_trade = self.long_entry.as_mut().is_some_and(|rule| rule.evaluate(candle));
+        self.start_short_trade = self.short_entry.as_mut().is_some_and(|rule| rule.evaluate(candle));
+        self.atr.next(candle);
+        self.position_sizer.new_candlestick(candle);
+        self.last_close = candle.close;
+    }
+
+    fn reset(&mut self) {
+        for rule in self.long_entry.iter_mut().chain(self.short_entry.iter_mut()) {
+            rule.reset();
+        }
+        self.atr.reset();
+        self.position_sizer.reset();
+        self.start_long_trade = false;
+        self.start_short_trade = false;
+    }
+
+    fn warmup_period(&self) -> usize {
+        self.long_entry.iter().chain(self.short_entry.iter())
+            .map(Rule::warmup_period)
+            .fold(Indicator::<&Candlestick>::warmup_period(&self.atr), usize::max)
+    }
+
+    fn position_size(&self, balance: f32, price: f32, stoploss: f32) -> f32 {
+        self.position_sizer.margin(balance, price, Some(stoploss), self.leverage)
+    }
+
+    fn on_event(&mut self, event: &Event) {
+        if let Event::PositionClosed { result, .. } = event {
+            self.position_sizer.record_trade_result(*result);
+        }
+    }
+
+    fn leverage(&self) -> u8 {
+        self.leverage
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candle(close: f32) -> Candlestick {
+        Candlestick::new().open(close).high(close + 1.0).low(close - 1.0).close(close)
+    }
+
+    #[test]
+    fn test_from_json() {
+        let strategy = RuleStrategy::from_json(r#"{"long_entry": "close > sma(3)", "leverage": 5, "atr_period": 20}"#).unwrap();
+        assert_eq!(strategy.long_entry().unwrap().to_string(), "close > sma(3)");
+        assert!(strategy.short_entry().is_none());
+        assert_eq!(strategy.leverage(), 5);
+        assert_eq!(strategy.warmup_period(), 20);
+
+        assert!(matches!(RuleStrategy::from_json(r#"{"long_entry": 3}"#), Err(RuleError::Config(_))));
+        assert!(matches!(RuleStrategy::from_json(r#"{"short_entry": "close >"}"#), Err(RuleError::Syntax { .. })));
+    }
+
+    #[test]
+    fn test_targets() {
+        let config = RuleStrategyConfig {
+            long_entry: Some("crosses_above(close, 10)".to_string()),
+            short_entry: Some("crosses_below(close, 10)".to_string()),
+            atr_period: 1,
+            ..RuleStrategyConfig::default()
+        };
+        let mut strategy = RuleStrategy::from_config(&config).unwrap();
+        strategy.new_candlestick(&candle(9.0));
+        assert_eq!(strategy.should_start_trade(), None);
+        // an atr of period 1 is the true range, 3.0 from the previous close to the high
+        strategy.new_candlestick(&candle(11.0));
+        assert_eq!(strategy.should_start_trade(), Some((TradeDirection::Long, 17.0, 8.0)));
+        strategy.new_candlestick(&candle(9.0));
+        assert_eq!(strategy.should_start_trade(), Some((TradeDirection::Short, 3.0, 12.0)));
+    }
+
+    #[test]
+    fn test_candlestick_patterns() {
+        let config = RuleStrategyConfig {
+            long_entry: Some("engulfing() > 0.5".to_string()),
+            short_entry: Some("engulfing() < -0.5".to_string()),
+            atr_period: 1,
+            ..RuleStrategyConfig::default()
+        };
+        let mut strategy = RuleStrategy::from_config(&config).unwrap();
+        assert_eq!(strategy.warmup_period(), 2);
+        let candles = [(10.0, 10.5, 8.5, 9.0), (8.5, 11.0, 8.0, 10.5), (10.5, 11.0, 7.5, 8.0)];
+        let trades: Vec<Option<TradeDirection>> = candles.iter()
+            .map(|(open, high, low, close)| {
+                strategy.new_candlestick(&Candlestick::new().open(*open).high(*high).low(*low).close(*close));
+                strategy.should_start_trade().map(|(direction, _, _)| direction)
+            })
+            .collect();
+        assert_eq!(trades, vec![None, Some(TradeDirection::Long), Some(TradeDirection::Short)]);
+    }
+}
@@ -1,9 +1,59 @@
 use std::f32;
+use std::fmt;
 
-use crate::utils::map_range;
 use crate::candlestick::Candlestick;
-use crate::backtest::trade::TradeDirection;
-use crate::backtest::trade_rule::TradeRule;
+use crate::backtest::trade::{Trade, TradeDirection};
+use crate::backtest::trade_rule::{RuleStructure, TradeRule, RULE_SCHEMA, RULE_CONSTRAINTS, RULE_AVERAGES_SCHEMA,
+    RULE_SOURCE_SCHEMA, RULE_STRUCTURE_SCHEMA};
+use crate::backtest::order::{OrderKind, TimeInForce};
+use crate::backtest::engine::Event;
+use crate::backtest::gene_schema::{genes_in, DecodedGenes, Gene, GeneSchema, SchemaError, Section};
+use crate::backtest::position_sizer::{PositionSizer, SizingMethod, SIZING_SCHEMA};
+use crate::indicators::cache::RangeCache;
+use crate::indicators::regime::{Regime, RegimeClassifier};
+
+const LEVERAGE: Section = Section::new("strategy", &[Gene::integer("leverage", 1.0, 60.0)]);
+
+// the leverage and the long and short rules, followed by the kinds of the averages and the sources of the rules
+const INDEPENDENT_SECTIONS: [Section; 8] = [
+    LEVERAGE,
+    Section::new("long", &RULE_SCHEMA).constraints(&RULE_CONSTRAINTS),
+    Section::new("short", &RULE_SCHEMA).constraints(&RULE_CONSTRAINTS),
+    // never read, it only keeps the position of the genes after it in older cromossomes
+    Section::new("short", &[Gene::linear("unused", 0.0, 1.0)]),
+    Section::new("long", &RULE_AVERAGES_SCHEMA),
+    Section::new("short", &RULE_AVERAGES_SCHEMA),
+    Section::new("long", &RULE_SOURCE_SCHEMA),
+    Section::new("short", &RULE_SOURCE_SCHEMA),
+];
+
+const STRUCTURE_SECTIONS: [Section; 2] = [
+    Section::new("long", &RULE_STRUCTURE_SCHEMA),
+    Section::new("short", &RULE_STRUCTURE_SCHEMA),
+];
+
+// the leverage and the genes of a single rule in the sections named `rule`
+const fn single_rule_sections(rule: &'static str) -> [Section; 4] {
+    [
+        LEVERAGE,
+        Section::new(rule, &RULE_SCHEMA).constraints(&RULE_CONSTRAINTS),
+        Section::new(rule, &RULE_AVERAGES_SCHEMA),
+        Section::new(rule, &RULE_SOURCE_SCHEMA),
+    ]
+}
+
+const LONG_SECTIONS: [Section; 4] = single_rule_sections("long");
+const SHORT_SECTIONS: [Section; 4] = single_rule_sections("short");
+const SYMMETRIC_SECTIONS: [Section; 4] = single_rule_sections("rule");
+
+const SIZING_SECTIONS: [Section; 1] = [Section::new("sizing", &SIZING_SCHEMA)];
+
+/// amount of genes of a `SingleStrategy` cromossome without the optional structure and position sizing genes
+pub const STRATEGY_GENES: usize = genes_in(&INDEPENDENT_SECTIONS);
+
+/// genes enabling the conditions of the long and short rules and choosing how they are combined,
+/// appended after the `STRATEGY_GENES`
+pub const STRUCTURE_GENES: usize = genes_in(&STRUCTURE_SECTIONS);
 
 pub trait TradingStrategy {
     /// evaluates if a new Long or Short trade should be executed based on the last candlesticks provided
@@ -16,18 +66,107 @@ pub trait TradingStrategy {
     //reset all interval parameters to run the strategy again with a new candlesticks
     fn reset(&mut self);
 
-    // returns the current trade direction
-    fn percentage_amount_per_trade(&self) -> f32;
+    /// called by the backtest before the candles of a range are received, the strategy can take precomputed
+    /// indicator series of the range from the cache instead of computing them on every candle until the next reset
+    fn precompute(&mut self, _cache: &RangeCache) {}
+
+    /// candles the strategy receives before the backtest starts trading a range, so its indicators are ready
+    fn warmup_period(&self) -> usize {
+        0
+    }
+
+    /// margin in USD used by a new trade entering at `price` with the given stoploss, limited to the balance by the backtest
+    fn position_size(&self, balance: f32, price: f32, stoploss: f32) -> f32;
+
+    /// receives every event of the `Engine` (orders, fills and positions) in the order they happen,
+    /// closed positions carry the result of the trade with fees already deducted
+    fn on_event(&mut self, _event: &Event) {}
 
     // amount of leverage used in trades 
     fn leverage(&self) -> u8;
+
+    /// order used to enter a trade returned by `should_start_trade`, market orders are filled on the current close
+    /// while any other kind waits in the order book until it is filled or expires
+    fn entry_order(&self, _direction: TradeDirection) -> (OrderKind, TimeInForce) {
+        (OrderKind::Market, TimeInForce::GoodTillCancelled)
+    }
+
+    /// callback rate (0.01 = 1%) of a trailing stop used instead of the fixed stoploss price
+    fn trailing_stop(&self, _direction: TradeDirection) -> Option<f32> {
+        None
+    }
+
+    /// called on every candlestick while a trade is open, returning true closes the trade on the current close
+    fn should_close_trade(&mut self, _trade: &Trade, _candle: &Candlestick) -> bool {
+        false
+    }
+
+    /// maximum amount of candlesticks a trade is kept open before being closed on the current close
+    fn max_candles_in_trade(&self) -> Option<u32> {
+        None
+    }
+
+    /// called on every candlestick while a trade is open, the returned adjustments are executed in order on the current close
+    fn adjust_trade(&mut self, _trade: &Trade, _candle: &Candlestick) -> Vec<TradeAdjustment> {
+        Vec::new()
+    }
+}
+
+/// Changes a strategy can request on a open trade
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum TradeAdjustment {
+    /// increases the position by the given fraction of the current balance
+    ScaleIn(f32),
+    /// closes the given fraction of the open position (0.5 = 50%), a fraction of 1.0 or more closes the trade
+    ScaleOut(f32),
+    MoveStoploss(f32),
+    MoveTakeprofit(f32),
+    /// moves the stoploss to the average entry price of the trade
+    StoplossToBreakeven,
+}
+
+/// Directions traded by a `SingleStrategy` and how the genes of its rules are laid out in the cromossome
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DirectionMode {
+    /// long and short rules decoded from their own genes, the layout of `STRATEGY_GENES`
+    #[default]
+    Independent,
+    /// a single set of genes decoding the long rule and a mirrored short rule, e.g. a long rule entering while
+    /// the rsi is between 20 and 30 has a short rule entering while it is between 70 and 80
+    Symmetric,
+    /// only the genes of the long rule, short trades are never entered
+    LongOnly,
+    /// only the genes of the short rule, long trades are never entered
+    ShortOnly,
+}
+
+impl DirectionMode {
+    // genes of the leverage and the rules without their structure
+    fn sections(&self) -> &'static [Section] {
+        match self {
+            DirectionMode::Independent => &INDEPENDENT_SECTIONS,
+            DirectionMode::Symmetric => &SYMMETRIC_SECTIONS,
+            DirectionMode::LongOnly => &LONG_SECTIONS,
+            DirectionMode::ShortOnly => &SHORT_SECTIONS,
+        }
+    }
+
+    // names of the sections of the rules, each one with its own structure genes
+    fn rules(&self) -> &'static [&'static str] {
+        match self {
+            DirectionMode::Independent => &["long", "short"],
+            DirectionMode::Symmetric => &["rule"],
+            DirectionMode::LongOnly => &["long"],
+            DirectionMode::ShortOnly => &["short"],
+        }
+    }
 }
 
 pub struct SingleStrategy {
     leverage: u8,
-    long_rule: TradeRule,
-    short_rule: TradeRule,
-    percentage_amount_per_trade: f32,
+    long_rule: Option<TradeRule>, // no long trades without it
+    short_rule: Option<TradeRule>, // no short trades without it
+    position_sizer: PositionSizer,
     start_long_trade: bool,
     start_short_trade: bool,
 }
@@ -36,33 +175,51 @@ pub struct SingleStrategy {
 impl TradingStrategy for SingleStrategy {
     // checks if a trade should be open and returns the direction with a takeprofit and a stoploss price targets
     fn should_start_trade(&mut self) -> Option<(TradeDirection, f32, f32)> {
+        let targets = |direction: TradeDirection, rule: &TradeRule| (direction, rule.evaluate_take_profit(), rule.evaluate_stop_loss());
         if self.start_long_trade && self.start_short_trade {
             return Option::None;
         }else if self.start_long_trade {
-            return Option::Some((TradeDirection::Long, 
-                self.long_rule.evaluate_take_profit(), 
-                self.long_rule.evaluate_stop_loss()));
+            return self.long_rule.as_ref().map(|rule| targets(TradeDirection::Long, rule));
         }else if self.start_short_trade {
-            return Option::Some((TradeDirection::Short, 
-                self.short_rule.evaluate_take_profit(), 
-                self.short_rule.evaluate_stop_loss()));
+            return self.short_rule.as_ref().map(|rule| targets(TradeDirection::Short, rule));
         }
         return None;
     }
 
     // interpret a new price datapoint
     fn new_candlestick(&mut self, candle: &Candlestick){
-        self.start_long_trade = self.long_rule.evaluate(candle); 
-        self.start_short_trade = self.short_rule.evaluate(candle);
+        self.start_long_trade = self.long_rule.as_mut().is_some_and(|rule| rule.evaluate(candle));
+        self.start_short_trade = self.short_rule.as_mut().is_some_and(|rule| rule.evaluate(candle));
+        self.position_sizer.new_candlestick(candle);
     }
 
     fn reset(&mut self) {
-        self.long_rule.reset();
-        self.short_rule.reset();
+        for rule in self.long_rule.iter_mut().chain(self.short_rule.iter_mut()) {
+            rule.reset();
+        }
+        self.position_sizer.reset();
     }
 
-    fn percentage_amount_per_trade(&self) -> f32 {
-        self.percentage_amount_per_trade
+    fn precompute(&mut self, cache: &RangeCache) {
+        for rule in self.long_rule.iter_mut().chain(self.short_rule.iter_mut()) {
+            rule.precompute(cache);
+        }
+    }
+
+    fn warmup_period(&self) -> usize {
+        self.long_rule.iter().chain(self.short_rule.iter())
+            .map(TradeRule::warmup_period)
+            .fold(self.position_sizer.warmup_period(), usize::max)
+    }
+
+    fn position_size(&self, balance: f32, price: f32, stoploss: f32) -> f32 {
+        self.position_sizer.margin(balance, price, Some(stoploss), self.leverage)
+    }
+
+    fn on_event(&mut self, event: &Event) {
+        if let Event::PositionClosed { result, .. } = event {
+            self.position_sizer.record_trade_result(*result);
+        }
     }
 
     fn leverage(&self) -> u8 {
@@ -72,37 +229,291 @@ impl TradingStrategy for SingleStrategy {
 
 
 impl SingleStrategy {
-    pub fn decode(cromossome: &[f32]) -> Self{
-        if cromossome.len() != 36 {
-            panic!("the cromossome must have {} genes, but it had {}", 12, cromossome.len());
+    /// layout of a cromossome with the leverage and the rules of the mode, followed by the genes switching the conditions
+    /// of each rule on and off when `structure` is set and by the genes of the position sizing when `sizing` is set.
+    /// The `DirectionMode::Independent` layout has `STRATEGY_GENES` genes and `STRUCTURE_GENES` structure genes
+    pub fn schema(mode: DirectionMode, structure: bool, sizing: bool) -> GeneSchema {
+        let mut schema = GeneSchema::new().sections(mode.sections());
+        if structure {
+            let sections: Vec<Section> = mode.rules().iter().map(|rule| Section::new(rule, &RULE_STRUCTURE_SCHEMA)).collect();
+            schema = schema.sections(&sections);
         }
-        
-        for i in 0..cromossome.len() {
-            if cromossome[i] < 0.0 || cromossome[i] > 1.0 {
-                panic!("the cromossome must have genes between 0.0 and 1.0, but it had {}", cromossome[i]);
-            }
+        if sizing {
+            schema = schema.sections(&SIZING_SECTIONS);
         }
+        schema
+    }
 
-        SingleStrategy {
-            leverage: map_range((1.0, 60.0), cromossome[0]) as u8,
-            long_rule: TradeRule::new(TradeDirection::Long, &cromossome[1..=17]),
-            short_rule: TradeRule::new(TradeDirection::Long, &cromossome[18..=35]),
-            percentage_amount_per_trade: 0.015,
+    /// the genes of a valid cromossome decoded with the layout of the mode matching its length
+    pub fn genes(mode: DirectionMode, cromossome: &[f32]) -> Result<DecodedGenes, SchemaError> {
+        let schemas = [false, true].into_iter()
+            .flat_map(|structure| [false, true].map(|sizing| Self::schema(mode, structure, sizing)))
+            .collect::<Vec<GeneSchema>>();
+        match schemas.iter().find(|schema| schema.size() == cromossome.len()) {
+            Some(schema) => schema.decode(cromossome),
+            None => Err(SchemaError::Length {
+                expected: schemas.iter().map(GeneSchema::size).collect(),
+                found: cromossome.len(),
+            }),
+        }
+    }
+
+    /// decodes a cromossome of any layout of `schema` for the mode. Without the structure genes every condition is
+    /// joined by `and` and without the sizing genes 1.5% of the balance is used on each trade
+    pub fn try_decode(mode: DirectionMode, cromossome: &[f32]) -> Result<Self, SchemaError> {
+        let genes = Self::genes(mode, cromossome)?;
+        let rule = |direction: TradeDirection, section: &str| TradeRule::new(direction, &genes, section)
+            .structure(RuleStructure::decode(&genes, section));
+        let (long_rule, short_rule) = match mode {
+            DirectionMode::Independent => (Some(rule(TradeDirection::Long, "long")), Some(rule(TradeDirection::Short, "short"))),
+            DirectionMode::Symmetric => (Some(rule(TradeDirection::Long, "rule")), Some(rule(TradeDirection::Short, "rule").mirrored())),
+            DirectionMode::LongOnly => (Some(rule(TradeDirection::Long, "long")), None),
+            DirectionMode::ShortOnly => (None, Some(rule(TradeDirection::Short, "short"))),
+        };
+        Ok(SingleStrategy {
+            leverage: genes.period("strategy", "leverage") as u8,
+            long_rule,
+            short_rule,
+            position_sizer: if genes.contains("sizing", "method") {
+                PositionSizer::decode(&genes, "sizing")
+            } else {
+                PositionSizer::new(SizingMethod::FixedFraction(0.015))
+            },
             start_long_trade: false,
             start_short_trade: false,
-        }
+        })
+    }
+
+    /// like `try_decode` with the `DirectionMode::Independent` layout, panicking on an invalid cromossome
+    pub fn decode(cromossome: &[f32]) -> Self {
+        Self::decode_mode(DirectionMode::Independent, cromossome)
+    }
+
+    /// like `try_decode`, panicking on an invalid cromossome
+    pub fn decode_mode(mode: DirectionMode, cromossome: &[f32]) -> Self {
+        Self::try_decode(mode, cromossome).unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    /// combines the conditions of both rules like `RuleStructure::legacy`, to reproduce the results of
+    /// cromossomes without structure genes optimized before every condition was required
+    pub fn legacy_structure(mut self) -> Self {
+        self.long_rule = self.long_rule.map(|rule| rule.structure(RuleStructure::legacy()));
+        self.short_rule = self.short_rule.map(|rule| rule.structure(RuleStructure::legacy()));
+        self
+    }
+
+    /// only enters long trades in the `long` regimes and short trades in the `short` regimes, labeled by a
+    /// `RegimeClassifier` with its default settings
+    pub fn regime_filter(mut self, long: &[Regime], short: &[Regime]) -> Self {
+        self.long_rule = self.long_rule.map(|rule| rule.regime_filter(RegimeClassifier::default(), long));
+        self.short_rule = self.short_rule.map(|rule| rule.regime_filter(RegimeClassifier::default(), short));
+        self
+    }
+}
+
+impl fmt::Display for SingleStrategy {
+    // the effective rules of the strategy, without the conditions switched off
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let describe = |rule: &Option<TradeRule>| rule.as_ref().map_or_else(|| "never".to_string(), TradeRule::to_string);
+        writeln!(f, "leverage: {}x", self.leverage)?;
+        writeln!(f, "long entry: {}", describe(&self.long_rule))?;
+        write!(f, "short entry: {}", describe(&self.short_rule))
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::indicators::price_source::PriceSource;
+
+    fn long(strategy: &SingleStrategy) -> &TradeRule {
+        strategy.long_rule.as_ref().unwrap()
+    }
+
+    fn short(strategy: &SingleStrategy) -> &TradeRule {
+        strategy.short_rule.as_ref().unwrap()
+    }
 
     #[test]
     fn test_decode_single_strategy() {
-        let strategy = SingleStrategy::decode(vec![0.3;36].as_slice());
+        let strategy = SingleStrategy::decode(vec![0.3;STRATEGY_GENES].as_slice());
         assert_eq!(strategy.leverage, 18);
-        assert_eq!(strategy.long_rule.ema_min_percentage_diff_from_price.round(), 30.0);
-        assert_eq!(strategy.short_rule.ema_min_percentage_diff_from_price.round(), 30.0);
+        assert_eq!(long(&strategy).ema_min_percentage_diff_from_price.round(), 30.0);
+        assert_eq!(short(&strategy).ema_min_percentage_diff_from_price.round(), 30.0);
+        assert_eq!(strategy.position_sizer.method(), SizingMethod::FixedFraction(0.015));
+    }
+
+    #[test]
+    fn test_decode_position_sizing_genes() {
+        let mut cromossome = vec![0.3; STRATEGY_GENES];
+        cromossome.extend([0.7, 0.5]);
+        let strategy = SingleStrategy::decode(cromossome.as_slice());
+        assert_eq!(strategy.position_sizer.method(), SizingMethod::RiskPerTrade(0.025500001));
+    }
+
+    #[test]
+    fn test_warmup_period() {
+        // the macd is the slowest indicator, a slow average of 31 candles followed by a signal of 31
+        let strategy = SingleStrategy::decode(vec![0.3;STRATEGY_GENES].as_slice());
+        assert_eq!(strategy.warmup_period(), 61);
+    }
+
+    #[test]
+    fn test_decode_moving_average_genes() {
+        let mut cromossome = vec![0.3; STRATEGY_GENES];
+        cromossome[36] = 0.15;
+        cromossome[37] = 0.0;
+        cromossome[38] = 0.4;
+        cromossome[39] = 1.0;
+        let strategy = SingleStrategy::decode(cromossome.as_slice());
+        assert_eq!(long(&strategy).moving_averages(), ("EMA(31)".to_string(), "SMA(31)".to_string()));
+        assert_eq!(short(&strategy).moving_averages(), ("HMA(31)".to_string(), "ZLEMA(31)".to_string()));
+    }
+
+    #[test]
+    fn test_decode_price_source_genes() {
+        let mut cromossome = vec![0.3; STRATEGY_GENES];
+        cromossome[40] = 0.4;
+        cromossome[41] = 0.8;
+        let strategy = SingleStrategy::decode(cromossome.as_slice());
+        assert_eq!(long(&strategy).source, PriceSource::Close);
+        assert_eq!(short(&strategy).source, PriceSource::Ohlc4);
+    }
+
+    #[test]
+    fn test_decode_structure_genes() {
+        let mut cromossome = vec![0.3; SingleStrategy::schema(DirectionMode::Independent, true, true).size()];
+        // only the rsi band on the long rule and nothing on the short rule
+        cromossome[STRATEGY_GENES..STRATEGY_GENES + 4].copy_from_slice(&[0.0, 1.0, 0.0, 0.0]);
+        let strategy = SingleStrategy::decode(cromossome.as_slice());
+        assert_eq!(long(&strategy).to_string(), "30.00 < RSI(30)[low] < 30.00");
+        assert_eq!(short(&strategy).to_string(), "never");
+        assert_eq!(strategy.position_sizer.method(), SizingMethod::FixedNotional(3070.0));
+        // the macd isn't used anymore, the slowest indicator is the atr of the position sizer
+        assert_eq!(strategy.warmup_period(), 31);
+    }
+
+    #[test]
+    fn test_schema() {
+        let sizes: Vec<usize> = [(false, false), (false, true), (true, false), (true, true)].iter()
+            .map(|(structure, sizing)| SingleStrategy::schema(DirectionMode::Independent, *structure, *sizing).size())
+            .collect();
+        assert_eq!(sizes, [STRATEGY_GENES, 44, STRATEGY_GENES + STRUCTURE_GENES, 58]);
+
+        let error = SingleStrategy::try_decode(DirectionMode::Independent, &[0.3; 12]).err().unwrap();
+        assert_eq!(error.to_string(), "the cromossome must have 42, 44, 56 or 58 genes, but it had 12");
+        let mut cromossome = vec![0.3; STRATEGY_GENES];
+        cromossome[8] = -0.1;
+        let error = SingleStrategy::try_decode(DirectionMode::Independent, &cromossome).err().unwrap();
+        assert_eq!(error.to_string(), "the gene long.macd_fast_period must be between 0.0 and 1.0, but it was -0.1");
+
+        let genes = SingleStrategy::genes(DirectionMode::Independent, &[0.3; STRATEGY_GENES]).unwrap().to_string();
+        let lines: Vec<&str> = genes.lines().collect();
+        assert_eq!(lines.len(), STRATEGY_GENES);
+        assert_eq!(lines[..3], ["strategy.leverage: 18", "long.take_profit_atr: 6.0700", "long.take_profit_atr_period: 31"]);
+        assert!(lines.contains(&"short.ema_type: wma"), "{}", genes);
+        assert!(lines.contains(&"long.source: low"), "{}", genes);
+    }
+
+    // a rule structure decoded from its genes alone
+    fn structure(genes: &[f32]) -> RuleStructure {
+        let schema = GeneSchema::new().sections(&[Section::new("rule", &RULE_STRUCTURE_SCHEMA)]);
+        RuleStructure::decode(&schema.decode(genes).unwrap(), "rule")
+    }
+
+    #[test]
+    fn test_rule_structure() {
+        let descriptions = || ["a".to_string(), "b".to_string(), "c".to_string(), "d".to_string()];
+        let all = RuleStructure::default();
+        assert_eq!(all.describe(descriptions()), "a and b and c and d");
+        assert!(!all.evaluate([false, false, false, true]));
+        assert!(all.evaluate([true; 4]));
+
+        let legacy = RuleStructure::legacy();
+        assert_eq!(legacy.describe(descriptions()), "(a and b and c) or d");
+        assert!(legacy.evaluate([false, false, false, true]));
+
+        let grouped = structure(&[1.0, 1.0, 1.0, 1.0, 0.0, 1.0, 0.0]);
+        assert_eq!(grouped.describe(descriptions()), "(a and b) or (c and d)");
+        assert!(grouped.evaluate([true, false, true, true]));
+        assert!(!grouped.evaluate([true, false, false, true]));
+
+        let alternatives = structure(&[0.0, 0.9, 0.2, 0.5, 1.0, 0.0, 0.0]);
+        assert_eq!(alternatives.describe(descriptions()), "b or d");
+        assert!(alternatives.evaluate([false, false, false, true]));
+
+        let disabled = structure(&[0.0; 7]);
+        assert_eq!(disabled.describe(descriptions()), "never");
+        assert!(!disabled.evaluate([true; 4]));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_display() {
+        let strategy = SingleStrategy::decode(vec![0.3;STRATEGY_GENES].as_slice());
+        let text = strategy.to_string();
+        assert!(text.starts_with("leverage: 18x\nlong entry: MACD(31, 31, 31)[low] signal > -400.00 and 30.00 < RSI(30)[low] < 30.00 and "), "{}", text);
+        // the sma distance alone triggers trades in the legacy rules
+        let legacy = SingleStrategy::decode(&[0.3; STRATEGY_GENES]).legacy_structure().to_string();
+        assert!(legacy.starts_with("leverage: 18x\nlong entry: (MACD(31, 31, 31)[low] signal > -400.00 and "), "{}", legacy);
+        assert!(legacy.contains(" >= 30.07) or difference of WMA(31)[low] from WMA(31)[low] <= 30.07\nshort entry: ("), "{}", legacy);
+        assert!(text.ends_with(" <= 30.07"), "{}", text);
+    }
+
+    #[test]
+    fn test_ema_distance_of_the_volume() {
+        // only the distance from an ema of 2 candles, of at least 10%, on the volume of the candles
+        let mut cromossome = vec![0.3; SingleStrategy::schema(DirectionMode::Independent, true, false).size()];
+        cromossome[12] = 0.0;
+        cromossome[13] = 0.0;
+        cromossome[36] = 0.15;
+        cromossome[40] = 1.0;
+        cromossome[STRATEGY_GENES..STRATEGY_GENES + 4].copy_from_slice(&[0.0, 0.0, 1.0, 0.0]);
+        let mut strategy = SingleStrategy::decode(&cromossome);
+        assert_eq!(long(&strategy).source, PriceSource::Volume);
+        assert_eq!(long(&strategy).to_string(), "difference of volume from EMA(2)[volume] >= 0.10");
+
+        // a steady volume far from the close doesn't signal, a volume spike does
+        let rule = strategy.long_rule.as_mut().unwrap();
+        let candle = |volume: f32| Candlestick::new().open(100.0).high(101.0).low(99.0).close(100.0).volume(volume);
+        let signals: Vec<bool> = [1000.0, 1000.0, 1000.0, 5000.0].iter().map(|volume| rule.evaluate(&candle(*volume))).collect();
+        assert_eq!(signals, [false, false, false, true]);
+    }
+
+    #[test]
+    fn test_short_rule_targets() {
+        let mut strategy = SingleStrategy::decode(&[0.3; STRATEGY_GENES]);
+        for i in 0..100 {
+            let price = 100.0 + (i as f32 / 5.0).sin();
+            strategy.new_candlestick(&Candlestick::new().open(price).high(price + 1.0).low(price - 1.0).close(price));
+        }
+        // the short take profit is below the lowest prices and the stoploss above the highest ones
+        assert!(short(&strategy).evaluate_take_profit() < 98.0, "{}", short(&strategy).evaluate_take_profit());
+        assert!(short(&strategy).evaluate_stop_loss() > 102.0, "{}", short(&strategy).evaluate_stop_loss());
+        assert!(long(&strategy).evaluate_take_profit() > 102.0);
+        assert!(long(&strategy).evaluate_stop_loss() < 98.0);
+    }
+
+    #[test]
+    fn test_direction_modes() {
+        let modes = [DirectionMode::Independent, DirectionMode::Symmetric, DirectionMode::LongOnly, DirectionMode::ShortOnly];
+        let sizes: Vec<usize> = modes.iter().map(|mode| SingleStrategy::schema(*mode, true, false).size()).collect();
+        assert_eq!(sizes, [STRATEGY_GENES + STRUCTURE_GENES, 28, 28, 28]);
+
+        let long_only = SingleStrategy::decode_mode(DirectionMode::LongOnly, &[0.3; 21]);
+        assert!(long_only.short_rule.is_none());
+        assert!(long_only.to_string().ends_with("\nshort entry: never"));
+        let short_only = SingleStrategy::decode_mode(DirectionMode::ShortOnly, &[0.3; 21]);
+        assert!(short_only.long_rule.is_none());
+        let genes = SingleStrategy::genes(DirectionMode::ShortOnly, &[0.3; 21]).unwrap().to_string();
+        assert_eq!(genes.lines().nth(1), Some("short.take_profit_atr: 6.0700"));
+
+        // the rsi bounds of the shared rule are 20 and 35
+        let mut cromossome = vec![0.3; 21];
+        cromossome[6] = 0.2;
+        cromossome[7] = 0.35;
+        let symmetric = SingleStrategy::decode_mode(DirectionMode::Symmetric, &cromossome);
+        assert!(long(&symmetric).to_string().starts_with("MACD(31, 31, 31)[low] signal > -400.00 and 20.00 < RSI(30)[low] < 35.00 and "));
+        assert!(short(&symmetric).to_string().starts_with("MACD(31, 31, 31)[low] signal < 400.00 and 65.00 < RSI(30)[low] < 80.00 and "));
+        assert_eq!(long(&symmetric).warmup_period(), short(&symmetric).warmup_period());
+    }
+}
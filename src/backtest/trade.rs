@@ -1,8 +1,7 @@
-use std::thread::current;
-
 use crate::candlestick;
 use candlestick::Candlestick;
 
+#[derive(Debug)]
 pub struct Trade {
     open_timestamp: u64,
     close_timestamp: u64,
@@ -20,9 +19,10 @@ pub struct Trade {
     stoploss: Option<f32>,
     takeprofit: Option<f32>,
     pub liquidation_price: f32,
+    pub candles_open: u32, // amount of candlesticks processed since the trade was opened
 }
 
-#[derive(PartialEq, Eq)]
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
 pub enum TradeDirection {
     Long,
     Short,
@@ -30,6 +30,11 @@ pub enum TradeDirection {
 
 impl Trade {
     pub fn open(side: TradeDirection, position_size: f32, start_candle: &Candlestick, leverage: u8, slipage: f32, fee_percentage: f32) -> Trade {
+        Trade::open_at_price(side, position_size, start_candle, start_candle.close, leverage, slipage, fee_percentage)
+    }
+
+    // opens the trade at a given fill price inside the start candle (e.g. a limit or stop entry order)
+    pub fn open_at_price(side: TradeDirection, position_size: f32, start_candle: &Candlestick, price: f32, leverage: u8, slipage: f32, fee_percentage: f32) -> Trade {
         let mut new_trade = Trade {
             open_timestamp: start_candle.open_time,
             leverage: leverage,
@@ -47,8 +52,9 @@ impl Trade {
             stoploss: None,
             takeprofit: None,
             liquidation_price: 0.0,
+            candles_open: 0,
         };
-        new_trade.avg_entry_price = new_trade.slipage_adjusted_price(start_candle.close, true);
+        new_trade.avg_entry_price = new_trade.slipage_adjusted_price(price, true);
         new_trade.apply_transaction_fee(position_size);
         new_trade.liquidation_price = new_trade.liquidation_price();
         new_trade
@@ -70,6 +76,24 @@ impl Trade {
         }   
     }
 
+    // moves the stoploss to the average entry price, which the validation of `stoploss` doesn't allow
+    pub fn stoploss_to_breakeven(&mut self) {
+        self.stoploss = Some(self.avg_entry_price);
+    }
+
+    // value of the position still open in USD without leverage
+    pub fn open_position_size(&self) -> f32 {
+        self.current_position_size
+    }
+
+    pub fn stoploss_price(&self) -> Option<f32> {
+        self.stoploss
+    }
+
+    pub fn takeprofit_price(&self) -> Option<f32> {
+        self.takeprofit
+    }
+
     fn liquidation_price(&self) -> f32 {
         let entry = self.avg_entry_price;
         let margin = self.current_position_size * 0.95;
@@ -87,6 +111,7 @@ impl Trade {
         self.avg_entry_price = self.current_position_size / (units_already_open + units_to_open);
 
         self.apply_transaction_fee(position_size_increase);
+        self.liquidation_price = self.liquidation_price();
     }
 
     pub fn decrease_position(&mut self, price_to_decrease: f32, position_size_decrease: f32) {
@@ -115,30 +140,31 @@ impl Trade {
         self.result
     }
 
+    // returns the profit or loss incurred by the trade when closed at the given price inside the end candle
+    pub fn close_at_price(&mut self, end_candle: &Candlestick, price: f32) -> f32 {
+        self.close_timestamp = end_candle.close_time;
+        self.decrease_position(price, self.current_position_size);
+        self.result - self.total_fee_paid
+    }
+
     // returns the loss incurred by the trade
     pub fn close_on_stoploss(&mut self, end_candle: &Candlestick) -> f32{
-        if self.stoploss.is_none() {
-            return 0.0;
+        match self.stoploss {
+            Some(stoploss) => self.close_at_price(end_candle, stoploss),
+            None => 0.0,
         }
-        self.close_timestamp = end_candle.close_time;
-        self.decrease_position(self.stoploss.unwrap(), self.current_position_size);
-        self.result - self.total_fee_paid
     }
 
     // returns the profit incurred by the trade
     pub fn close_on_takeprofit(&mut self, end_candle: &Candlestick) -> f32{
-        if self.takeprofit.is_none() {
-            return 0.0;
+        match self.takeprofit {
+            Some(takeprofit) => self.close_at_price(end_candle, takeprofit),
+            None => 0.0,
         }
-        self.close_timestamp = end_candle.close_time;
-        self.decrease_position(self.takeprofit.unwrap(), self.current_position_size);
-        self.result - self.total_fee_paid
     }
 
     pub fn close_on_liquidation(&mut self, end_candle: &Candlestick) -> f32{
-        self.close_timestamp = end_candle.close_time;
-        self.decrease_position(self.liquidation_price, self.current_position_size);
-        self.result - self.total_fee_paid
+        self.close_at_price(end_candle, self.liquidation_price)
     }
 
     // amount of minutes the trade was open
@@ -185,9 +211,10 @@ impl Trade {
         self.fee_percentage * position_size * self.leverage as f32
     }
 
+    // profit or loss of the position still open if it was closed on the current candle, fees are not included
     pub fn check_profit(&self, current_candle: &Candlestick) -> f32 {
         let adjusted_price = self.slipage_adjusted_price(current_candle.close, false);
-        self.calculate_result(adjusted_price, self.closed_position_size)
+        self.calculate_result(adjusted_price, self.current_position_size)
     }
 
     pub fn is_takeprofit_reached(&self, current_candle: &Candlestick) -> bool {
@@ -317,4 +344,29 @@ mod tests {
 
         assert_eq!(new_trade.result, 2000.0);
     }
+
+    #[test]
+    fn scale_in_and_out_keeps_average_prices() {
+        let mut new_trade = Trade::open(TradeDirection::Long, 100.0, &Candlestick::new().close(100.0), 10, 0.0, 0.0);
+        new_trade.increase_position(&Candlestick::new().close(200.0), 100.0);
+        assert_eq!(new_trade.avg_entry_price.round(), 133.0);
+        assert_eq!(new_trade.open_position_size(), 200.0);
+        assert_eq!(new_trade.liquidation_price, new_trade.liquidation_price());
+
+        new_trade.decrease_position(150.0, 100.0);
+        new_trade.decrease_position(300.0, 100.0);
+        assert_eq!(new_trade.open_position_size(), 0.0);
+        assert_eq!(new_trade.avg_end_price.round(), 200.0);
+        assert_eq!(new_trade.result.round(), 1375.0);
+    }
+
+    #[test]
+    fn open_and_close_at_fill_prices() {
+        let candle = &Candlestick::new().close(200.0);
+        let mut new_trade = Trade::open_at_price(TradeDirection::Long, 200.0, candle, 190.0, 10, 0.0, 0.0);
+        assert_eq!(new_trade.avg_entry_price, 190.0);
+
+        assert_eq!(new_trade.close_at_price(candle, 209.0), 200.0);
+        assert_eq!(new_trade.avg_end_price, 209.0);
+    }
 }
\ No newline at end of file
@@ -1,28 +1,37 @@
 use std::f32;
+use std::fmt;
+use std::sync::Arc;
 
 use crate::candlestick::Candlestick;
 use crate::backtest::trade::TradeDirection;
-use crate::utils::{map_range, percentage_difference};
+use crate::utils::percentage_difference;
+use crate::backtest::gene_schema::{Constraint, DecodedGenes, Gene};
 
-use crate::indicators::traits::Next;
+use crate::indicators::traits::{Indicator, MovingAverage, Next};
+use crate::indicators::moving_average::MovingAverageType;
+use crate::indicators::price_source::PriceSource;
+use crate::indicators::cache::{IndicatorKey, RangeCache};
+use crate::indicators::regime::{Regime, RegimeClassifier};
+use crate::indicators::exponential_moving_average::ExponentialMovingAverage as Ema;
 use crate::indicators::simple_moving_average::SimpleMovingAverage as Sma;
 use crate::indicators::average_true_range::AverageTrueRange as ATR;
 use crate::indicators::relative_strength_index::RelativeStrengthIndex as Rsi;
-use crate::indicators::exponential_moving_average::ExponentialMovingAverage as Ema;
 use crate::indicators::moving_average_convergence_divergence::MovingAverageConvergenceDivergence as Macd;
 
 pub struct TradeRule {
     direction: TradeDirection,
-    sma: Sma,
+    pub source: PriceSource, // value of the candles fed to the rsi, macd and the averages
+    sma: Box<dyn MovingAverage>,
     rsi: Rsi,
     macd: Macd,
-    ema: Ema,
+    macd_signal_period: usize,
+    ema: Box<dyn MovingAverage>,
     take_profit_atr: ATR,
     stoploss_atr: ATR,
     
     macd_target_value: f32,
-    rsi_lower_bound: f32,
-    rsi_higher_bound: f32,
+    rsi_low_bound: f32,
+    rsi_high_bound: f32,
     pub ema_min_percentage_diff_from_price: f32,
     pub sma_max_percentage_diff_from_ema: f32,
 
@@ -31,6 +40,177 @@ pub struct TradeRule {
 
     ema_takeprofit: Sma,
     ema_stoploss: Sma,
+
+    // series that can be precomputed: rsi, macd fast ema, macd slow ema, ema, sma, take profit atr and stoploss atr
+    keys: [IndicatorKey; 7],
+    precomputed: Option<Precomputed>,
+
+    regime_filter: Option<RegimeFilter>,
+    structure: RuleStructure,
+    mirrored: bool, // the macd signal must be below the target instead of above it
+}
+
+/// genes of the targets and of the conditions of a `TradeRule`
+pub const RULE_SCHEMA: [Gene; 17] = [
+    Gene::linear("take_profit_atr", 0.1, 20.0),
+    Gene::integer("take_profit_atr_period", 2.0, 100.0),
+    Gene::linear("stoploss_atr", 0.1, 20.0),
+    Gene::integer("stoploss_atr_period", 2.0, 50.0),
+    Gene::integer("rsi_period", 0.0, 100.0),
+    Gene::linear("rsi_low", 0.0, 100.0),
+    Gene::linear("rsi_high", 0.0, 100.0),
+    Gene::integer("macd_fast_period", 2.0, 100.0),
+    Gene::integer("macd_slow_period", 2.0, 100.0),
+    Gene::integer("macd_signal_period", 2.0, 100.0),
+    Gene::linear("macd_target", -1000.0, 1000.0),
+    Gene::integer("ema_period", 2.0, 100.0),
+    Gene::linear("ema_min_distance", 0.1, 100.0),
+    Gene::integer("sma_period", 2.0, 100.0),
+    Gene::linear("sma_max_distance", 0.1, 100.0),
+    // candles of the highs and lows the take profit and the stoploss are placed from
+    Gene::integer("take_profit_window", 1.0, 100.0),
+    Gene::integer("stoploss_window", 1.0, 100.0),
+];
+
+pub const RULE_CONSTRAINTS: [Constraint; 1] = [Constraint::Ordered("rsi_low", "rsi_high")];
+
+/// genes of the kinds of the two averages of a `TradeRule`
+pub const RULE_AVERAGES_SCHEMA: [Gene; 2] = [
+    Gene::choice("ema_type", &MovingAverageType::NAMES),
+    Gene::choice("sma_type", &MovingAverageType::NAMES),
+];
+
+/// gene of the value of the candles used by a `TradeRule`
+pub const RULE_SOURCE_SCHEMA: [Gene; 1] = [Gene::choice("source", &PriceSource::NAMES)];
+
+/// genes of a `RuleStructure`, a switch for each condition of `Condition::ALL` followed by the connectives between them
+pub const RULE_STRUCTURE_SCHEMA: [Gene; 7] = [
+    Gene::choice("macd", &["off", "on"]),
+    Gene::choice("rsi_band", &["off", "on"]),
+    Gene::choice("ema_distance", &["off", "on"]),
+    Gene::choice("sma_distance", &["off", "on"]),
+    Gene::choice("connective_1", &["and", "or"]),
+    Gene::choice("connective_2", &["and", "or"]),
+    Gene::choice("connective_3", &["and", "or"]),
+];
+
+/// Entry condition of a `TradeRule` that can be switched on and off
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Condition {
+    /// the macd signal is above the target value
+    Macd,
+    /// the rsi is between the two bounds
+    RsiBand,
+    /// the close is far enough from the ema
+    EmaDistance,
+    /// the sma is close enough to the ema
+    SmaDistance,
+}
+
+impl Condition {
+    pub const ALL: [Condition; 4] = [Condition::Macd, Condition::RsiBand, Condition::EmaDistance, Condition::SmaDistance];
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Connective {
+    And,
+    Or,
+}
+
+/// Conditions of a `TradeRule` in use and how they are combined. The connectives join the enabled conditions
+/// in the order of `Condition::ALL`, `and` binding tighter than `or`. A rule without conditions never signals
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RuleStructure {
+    enabled: [bool; 4], // of each condition of `Condition::ALL`
+    connectives: [Connective; 3], // between the first and second enabled condition, the second and third...
+}
+
+impl Default for RuleStructure {
+    /// every condition joined by `and`, the rule used by cromossomes without structure genes
+    fn default() -> Self {
+        Self {
+            enabled: [true; 4],
+            connectives: [Connective::And; 3],
+        }
+    }
+}
+
+impl RuleStructure {
+    /// every condition combined as `(macd and rsi band and ema distance) or sma distance` like the first
+    /// versions of `TradeRule`, where the sma distance alone entered trades. Only kept to reproduce older results
+    pub fn legacy() -> Self {
+        Self {
+            enabled: [true; 4],
+            connectives: [Connective::And, Connective::And, Connective::Or],
+        }
+    }
+
+    /// reads the `RULE_STRUCTURE_SCHEMA` genes of the section, a cromossome without them uses the default
+    pub fn decode(genes: &DecodedGenes, section: &str) -> Self {
+        if !genes.contains(section, RULE_STRUCTURE_SCHEMA[0].name) {
+            return Self::default();
+        }
+        let enabled = |gene: &str| genes.choice(section, gene, &[false, true]);
+        let connective = |gene: &str| genes.choice(section, gene, &[Connective::And, Connective::Or]);
+        Self {
+            enabled: [enabled("macd"), enabled("rsi_band"), enabled("ema_distance"), enabled("sma_distance")],
+            connectives: [connective("connective_1"), connective("connective_2"), connective("connective_3")],
+        }
+    }
+
+    pub fn is_enabled(&self, condition: Condition) -> bool {
+        Condition::ALL.iter().zip(self.enabled).any(|(candidate, enabled)| *candidate == condition && enabled)
+    }
+
+    /// combines the result of each condition of `Condition::ALL`, disabled ones are ignored
+    pub fn evaluate(&self, signals: [bool; 4]) -> bool {
+        self.groups(signals).into_iter().any(|group| !group.is_empty() && group.into_iter().all(|signal| signal))
+    }
+
+    // values of the enabled conditions split in the groups joined by `and`, the groups are joined by `or`
+    fn groups<T: Clone>(&self, values: [T; 4]) -> Vec<Vec<T>> {
+        let mut groups = vec![Vec::new()];
+        let enabled = values.iter().zip(self.enabled).filter(|(_, enabled)| *enabled).map(|(value, _)| value);
+        for (index, value) in enabled.enumerate() {
+            if index > 0 && self.connectives[index - 1] == Connective::Or {
+                groups.push(Vec::new());
+            }
+            groups.last_mut().unwrap().push(value.clone());
+        }
+        groups
+    }
+
+    /// the combination of the given descriptions of each condition, e.g. `(a and b) or c`
+    pub fn describe(&self, descriptions: [String; 4]) -> String {
+        let groups: Vec<Vec<String>> = self.groups(descriptions).into_iter().filter(|group| !group.is_empty()).collect();
+        if groups.is_empty() {
+            return "never".to_string();
+        }
+        let parenthesize = groups.len() > 1;
+        groups.into_iter().map(|group| {
+            let text = group.join(" and ");
+            if parenthesize && group.len() > 1 { format!("({})", text) } else { text }
+        }).collect::<Vec<String>>().join(" or ")
+    }
+}
+
+// entries are only allowed while the classifier labels the candles with one of the allowed regimes
+struct RegimeFilter {
+    classifier: RegimeClassifier,
+    allowed: Vec<Regime>,
+}
+
+// precomputed series of the current range, read on each candle instead of updating the indicators
+struct Precomputed {
+    series: Vec<Arc<[f32]>>,
+    index: usize, // position of the next candle
+    macd_signal: Ema, // the signal is an ema of the macd so it is still updated on every candle
+}
+
+impl Precomputed {
+    fn last(&self, key: usize) -> f32 {
+        self.series[key][self.index.saturating_sub(1)]
+    }
 }
 
 impl TradeRule {
@@ -43,59 +223,165 @@ impl TradeRule {
         self.stoploss_atr.reset();
         self.ema_takeprofit.reset();
         self.ema_stoploss.reset();
+        self.precomputed = None;
+        if let Some(filter) = self.regime_filter.as_mut() {
+            filter.classifier.reset();
+        }
+    }
+
+    /// only signals entries on candles labeled by `classifier` with one of the `allowed` regimes
+    pub fn regime_filter(mut self, classifier: RegimeClassifier, allowed: &[Regime]) -> Self {
+        self.regime_filter = Some(RegimeFilter { classifier, allowed: allowed.to_vec() });
+        self
     }
 
-    pub fn new(direction: TradeDirection, cromossome: &[f32]) -> Self {
-        let macd_period1 = map_range((2.0, 100.0), cromossome[7]) as usize;
-        let macd_period2 = map_range((2.0, 100.0), cromossome[8]) as usize;
-        let macd_singal_period = map_range((2.0, 100.0), cromossome[9]) as usize;
+    /// takes the indicator series of the range about to be evaluated from the cache, until the next reset
+    /// `evaluate` must receive exactly the candles of the range from the first one
+    pub fn precompute(&mut self, cache: &RangeCache) {
+        self.precomputed = Some(Precomputed {
+            series: self.keys.iter().map(|key| cache.get(*key)).collect(),
+            index: 0,
+            macd_signal: Ema::new(self.macd_signal_period),
+        });
+    }
+
+    /// builds the rule from the `RULE_SCHEMA`, `RULE_AVERAGES_SCHEMA` and `RULE_SOURCE_SCHEMA` genes of the section,
+    /// the two averages compared with the price are computed like the rsi and macd on the decoded source
+    pub fn new(direction: TradeDirection, genes: &DecodedGenes, section: &str) -> Self {
+        let value = |gene: &str| genes.value(section, gene);
+        let period = |gene: &str| genes.period(section, gene);
+        let macd_period1 = period("macd_fast_period");
+        let macd_period2 = period("macd_slow_period");
+        let macd_singal_period = period("macd_signal_period");
 
-        let rsi_bound1 = map_range((0.0, 100.0), cromossome[5]);
-        let rsi_bound2 = map_range((0.0, 100.0), cromossome[6]);
+        let rsi_period = period("rsi_period");
+        let ema_period = period("ema_period");
+        let sma_period = period("sma_period");
+        let take_profit_atr_period = period("take_profit_atr_period");
+        let stoploss_atr_period = period("stoploss_atr_period");
+
+        let ema_type = genes.choice(section, "ema_type", &MovingAverageType::ALL);
+        let sma_type = genes.choice(section, "sma_type", &MovingAverageType::ALL);
+        let source = genes.choice(section, "source", &PriceSource::ALL);
 
         TradeRule {
             direction: direction,
-            take_profit_target_atr: map_range( (0.1, 20.0),cromossome[0]),  
-            take_profit_atr: ATR::new(map_range((2.0, 100.0), cromossome[1]) as usize),
-            stop_loss_target_atr: map_range( (0.1, 20.0),cromossome[2]),
-            stoploss_atr: ATR::new(map_range((2.0, 50.0),cromossome[3]) as usize),
-            rsi: Rsi::new(map_range((0.0, 100.0), cromossome[4]) as usize),
-            rsi_lower_bound: rsi_bound1.max(rsi_bound2),
-            rsi_higher_bound: rsi_bound1.min(rsi_bound2),
+            source,
+            take_profit_target_atr: value("take_profit_atr"),
+            take_profit_atr: ATR::new(take_profit_atr_period),
+            stop_loss_target_atr: value("stoploss_atr"),
+            stoploss_atr: ATR::new(stoploss_atr_period),
+            rsi: Rsi::new(rsi_period),
+            rsi_low_bound: value("rsi_low"),
+            rsi_high_bound: value("rsi_high"),
             macd: Macd::new(macd_period1, macd_period2, macd_singal_period),
-            macd_target_value: map_range((-1000.0, 1000.0), cromossome[10]),
-            ema: Ema::new(map_range((2.0, 100.0), cromossome[11]) as usize),
-            ema_min_percentage_diff_from_price: map_range((0.1, 100.0), cromossome[12]),
-            sma: Sma::new(map_range((2.0, 100.0), cromossome[13]) as usize),
-            sma_max_percentage_diff_from_ema: map_range((0.1, 100.0), cromossome[14]),
-            ema_takeprofit: Sma::new(map_range((1.0, 100.0), cromossome[15]) as usize),
-            ema_stoploss: Sma::new(map_range((1.0, 100.0), cromossome[16]) as usize),
+            macd_signal_period: macd_singal_period,
+            macd_target_value: value("macd_target"),
+            ema: ema_type.build(ema_period),
+            ema_min_percentage_diff_from_price: value("ema_min_distance"),
+            sma: sma_type.build(sma_period),
+            sma_max_percentage_diff_from_ema: value("sma_max_distance"),
+            ema_takeprofit: Sma::new(period("take_profit_window")),
+            ema_stoploss: Sma::new(period("stoploss_window")),
+            keys: [
+                IndicatorKey::Rsi(rsi_period, source),
+                IndicatorKey::MovingAverage(MovingAverageType::Ema, macd_period1, source),
+                IndicatorKey::MovingAverage(MovingAverageType::Ema, macd_period2, source),
+                IndicatorKey::MovingAverage(ema_type, ema_period, source),
+                IndicatorKey::MovingAverage(sma_type, sma_period, source),
+                IndicatorKey::Atr(take_profit_atr_period),
+                IndicatorKey::Atr(stoploss_atr_period),
+            ],
+            precomputed: None,
+            regime_filter: None,
+            structure: RuleStructure::default(),
+            mirrored: false,
         } 
     }
 
+    /// mirrors the conditions, so a rule decoded for one direction enters the other direction in the mirrored
+    /// situation: the macd signal must be below the negated target and the rsi band is reflected around 50
+    pub fn mirrored(mut self) -> Self {
+        self.mirrored = !self.mirrored;
+        self.macd_target_value = -self.macd_target_value;
+        (self.rsi_low_bound, self.rsi_high_bound) = (100.0 - self.rsi_high_bound, 100.0 - self.rsi_low_bound);
+        self
+    }
+
+    /// conditions used by the rule and how they are combined, every condition joined by `and` by default
+    pub fn structure(mut self, structure: RuleStructure) -> Self {
+        self.structure = structure;
+        self
+    }
+
     pub fn evaluate(&mut self, candle: &Candlestick) -> bool {
-        let rsi = self.rsi.next(candle.close);
-        let macd = self.macd.next(candle);
-        let ema = self.ema.next(candle);
-        let sma = self.sma.next(candle);
-        let pd_ema_from_close = percentage_difference(ema, candle.close);
+        let price = self.source.of(candle);
+        let (rsi, macd_signal, ema, sma) = match self.precomputed.as_mut() {
+            Some(precomputed) => {
+                let i = precomputed.index;
+                precomputed.index += 1;
+                let series = &precomputed.series;
+                let macd_signal = precomputed.macd_signal.next(series[1][i] - series[2][i]);
+                (series[0][i], macd_signal, series[3][i], series[4][i])
+            }
+            None => {
+                let rsi = self.rsi.next(price);
+                let macd_signal = self.macd.next(price).signal;
+                let ema = self.ema.next(price);
+                let sma = self.sma.next(price);
+                self.take_profit_atr.next(candle);
+                self.stoploss_atr.next(candle);
+                (rsi, macd_signal, ema, sma)
+            }
+        };
+        // the ema is an average of the source, so the distance is measured from the source and not the close
+        let pd_ema_from_price = percentage_difference(ema, price);
         let pd_sma_from_ema = percentage_difference(sma, ema);
 
         self.ema_takeprofit.next(if self.direction == TradeDirection::Long { candle.high } else { candle.low });
         self.ema_stoploss.next(if self.direction == TradeDirection::Long { candle.low } else { candle.high });
 
-        self.take_profit_atr.next(candle);
-        self.stoploss_atr.next(candle);
-        
-        //expression that evaluates if a trade should be opened
-        macd.signal > self.macd_target_value &&
-        rsi > self.rsi_higher_bound && rsi < self.rsi_lower_bound &&
-        pd_ema_from_close >= self.ema_min_percentage_diff_from_price ||
-        pd_sma_from_ema <= self.sma_max_percentage_diff_from_ema
+        let regime_allowed = match self.regime_filter.as_mut() {
+            Some(filter) => filter.allowed.contains(&filter.classifier.next(candle)),
+            None => true,
+        };
+
+        // result of each condition in the order of `Condition::ALL`
+        let signals = [
+            if self.mirrored { macd_signal < self.macd_target_value } else { macd_signal > self.macd_target_value },
+            rsi > self.rsi_low_bound && rsi < self.rsi_high_bound,
+            pd_ema_from_price >= self.ema_min_percentage_diff_from_price,
+            pd_sma_from_ema <= self.sma_max_percentage_diff_from_ema,
+        ];
+        self.structure.evaluate(signals) && regime_allowed
+    }
+
+    /// candles needed until every indicator of the enabled conditions is ready
+    pub fn warmup_period(&self) -> usize {
+        let atr = |atr: &ATR| Indicator::<&Candlestick>::warmup_period(atr);
+        let enabled = |condition: Condition, warmup: usize| if self.structure.is_enabled(condition) { warmup } else { 0 };
+        [
+            enabled(Condition::Macd, self.macd.warmup_period()),
+            enabled(Condition::RsiBand, self.rsi.warmup_period()),
+            enabled(Condition::EmaDistance, self.ema.warmup_period()),
+            enabled(Condition::SmaDistance, self.ema.warmup_period().max(self.sma.warmup_period())),
+            atr(&self.take_profit_atr),
+            atr(&self.stoploss_atr),
+            self.ema_takeprofit.warmup_period(),
+            self.ema_stoploss.warmup_period(),
+            self.regime_filter.as_ref().map_or(0, |filter| Indicator::<&Candlestick>::warmup_period(&filter.classifier)),
+        ].into_iter().max().unwrap_or(0)
+    }
+
+    // names of the two averages compared with the price, e.g. ("EMA(20)", "SMA(50)")
+    #[cfg(test)]
+    pub fn moving_averages(&self) -> (String, String) {
+        (self.ema.to_string(), self.sma.to_string())
     }
 
     pub fn evaluate_take_profit(&self) -> f32 {
-        let diff = self.take_profit_atr.value() * self.take_profit_target_atr;
+        let atr = self.precomputed.as_ref().map_or_else(|| self.take_profit_atr.value(), |precomputed| precomputed.last(5));
+        let diff = atr * self.take_profit_target_atr;
         if self.direction == TradeDirection::Long {
             self.ema_takeprofit.max_value_on_period() + diff
         }else{
@@ -104,11 +390,30 @@ impl TradeRule {
     }
 
     pub fn evaluate_stop_loss(&self) -> f32 {
-        let diff = self.stoploss_atr.value() * self.stop_loss_target_atr;
+        let atr = self.precomputed.as_ref().map_or_else(|| self.stoploss_atr.value(), |precomputed| precomputed.last(6));
+        let diff = atr * self.stop_loss_target_atr;
         if self.direction == TradeDirection::Long {
             self.ema_stoploss.min_value_on_period() - diff
         }else{
             self.ema_stoploss.max_value_on_period() + diff
         }
     }
-}
\ No newline at end of file
+}
+impl fmt::Display for TradeRule {
+    // the entry condition with the decoded periods and thresholds, e.g. `MACD(12, 26, 9)[close] signal > 0.50 or ...`
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let source = self.source;
+        let descriptions = [
+            format!("{}[{}] signal {} {:.2}", self.macd, source, if self.mirrored { "<" } else { ">" }, self.macd_target_value),
+            format!("{:.2} < {}[{}] < {:.2}", self.rsi_low_bound, self.rsi, source, self.rsi_high_bound),
+            format!("difference of {} from {}[{}] >= {:.2}", source, self.ema, source, self.ema_min_percentage_diff_from_price),
+            format!("difference of {}[{}] from {}[{}] <= {:.2}", self.sma, source, self.ema, source, self.sma_max_percentage_diff_from_ema),
+        ];
+        write!(f, "{}", self.structure.describe(descriptions))?;
+        if let Some(filter) = &self.regime_filter {
+            let regimes: Vec<String> = filter.allowed.iter().map(Regime::to_string).collect();
+            write!(f, " while {}", regimes.join(" or "))?;
+        }
+        Ok(())
+    }
+}
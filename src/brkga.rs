@@ -4,7 +4,7 @@ use rand_pcg::{Pcg64, Lcg128Xsl64};
 use std::time::Instant;
 use crate::candlestick::{Candlestick, load_candlesticks};
 use crate::backtest::{Backtest, RunMode};
-use crate::backtest::strategy::SingleStrategy;
+use crate::backtest::strategy::{DirectionMode, SingleStrategy};
 
 pub struct BRKGA {
     fraction_top: f32, // amount of individuals considered elite
@@ -143,6 +143,12 @@ impl BRKGA {
         
         println!("validation fitness best of gen {} is {}", generation, 
             self.fitness_executor.validation_fitness(best.cromossome.as_slice()));
+        println!("{}", self.fitness_executor.decode(best.cromossome.as_slice()));
+        if generation + 1 == self.max_iterations {
+            if let Ok(genes) = SingleStrategy::genes(self.fitness_executor.direction_mode, best.cromossome.as_slice()) {
+                println!("genes of the best cromossome:\n{}", genes);
+            }
+        }
 
     }
 }
@@ -173,6 +179,8 @@ impl Clone for Individual {
 pub struct FitnessExecutor {
     backtester: Backtest,
     mode: RunMode,
+    direction_mode: DirectionMode,
+    legacy_structure: bool,
 }
 
 
@@ -181,16 +189,35 @@ impl FitnessExecutor {
         Self {
             backtester,
             mode,
+            direction_mode: DirectionMode::Independent,
+            legacy_structure: false,
         }
     }
 
+    /// decodes the cromossomes with `SingleStrategy::legacy_structure`
+    pub fn legacy_structure(mut self) -> Self {
+        self.legacy_structure = true;
+        self
+    }
+
+    /// layout of the evaluated cromossomes, the size of the cromossomes of the `BRKGA` must match it
+    pub fn direction_mode(mut self, direction_mode: DirectionMode) -> Self {
+        self.direction_mode = direction_mode;
+        self
+    }
+
+    fn decode(&self, cromossome: &[f32]) -> SingleStrategy {
+        let strategy = SingleStrategy::decode_mode(self.direction_mode, cromossome);
+        if self.legacy_structure { strategy.legacy_structure() } else { strategy }
+    }
+
     pub fn calculate_fitness(&self, cromossome: &[f32]) -> f32 {
-        let mut trading_model = SingleStrategy::decode(cromossome);
+        let mut trading_model = self.decode(cromossome);
         self.backtester.run(self.mode, &mut trading_model)
     }
 
     pub fn validation_fitness(&self, cromossome: &[f32]) -> f32 {
-        let mut trading_model = SingleStrategy::decode(cromossome);
+        let mut trading_model = self.decode(cromossome);
         self.backtester.run(RunMode::Validation, &mut trading_model)
     }
 }
@@ -198,15 +225,16 @@ impl FitnessExecutor {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::backtest::strategy::STRATEGY_GENES;
 
     #[test]
     fn test_new_brkga(){
         let config: BrkgaConfig = (0.1, 0.2, 15000, 1000, 0.6);
         let candles = load_candlesticks("test_files/ADAUSDT-30m.csv").unwrap();
         let backtest_engine = Backtest::new(candles, 12, 0.005, 0.02);
-        let brkga = BRKGA::new(1223,36, config, FitnessExecutor::new(backtest_engine, RunMode::Training));
+        let brkga = BRKGA::new(1223,STRATEGY_GENES, config, FitnessExecutor::new(backtest_engine, RunMode::Training));
         
-        assert_eq!(brkga.cromossome_size, 36);
+        assert_eq!(brkga.cromossome_size, STRATEGY_GENES);
         assert_eq!(brkga.fraction_top, 0.1);
         assert_eq!(brkga.fraction_bottom, 0.2);
         assert_eq!(brkga.population_size, 15000);
@@ -219,10 +247,10 @@ mod tests {
         let config: BrkgaConfig = (0.1, 0.2, 15000, 1000, 0.6);
         let candles = load_candlesticks("test_files/ADAUSDT-30m.csv").unwrap();
         let backtest_engine = Backtest::new(candles, 12, 0.005, 0.02);
-        let mut brkga = BRKGA::new(59841, 36, config, FitnessExecutor::new(backtest_engine, RunMode::Training));
+        let mut brkga = BRKGA::new(59841, STRATEGY_GENES, config, FitnessExecutor::new(backtest_engine, RunMode::Training));
         let first_indivual = brkga.random_individual();
 
-        assert_eq!(first_indivual.cromossome.len(), 36);
+        assert_eq!(first_indivual.cromossome.len(), STRATEGY_GENES);
 
         for i in 0..first_indivual.cromossome.len() {
             assert!(first_indivual.cromossome[i] >= 0.0 && first_indivual.cromossome[i] <= 1.0);
@@ -2,7 +2,7 @@ extern crate csv;
 extern crate serde;
 use serde::{Serialize, Deserialize};
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "PascalCase")]
 pub struct Candlestick {
     pub open_time: u64,
@@ -16,6 +16,16 @@ pub struct Candlestick {
     pub num_trades: u64,   
 }
 
+pub trait Open {
+    fn open(&self) -> f32;
+}
+
+impl Open for Candlestick {
+    fn open(&self) -> f32 {
+        self.open
+    }
+}
+
 pub trait Close {
     fn close(&self) -> f32;
 }
@@ -46,6 +56,38 @@ impl High for Candlestick {
     }
 }
 
+pub trait Volume {
+    fn volume(&self) -> f32;
+}
+
+impl Volume for Candlestick {
+    fn volume(&self) -> f32 {
+        self.volume
+    }
+}
+
+/// volume of the candle in the quote asset, e.g. USDT for BTCUSDT
+pub trait QuoteVolume {
+    fn quote_volume(&self) -> f32;
+}
+
+impl QuoteVolume for Candlestick {
+    fn quote_volume(&self) -> f32 {
+        self.quote_asset_volume
+    }
+}
+
+/// amount of trades executed during the candle
+pub trait Trades {
+    fn trades(&self) -> u64;
+}
+
+impl Trades for Candlestick {
+    fn trades(&self) -> u64 {
+        self.num_trades
+    }
+}
+
 impl Candlestick {
     pub fn new() -> Self {
         Self {
@@ -85,6 +127,16 @@ impl Candlestick {
         self.volume = val;
         self
     }
+
+    pub fn quote_asset_volume(mut self, val: f32) -> Self {
+        self.quote_asset_volume = val;
+        self
+    }
+
+    pub fn num_trades(mut self, val: u64) -> Self {
+        self.num_trades = val;
+        self
+    }
 }
 
 /// load candlestick from a structured csv file
@@ -0,0 +1,153 @@
+pub mod binance;
+pub mod mock;
+pub mod live_trader;
+
+use std::fmt;
+use std::io;
+
+use crate::candlestick::Candlestick;
+use crate::backtest::trade::TradeDirection;
+use crate::backtest::order::{OrderKind, OrderSide, TimeInForce};
+use crate::paper_trading::candle_source::CandleSource;
+
+/// Order sent to an exchange, `quantity` is in units of the traded asset
+#[derive(Debug, Clone, PartialEq)]
+pub struct OrderRequest {
+    pub symbol: String,
+    pub side: OrderSide,
+    pub kind: OrderKind,
+    pub quantity: f32,
+    pub time_in_force: TimeInForce,
+    pub reduce_only: bool, // the order can only reduce the open position
+}
+
+impl OrderRequest {
+    pub fn new(symbol: &str, side: OrderSide, kind: OrderKind, quantity: f32) -> Self {
+        Self {
+            symbol: symbol.to_string(),
+            side,
+            kind,
+            quantity,
+            time_in_force: TimeInForce::GoodTillCancelled,
+            reduce_only: false,
+        }
+    }
+
+    pub fn time_in_force(mut self, time_in_force: TimeInForce) -> Self {
+        self.time_in_force = time_in_force;
+        self
+    }
+
+    pub fn reduce_only(mut self) -> Self {
+        self.reduce_only = true;
+        self
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum OrderStatus {
+    New,
+    PartiallyFilled,
+    Filled,
+    Cancelled,
+    Expired,
+}
+
+/// Order as reported by the exchange
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExchangeOrder {
+    pub id: u64,
+    pub symbol: String,
+    pub side: OrderSide,
+    pub kind: OrderKind,
+    pub quantity: f32,
+    pub filled_quantity: f32,
+    pub average_price: f32, // average fill price, 0.0 while nothing was filled
+    pub reduce_only: bool,
+    pub status: OrderStatus,
+}
+
+/// Net position of a symbol, `quantity` is negative on short positions
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExchangePosition {
+    pub symbol: String,
+    pub quantity: f32,
+    pub entry_price: f32,
+    pub unrealized_profit: f32,
+    pub liquidation_price: f32,
+    pub leverage: u8,
+}
+
+impl ExchangePosition {
+    pub fn direction(&self) -> TradeDirection {
+        if self.quantity < 0.0 { TradeDirection::Short } else { TradeDirection::Long }
+    }
+}
+
+#[derive(Debug)]
+pub enum ExchangeError {
+    /// the exchange could not be reached or the connection was lost
+    Connection(String),
+    /// the exchange refused the request
+    Api { code: i64, message: String },
+    /// a response the client doesn't understand
+    InvalidResponse(String),
+}
+
+impl fmt::Display for ExchangeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ExchangeError::Connection(message) => write!(f, "connection error: {}", message),
+            ExchangeError::Api { code, message } => write!(f, "exchange error {}: {}", code, message),
+            ExchangeError::InvalidResponse(message) => write!(f, "invalid response: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for ExchangeError {}
+
+/// Operations needed to trade a strategy on a futures exchange
+pub trait ExchangeClient {
+    fn place_order(&mut self, order: &OrderRequest) -> Result<ExchangeOrder, ExchangeError>;
+
+    fn cancel_order(&mut self, symbol: &str, order_id: u64) -> Result<(), ExchangeError>;
+
+    fn open_orders(&mut self, symbol: &str) -> Result<Vec<ExchangeOrder>, ExchangeError>;
+
+    /// open position of the symbol, `None` when there is no position
+    fn position(&mut self, symbol: &str) -> Result<Option<ExchangePosition>, ExchangeError>;
+
+    /// balance of the asset available to open new positions
+    fn balance(&mut self, asset: &str) -> Result<f32, ExchangeError>;
+
+    fn set_leverage(&mut self, symbol: &str, leverage: u8) -> Result<(), ExchangeError>;
+
+    /// last `limit` closed klines of the interval (e.g. "5m"), oldest first
+    fn klines(&mut self, symbol: &str, interval: &str, limit: usize) -> Result<Vec<Candlestick>, ExchangeError>;
+
+    /// waits for the next closed kline of the stream, `None` when the stream ended
+    fn next_kline(&mut self, symbol: &str, interval: &str) -> Result<Option<Candlestick>, ExchangeError>;
+}
+
+/// Kline stream of an exchange as a `CandleSource`, e.g. to paper trade on live prices
+pub struct KlineSource<'a, E: ExchangeClient> {
+    exchange: &'a mut E,
+    symbol: String,
+    interval: String,
+}
+
+impl<'a, E: ExchangeClient> KlineSource<'a, E> {
+    pub fn new(exchange: &'a mut E, symbol: &str, interval: &str) -> Self {
+        Self {
+            exchange,
+            symbol: symbol.to_string(),
+            interval: interval.to_string(),
+        }
+    }
+}
+
+impl<E: ExchangeClient> CandleSource for KlineSource<'_, E> {
+    fn next_candle(&mut self) -> io::Result<Option<Candlestick>> {
+        self.exchange.next_kline(&self.symbol, &self.interval).map_err(io::Error::other)
+    }
+}
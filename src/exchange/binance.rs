@@ -0,0 +1,370 @@
+use std::net::TcpStream;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use hmac::{Hmac, Mac};
+use serde_json::Value;
+use sha2::Sha256;
+use tungstenite::{Message, WebSocket};
+use tungstenite::stream::MaybeTlsStream;
+
+use crate::candlestick::Candlestick;
+use crate::backtest::order::{OrderKind, OrderSide, TimeInForce};
+use crate::exchange::{ExchangeClient, ExchangeError, ExchangeOrder, ExchangePosition, OrderRequest, OrderStatus};
+
+const REST_URL: &str = "https://fapi.binance.com";
+const STREAM_URL: &str = "wss://fstream.binance.com/ws";
+const TESTNET_REST_URL: &str = "https://testnet.binancefuture.com";
+const TESTNET_STREAM_URL: &str = "wss://fstream.binancefuture.com/ws";
+
+/// Client of the Binance USDⓈ-M Futures REST API and kline websocket streams, positions are expected in one-way mode
+pub struct BinanceFutures {
+    api_key: String,
+    secret_key: String,
+    rest_url: String,
+    stream_url: String,
+    recv_window: u64, // milliseconds a signed request stays valid
+    quantity_decimals: usize, // quantities and prices are rounded to the precision accepted by the symbol
+    price_decimals: usize,
+    agent: ureq::Agent,
+    stream: Option<(String, WebSocket<MaybeTlsStream<TcpStream>>)>, // name of the kline stream and its connection
+}
+
+impl BinanceFutures {
+    pub fn new(api_key: &str, secret_key: &str) -> Self {
+        Self {
+            api_key: api_key.to_string(),
+            secret_key: secret_key.to_string(),
+            rest_url: REST_URL.to_string(),
+            stream_url: STREAM_URL.to_string(),
+            recv_window: 5000,
+            quantity_decimals: 3,
+            price_decimals: 2,
+            agent: ureq::Agent::new(),
+            stream: None,
+        }
+    }
+
+    // sends every request to the futures testnet
+    pub fn testnet(mut self) -> Self {
+        self.rest_url = TESTNET_REST_URL.to_string();
+        self.stream_url = TESTNET_STREAM_URL.to_string();
+        self
+    }
+
+    // decimals of the quantity and price of the orders, must match the filters of the traded symbol
+    pub fn precision(mut self, quantity_decimals: usize, price_decimals: usize) -> Self {
+        self.quantity_decimals = quantity_decimals;
+        self.price_decimals = price_decimals;
+        self
+    }
+
+    // HMAC SHA256 of the query string with the secret key, in hexadecimal
+    fn sign(&self, query: &str) -> String {
+        let mut mac = Hmac::<Sha256>::new_from_slice(self.secret_key.as_bytes()).expect("HMAC accepts keys of any size");
+        mac.update(query.as_bytes());
+        hex::encode(mac.finalize().into_bytes())
+    }
+
+    fn signed_request(&self, method: &str, path: &str, mut params: Vec<(&str, String)>) -> Result<Value, ExchangeError> {
+        let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_millis();
+        params.push(("recvWindow", self.recv_window.to_string()));
+        params.push(("timestamp", timestamp.to_string()));
+        let query = encode_query(&params);
+        let url = format!("{}{}?{}&signature={}", self.rest_url, path, query, self.sign(&query));
+        self.send(self.agent.request(method, &url).set("X-MBX-APIKEY", &self.api_key))
+    }
+
+    fn public_request(&self, path: &str, params: Vec<(&str, String)>) -> Result<Value, ExchangeError> {
+        let url = format!("{}{}?{}", self.rest_url, path, encode_query(&params));
+        self.send(self.agent.get(&url))
+    }
+
+    fn send(&self, request: ureq::Request) -> Result<Value, ExchangeError> {
+        let response = match request.call() {
+            Ok(response) => response,
+            Err(ureq::Error::Status(_, response)) => {
+                let body = parse_body(response)?;
+                return Err(ExchangeError::Api {
+                    code: body["code"].as_i64().unwrap_or(0),
+                    message: body["msg"].as_str().unwrap_or_default().to_string(),
+                });
+            }
+            Err(error) => return Err(ExchangeError::Connection(error.to_string())),
+        };
+        parse_body(response)
+    }
+
+    // parameters of a new order, the time in force in candles is not supported by the exchange and becomes good till cancelled
+    fn order_params(&self, order: &OrderRequest) -> Vec<(&'static str, String)> {
+        let price = |value: f32| format!("{:.*}", self.price_decimals, value);
+        let mut params = vec![
+            ("symbol", order.symbol.clone()),
+            ("side", match order.side { OrderSide::Buy => "BUY", OrderSide::Sell => "SELL" }.to_string()),
+            ("quantity", format!("{:.*}", self.quantity_decimals, order.quantity)),
+            ("newOrderRespType", "RESULT".to_string()),
+        ];
+
+        match order.kind {
+            OrderKind::Market => params.push(("type", "MARKET".to_string())),
+            OrderKind::Limit { price: limit } => {
+                params.push(("type", "LIMIT".to_string()));
+                params.push(("price", price(limit)));
+            }
+            OrderKind::StopMarket { stop } => {
+                params.push(("type", "STOP_MARKET".to_string()));
+                params.push(("stopPrice", price(stop)));
+            }
+            OrderKind::StopLimit { stop, limit } => {
+                params.push(("type", "STOP".to_string()));
+                params.push(("stopPrice", price(stop)));
+                params.push(("price", price(limit)));
+            }
+            OrderKind::TrailingStop { callback_rate } => {
+                params.push(("type", "TRAILING_STOP_MARKET".to_string()));
+                params.push(("callbackRate", format!("{:.1}", callback_rate * 100.0)));
+            }
+        }
+
+        if matches!(order.kind, OrderKind::Limit { .. } | OrderKind::StopLimit { .. }) {
+            match order.time_in_force {
+                TimeInForce::GoodTillTime(timestamp) => {
+                    params.push(("timeInForce", "GTD".to_string()));
+                    params.push(("goodTillDate", timestamp.to_string()));
+                }
+                _ => params.push(("timeInForce", "GTC".to_string())),
+            }
+        }
+        if order.reduce_only {
+            params.push(("reduceOnly", "true".to_string()));
+        }
+        params
+    }
+
+    // connects to the kline stream, replacing the stream of another symbol or interval
+    fn connect_stream(&mut self, name: &str) -> Result<(), ExchangeError> {
+        if self.stream.as_ref().is_some_and(|(connected, _)| connected == name) {
+            return Ok(());
+        }
+        let (socket, _) = tungstenite::connect(format!("{}/{}", self.stream_url, name))
+            .map_err(|e| ExchangeError::Connection(e.to_string()))?;
+        self.stream = Some((name.to_string(), socket));
+        Ok(())
+    }
+}
+
+impl ExchangeClient for BinanceFutures {
+    fn place_order(&mut self, order: &OrderRequest) -> Result<ExchangeOrder, ExchangeError> {
+        let response = self.signed_request("POST", "/fapi/v1/order", self.order_params(order))?;
+        parse_order(&response)
+    }
+
+    fn cancel_order(&mut self, symbol: &str, order_id: u64) -> Result<(), ExchangeError> {
+        self.signed_request("DELETE", "/fapi/v1/order", vec![("symbol", symbol.to_string()), ("orderId", order_id.to_string())])?;
+        Ok(())
+    }
+
+    fn open_orders(&mut self, symbol: &str) -> Result<Vec<ExchangeOrder>, ExchangeError> {
+        let response = self.signed_request("GET", "/fapi/v1/openOrders", vec![("symbol", symbol.to_string())])?;
+        as_array(&response)?.iter().map(parse_order).collect()
+    }
+
+    fn position(&mut self, symbol: &str) -> Result<Option<ExchangePosition>, ExchangeError> {
+        let response = self.signed_request("GET", "/fapi/v2/positionRisk", vec![("symbol", symbol.to_string())])?;
+        for position in as_array(&response)? {
+            let quantity = number(&position["positionAmt"])?;
+            if quantity != 0.0 {
+                return Ok(Some(ExchangePosition {
+                    symbol: symbol.to_string(),
+                    quantity,
+                    entry_price: number(&position["entryPrice"])?,
+                    unrealized_profit: number(&position["unRealizedProfit"])?,
+                    liquidation_price: number(&position["liquidationPrice"])?,
+                    leverage: number(&position["leverage"])? as u8,
+                }));
+            }
+        }
+        Ok(None)
+    }
+
+    fn balance(&mut self, asset: &str) -> Result<f32, ExchangeError> {
+        let response = self.signed_request("GET", "/fapi/v2/balance", Vec::new())?;
+        match as_array(&response)?.iter().find(|balance| balance["asset"] == asset) {
+            Some(balance) => number(&balance["availableBalance"]),
+            None => Ok(0.0),
+        }
+    }
+
+    fn set_leverage(&mut self, symbol: &str, leverage: u8) -> Result<(), ExchangeError> {
+        self.signed_request("POST", "/fapi/v1/leverage", vec![("symbol", symbol.to_string()), ("leverage", leverage.to_string())])?;
+        Ok(())
+    }
+
+    fn klines(&mut self, symbol: &str, interval: &str, limit: usize) -> Result<Vec<Candlestick>, ExchangeError> {
+        // the last kline returned is still open
+        let params = vec![("symbol", symbol.to_string()), ("interval", interval.to_string()), ("limit", (limit + 1).to_string())];
+        let response = self.public_request("/fapi/v1/klines", params)?;
+        let klines = as_array(&response)?;
+        klines[..klines.len().saturating_sub(1)].iter().map(parse_rest_kline).collect()
+    }
+
+    fn next_kline(&mut self, symbol: &str, interval: &str) -> Result<Option<Candlestick>, ExchangeError> {
+        self.connect_stream(&format!("{}@kline_{}", symbol.to_lowercase(), interval))?;
+        loop {
+            let (_, socket) = self.stream.as_mut().unwrap();
+            let message = socket.read().map_err(|e| ExchangeError::Connection(e.to_string()))?;
+            match message {
+                Message::Text(text) => {
+                    let event: Value = serde_json::from_str(&text).map_err(|e| ExchangeError::InvalidResponse(e.to_string()))?;
+                    if let Some(candle) = parse_stream_kline(&event)? {
+                        return Ok(Some(candle));
+                    }
+                }
+                Message::Close(_) => {
+                    self.stream = None;
+                    return Ok(None);
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+fn parse_body(response: ureq::Response) -> Result<Value, ExchangeError> {
+    let body = response.into_string().map_err(|e| ExchangeError::Connection(e.to_string()))?;
+    serde_json::from_str(&body).map_err(|e| ExchangeError::InvalidResponse(e.to_string()))
+}
+
+fn encode_query(params: &[(&str, String)]) -> String {
+    params.iter().map(|(key, value)| format!("{}={}", key, value)).collect::<Vec<String>>().join("&")
+}
+
+fn as_array(value: &Value) -> Result<&Vec<Value>, ExchangeError> {
+    value.as_array().ok_or_else(|| ExchangeError::InvalidResponse(format!("expected an array, got {}", value)))
+}
+
+// binance sends most numbers as strings to keep their precision
+fn number(value: &Value) -> Result<f32, ExchangeError> {
+    match value {
+        Value::String(text) => text.parse().map_err(|_| ExchangeError::InvalidResponse(format!("{} is not a number", text))),
+        Value::Number(number) => Ok(number.as_f64().unwrap_or_default() as f32),
+        _ => Err(ExchangeError::InvalidResponse(format!("{} is not a number", value))),
+    }
+}
+
+fn integer(value: &Value) -> Result<u64, ExchangeError> {
+    value.as_u64().ok_or_else(|| ExchangeError::InvalidResponse(format!("{} is not an integer", value)))
+}
+
+fn parse_order(order: &Value) -> Result<ExchangeOrder, ExchangeError> {
+    let price = number(&order["price"]).unwrap_or_default();
+    let stop = number(&order["stopPrice"]).unwrap_or_default();
+    let kind = match order["type"].as_str().unwrap_or_default() {
+        "MARKET" => OrderKind::Market,
+        "LIMIT" => OrderKind::Limit { price },
+        "STOP_MARKET" | "TAKE_PROFIT_MARKET" => OrderKind::StopMarket { stop },
+        // a take profit waits for its trigger like a stop, then rests at its limit price
+        "STOP" | "TAKE_PROFIT" => OrderKind::StopLimit { stop, limit: price },
+        "TRAILING_STOP_MARKET" => OrderKind::TrailingStop { callback_rate: number(&order["priceRate"])? / 100.0 },
+        other => return Err(ExchangeError::InvalidResponse(format!("unknown order type {}", other))),
+    };
+
+    Ok(ExchangeOrder {
+        id: integer(&order["orderId"])?,
+        symbol: order["symbol"].as_str().unwrap_or_default().to_string(),
+        side: if order["side"] == "BUY" { OrderSide::Buy } else { OrderSide::Sell },
+        kind,
+        quantity: number(&order["origQty"])?,
+        filled_quantity: number(&order["executedQty"])?,
+        average_price: number(&order["avgPrice"]).unwrap_or_default(),
+        reduce_only: order["reduceOnly"].as_bool().unwrap_or(false),
+        status: match order["status"].as_str().unwrap_or_default() {
+            "NEW" => OrderStatus::New,
+            "PARTIALLY_FILLED" => OrderStatus::PartiallyFilled,
+            "FILLED" => OrderStatus::Filled,
+            "CANCELED" => OrderStatus::Cancelled,
+            _ => OrderStatus::Expired,
+        },
+    })
+}
+
+// [open time, open, high, low, close, volume, close time, quote asset volume, number of trades, ...]
+fn parse_rest_kline(kline: &Value) -> Result<Candlestick, ExchangeError> {
+    let mut candle = Candlestick::new().open(number(&kline[1])?).high(number(&kline[2])?).low(number(&kline[3])?)
+        .close(number(&kline[4])?).volume(number(&kline[5])?);
+    candle.open_time = integer(&kline[0])?;
+    candle.close_time = integer(&kline[6])?;
+    candle.quote_asset_volume = number(&kline[7])?;
+    candle.num_trades = integer(&kline[8])?;
+    Ok(candle)
+}
+
+// kline event of the websocket stream, only closed klines are returned
+fn parse_stream_kline(event: &Value) -> Result<Option<Candlestick>, ExchangeError> {
+    let kline = &event["k"];
+    if !kline["x"].as_bool().unwrap_or(false) {
+        return Ok(None);
+    }
+    let mut candle = Candlestick::new().open(number(&kline["o"])?).high(number(&kline["h"])?).low(number(&kline["l"])?)
+        .close(number(&kline["c"])?).volume(number(&kline["v"])?);
+    candle.open_time = integer(&kline["t"])?;
+    candle.close_time = integer(&kline["T"])?;
+    candle.quote_asset_volume = number(&kline["q"])?;
+    candle.num_trades = integer(&kline["n"])?;
+    Ok(Some(candle))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn signature_matches_the_api_documentation() {
+        let client = BinanceFutures::new("", "2b5eb11e18796d12d88f13dc27dbbd02c2cc51ff7059765ed9821957d82bb4d9");
+        let query = "symbol=BTCUSDT&side=BUY&type=LIMIT&quantity=1&price=9000&timeInForce=GTC&recvWindow=5000&timestamp=1591702613943";
+        assert_eq!(client.sign(query), "3c661234138461fcc7a7d8746c6558c9842d4e10870d2ecbedf7777cad694af9");
+    }
+
+    #[test]
+    fn order_parameters() {
+        let client = BinanceFutures::new("", "").precision(3, 1);
+        let order = OrderRequest::new("BTCUSDT", OrderSide::Sell, OrderKind::StopMarket { stop: 25_000.04 }, 0.0125).reduce_only();
+        assert_eq!(encode_query(&client.order_params(&order)),
+            "symbol=BTCUSDT&side=SELL&quantity=0.013&newOrderRespType=RESULT&type=STOP_MARKET&stopPrice=25000.0&reduceOnly=true");
+
+        let order = OrderRequest::new("BTCUSDT", OrderSide::Buy, OrderKind::Limit { price: 100.0 }, 1.0)
+            .time_in_force(TimeInForce::GoodTillTime(1700000000000));
+        assert!(encode_query(&client.order_params(&order)).ends_with("price=100.0&timeInForce=GTD&goodTillDate=1700000000000"));
+    }
+
+    #[test]
+    fn parse_order_response() {
+        let response: Value = serde_json::from_str(r#"{"orderId": 22542179, "symbol": "BTCUSDT", "status": "FILLED",
+            "price": "0", "avgPrice": "30000.50", "origQty": "0.010", "executedQty": "0.010", "type": "MARKET",
+            "side": "BUY", "reduceOnly": false, "stopPrice": "0"}"#).unwrap();
+        let order = parse_order(&response).unwrap();
+        assert_eq!(order.id, 22542179);
+        assert_eq!(order.kind, OrderKind::Market);
+        assert_eq!(order.status, OrderStatus::Filled);
+        assert_eq!(order.average_price, 30000.5);
+
+        let response: Value = serde_json::from_str(r#"{"orderId": 22542180, "symbol": "BTCUSDT", "status": "NEW",
+            "price": "31000", "avgPrice": "0", "origQty": "0.010", "executedQty": "0", "type": "TAKE_PROFIT",
+            "side": "SELL", "reduceOnly": true, "stopPrice": "30900"}"#).unwrap();
+        let order = parse_order(&response).unwrap();
+        assert_eq!(order.kind, OrderKind::StopLimit { stop: 30900.0, limit: 31000.0 });
+        assert_eq!(order.status, OrderStatus::New);
+        assert!(order.reduce_only);
+    }
+
+    #[test]
+    fn only_closed_stream_klines_are_returned() {
+        let mut event: Value = serde_json::from_str(r#"{"e": "kline", "E": 1638747660000, "s": "BTCUSDT", "k": {
+            "t": 1638747660000, "T": 1638747719999, "s": "BTCUSDT", "i": "1m", "o": "0.0010", "c": "0.0020",
+            "h": "0.0025", "l": "0.0015", "v": "1000", "n": 100, "x": false, "q": "1.0000"}}"#).unwrap();
+        assert!(parse_stream_kline(&event).unwrap().is_none());
+
+        event["k"]["x"] = Value::Bool(true);
+        let candle = parse_stream_kline(&event).unwrap().unwrap();
+        assert_eq!((candle.open_time, candle.close, candle.num_trades), (1638747660000, 0.002, 100));
+    }
+}
@@ -0,0 +1,321 @@
+use crate::candlestick::Candlestick;
+use crate::backtest::engine::{Event, ExitReason};
+use crate::backtest::order::{OrderKind, OrderSide};
+use crate::backtest::position_manager::PositionId;
+use crate::backtest::strategy::{TradingStrategy, TradeAdjustment};
+use crate::backtest::trade::{Trade, TradeDirection};
+use crate::exchange::{ExchangeClient, ExchangeError, OrderRequest};
+
+// trade opened on the exchange, mirrored with the `Trade` accounting of the backtest for the strategy
+struct LiveTrade {
+    id: PositionId,
+    trade: Trade,
+    quantity: f32,
+    balance_at_open: f32, // the difference to the balance after the exit is the result of the trade
+    stoploss_order: Option<u64>,
+    takeprofit_order: Option<u64>,
+}
+
+/// Trades a `TradingStrategy` with real orders on an exchange, one trade at a time on a single symbol.
+/// Entries are market orders protected by reduce only stoploss (or trailing stop) and takeprofit orders,
+/// and the strategy receives the same candle and position events sent by the backtest `Engine`
+pub struct LiveTrader<E: ExchangeClient, S: TradingStrategy> {
+    exchange: E,
+    strategy: S,
+    symbol: String,
+    interval: String,
+    quote_asset: String,
+    open_trade: Option<LiveTrade>,
+    next_position_id: PositionId,
+}
+
+impl<E: ExchangeClient, S: TradingStrategy> LiveTrader<E, S> {
+    /// trades the symbol on klines of the given interval (e.g. "5m"), the leverage of the strategy is set on the exchange
+    pub fn new(mut exchange: E, strategy: S, symbol: &str, interval: &str) -> Result<Self, ExchangeError> {
+        exchange.set_leverage(symbol, strategy.leverage())?;
+        Ok(Self {
+            exchange,
+            strategy,
+            symbol: symbol.to_string(),
+            interval: interval.to_string(),
+            quote_asset: "USDT".to_string(),
+            open_trade: None,
+            next_position_id: 1,
+        })
+    }
+
+    // asset of the balance used to size the trades
+    pub fn quote_asset(mut self, quote_asset: &str) -> Self {
+        self.quote_asset = quote_asset.to_string();
+        self
+    }
+
+    pub fn exchange(&self) -> &E {
+        &self.exchange
+    }
+
+    pub fn strategy(&self) -> &S {
+        &self.strategy
+    }
+
+    pub fn has_open_trade(&self) -> bool {
+        self.open_trade.is_some()
+    }
+
+    /// initializes the indicators of the strategy with the last closed klines
+    pub fn warm_up(&mut self, klines: usize) -> Result<(), ExchangeError> {
+        for candle in self.exchange.klines(&self.symbol, &self.interval, klines)? {
+            self.strategy.new_candlestick(&candle);
+        }
+        Ok(())
+    }
+
+    /// trades every closed kline of the stream until it ends
+    pub fn run(&mut self) -> Result<(), ExchangeError> {
+        while let Some(candle) = self.exchange.next_kline(&self.symbol, &self.interval)? {
+            self.on_candle(&candle)?;
+        }
+        Ok(())
+    }
+
+    /// checks the open trade on the exchange, applies the adjustments and exits of the strategy and opens new trades
+    pub fn on_candle(&mut self, candle: &Candlestick) -> Result<(), ExchangeError> {
+        self.strategy.new_candlestick(candle);
+        self.strategy.on_event(&Event::Candle(candle));
+
+        if self.open_trade.is_some() {
+            if self.exchange.position(&self.symbol)?.is_none() {
+                self.trade_closed_by_exchange(candle)?;
+            } else {
+                self.manage_trade(candle)?;
+            }
+        }
+
+        if self.open_trade.is_none() {
+            if let Some((direction, takeprofit, stoploss)) = self.strategy.should_start_trade() {
+                self.open_trade(direction, candle, takeprofit, stoploss)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn open_trade(&mut self, direction: TradeDirection, candle: &Candlestick, takeprofit: f32, stoploss: f32) -> Result<(), ExchangeError> {
+        let balance = self.exchange.balance(&self.quote_asset)?;
+        let margin = self.strategy.position_size(balance, candle.close, stoploss).clamp(0.0, balance.max(0.0));
+        let leverage = self.strategy.leverage();
+        let quantity = margin * leverage.max(1) as f32 / candle.close;
+        if quantity <= 0.0 {
+            return Ok(());
+        }
+
+        let entry = self.exchange.place_order(&OrderRequest::new(&self.symbol, OrderSide::entry(direction), OrderKind::Market, quantity))?;
+        let price = if entry.average_price > 0.0 { entry.average_price } else { candle.close };
+        let mut trade = Trade::open_at_price(direction, margin, candle, price, leverage, 0.0, 0.0);
+        trade.takeprofit(takeprofit);
+        trade.stoploss(stoploss);
+
+        let id = self.next_position_id;
+        self.next_position_id += 1;
+        let live = self.open_trade.insert(LiveTrade {
+            id,
+            trade,
+            quantity: if entry.filled_quantity > 0.0 { entry.filled_quantity } else { quantity },
+            balance_at_open: balance,
+            stoploss_order: None,
+            takeprofit_order: None,
+        });
+        self.strategy.on_event(&Event::PositionOpened { id, trade: &live.trade });
+        self.place_exit_orders()
+    }
+
+    // adjustments and exits requested by the strategy on the open trade
+    fn manage_trade(&mut self, candle: &Candlestick) -> Result<(), ExchangeError> {
+        let live = self.open_trade.as_mut().unwrap();
+        live.trade.candles_open += 1;
+        let adjustments = self.strategy.adjust_trade(&live.trade, candle);
+        let mut exits_changed = false;
+        let mut close_trade = false;
+        for adjustment in &adjustments {
+            let live = self.open_trade.as_mut().unwrap();
+            let exit_side = OrderSide::exit(live.trade.side);
+            match *adjustment {
+                TradeAdjustment::ScaleIn(fraction) => {
+                    let margin = fraction * self.exchange.balance(&self.quote_asset)?;
+                    let quantity = margin * live.trade.leverage.max(1) as f32 / candle.close;
+                    let side = OrderSide::entry(live.trade.side);
+                    let order = self.exchange.place_order(&OrderRequest::new(&self.symbol, side, OrderKind::Market, quantity))?;
+                    live.trade.increase_position(candle, margin);
+                    live.quantity += order.filled_quantity;
+                }
+                TradeAdjustment::ScaleOut(fraction) if fraction < 1.0 => {
+                    let request = OrderRequest::new(&self.symbol, exit_side, OrderKind::Market, live.quantity * fraction).reduce_only();
+                    let order = self.exchange.place_order(&request)?;
+                    let size_decrease = live.trade.open_position_size() * fraction;
+                    live.trade.decrease_position(candle.close, size_decrease);
+                    live.quantity -= order.filled_quantity;
+                }
+                TradeAdjustment::ScaleOut(_) => {
+                    close_trade = true;
+                    break;
+                }
+                TradeAdjustment::MoveStoploss(price) => live.trade.stoploss(price),
+                TradeAdjustment::MoveTakeprofit(price) => live.trade.takeprofit(price),
+                TradeAdjustment::StoplossToBreakeven => live.trade.stoploss_to_breakeven(),
+            }
+            // a different size also needs exit orders with the new quantity
+            exits_changed = true;
+        }
+
+        // exits requested by the strategy or by the maximum holding time
+        let live = self.open_trade.as_ref().unwrap();
+        let max_candles_reached = self.strategy.max_candles_in_trade().is_some_and(|max| live.trade.candles_open >= max);
+        if close_trade || max_candles_reached || self.strategy.should_close_trade(&live.trade, candle) {
+            let request = OrderRequest::new(&self.symbol, OrderSide::exit(live.trade.side), OrderKind::Market, live.quantity).reduce_only();
+            self.cancel_exit_orders()?;
+            let exit = self.exchange.place_order(&request)?;
+            let price = if exit.average_price > 0.0 { exit.average_price } else { candle.close };
+            return self.close_trade(candle, price, ExitReason::Strategy);
+        }
+
+        if exits_changed {
+            let live = self.open_trade.as_ref().unwrap();
+            self.strategy.on_event(&Event::PositionChanged { id: live.id, trade: &live.trade });
+            self.cancel_exit_orders()?;
+            self.place_exit_orders()?;
+        }
+        Ok(())
+    }
+
+    // the position was closed on the exchange by one of the exit orders or by liquidation
+    fn trade_closed_by_exchange(&mut self, candle: &Candlestick) -> Result<(), ExchangeError> {
+        let open_orders: Vec<u64> = self.exchange.open_orders(&self.symbol)?.iter().map(|order| order.id).collect();
+        let live = self.open_trade.as_ref().unwrap();
+        let filled = |order: Option<u64>| order.is_some_and(|id| !open_orders.contains(&id));
+        let (reason, price) = if filled(live.stoploss_order) {
+            (ExitReason::Stoploss, live.trade.stoploss_price().unwrap_or(candle.close))
+        } else if filled(live.takeprofit_order) {
+            (ExitReason::Takeprofit, live.trade.takeprofit_price().unwrap_or(candle.close))
+        } else {
+            (ExitReason::Liquidation, live.trade.liquidation_price)
+        };
+
+        self.cancel_exit_orders()?;
+        self.close_trade(candle, price, reason)
+    }
+
+    // the result sent to the strategy is the change of the balance, so it includes the fees charged by the exchange
+    fn close_trade(&mut self, candle: &Candlestick, price: f32, reason: ExitReason) -> Result<(), ExchangeError> {
+        let mut live = self.open_trade.take().unwrap();
+        live.trade.close_at_price(candle, price);
+        let result = self.exchange.balance(&self.quote_asset)? - live.balance_at_open;
+        self.strategy.on_event(&Event::PositionClosed { id: live.id, trade: &live.trade, result, reason });
+        Ok(())
+    }
+
+    // places the takeprofit and stoploss (or trailing stop) of the open trade as reduce only orders
+    fn place_exit_orders(&mut self) -> Result<(), ExchangeError> {
+        let live = self.open_trade.as_mut().unwrap();
+        let side = OrderSide::exit(live.trade.side);
+        let stop_kind = match (self.strategy.trailing_stop(live.trade.side), live.trade.stoploss_price()) {
+            (Some(callback_rate), _) => Some(OrderKind::TrailingStop { callback_rate }),
+            (None, Some(stop)) => Some(OrderKind::StopMarket { stop }),
+            (None, None) => None,
+        };
+
+        if let Some(kind) = stop_kind {
+            let order = self.exchange.place_order(&OrderRequest::new(&self.symbol, side, kind, live.quantity).reduce_only())?;
+            live.stoploss_order = Some(order.id);
+        }
+        if let Some(price) = live.trade.takeprofit_price() {
+            let request = OrderRequest::new(&self.symbol, side, OrderKind::Limit { price }, live.quantity).reduce_only();
+            live.takeprofit_order = Some(self.exchange.place_order(&request)?.id);
+        }
+        Ok(())
+    }
+
+    fn cancel_exit_orders(&mut self) -> Result<(), ExchangeError> {
+        let live = self.open_trade.as_mut().unwrap();
+        for order_id in [live.stoploss_order.take(), live.takeprofit_order.take()].into_iter().flatten() {
+            match self.exchange.cancel_order(&self.symbol, order_id) {
+                // orders already filled or expired can't be cancelled
+                Ok(()) | Err(ExchangeError::Api { .. }) => {}
+                Err(error) => return Err(error),
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exchange::mock::MockExchange;
+
+    // opens a single long trade with a takeprofit at 110 and a stoploss at 90
+    #[derive(Default)]
+    struct SingleEntry {
+        started: bool,
+        max_candles: Option<u32>,
+        closed: Vec<(f32, ExitReason)>,
+    }
+
+    impl TradingStrategy for SingleEntry {
+        fn should_start_trade(&mut self) -> Option<(TradeDirection, f32, f32)> {
+            if self.started {
+                return None;
+            }
+            self.started = true;
+            Some((TradeDirection::Long, 110.0, 90.0))
+        }
+
+        fn new_candlestick(&mut self, _candle: &Candlestick) {}
+
+        fn reset(&mut self) {}
+
+        fn position_size(&self, balance: f32, _price: f32, _stoploss: f32) -> f32 {
+            balance * 0.1
+        }
+
+        fn leverage(&self) -> u8 {
+            2
+        }
+
+        fn max_candles_in_trade(&self) -> Option<u32> {
+            self.max_candles
+        }
+
+        fn on_event(&mut self, event: &Event) {
+            if let Event::PositionClosed { result, reason, .. } = event {
+                self.closed.push((*result, *reason));
+            }
+        }
+    }
+
+    fn kline(open: f32, high: f32, low: f32, close: f32) -> Candlestick {
+        Candlestick::new().open(open).high(high).low(low).close(close)
+    }
+
+    #[test]
+    fn takeprofit_filled_on_the_exchange() {
+        let klines = vec![kline(100.0, 100.0, 100.0, 100.0), kline(100.0, 105.0, 99.0, 104.0), kline(104.0, 112.0, 103.0, 111.0)];
+        let exchange = MockExchange::new("BTCUSDT", 1000.0, klines);
+        let mut trader = LiveTrader::new(exchange, SingleEntry::default(), "BTCUSDT", "5m").unwrap();
+        trader.run().unwrap();
+
+        // 100 USD of margin with 2x leverage is 2 units, each one gaining 10 USD
+        assert!(!trader.has_open_trade());
+        assert_eq!(trader.strategy().closed, vec![(20.0, ExitReason::Takeprofit)]);
+        assert_eq!(trader.exchange().orders().len(), 3);
+    }
+
+    #[test]
+    fn strategy_exit_cancels_the_exit_orders() {
+        let klines = vec![kline(100.0, 100.0, 100.0, 100.0), kline(100.0, 101.0, 94.0, 95.0)];
+        let strategy = SingleEntry { max_candles: Some(1), ..Default::default() };
+        let mut trader = LiveTrader::new(MockExchange::new("BTCUSDT", 1000.0, klines), strategy, "BTCUSDT", "5m").unwrap();
+        trader.run().unwrap();
+
+        assert_eq!(trader.strategy().closed, vec![(-10.0, ExitReason::Strategy)]);
+        assert!(trader.exchange.open_orders("BTCUSDT").unwrap().is_empty());
+    }
+}
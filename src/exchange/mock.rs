@@ -0,0 +1,263 @@
+use std::collections::VecDeque;
+
+use crate::candlestick::Candlestick;
+use crate::backtest::order::{Order, OrderBook, OrderKind, OrderSide};
+use crate::exchange::{ExchangeClient, ExchangeError, ExchangeOrder, ExchangePosition, OrderRequest, OrderStatus};
+
+/// In-process exchange for tests: klines are streamed from memory and the orders are matched by the
+/// `OrderBook` of the backtest. It keeps a single net position like the one-way mode of Binance Futures
+/// and doesn't liquidate positions
+pub struct MockExchange {
+    symbol: String,
+    quote_asset: String,
+    upcoming_klines: VecDeque<Candlestick>,
+    klines: Vec<Candlestick>, // klines already streamed
+    balance: f32,
+    fee_rate: f32, // fraction of the value of each fill paid as fee
+    leverage: u8,
+    position_quantity: f32, // negative on short positions
+    entry_price: f32,
+    order_book: OrderBook,
+    orders: Vec<ExchangeOrder>, // every order placed, with its current status
+}
+
+impl MockExchange {
+    /// exchange of a single symbol quoted in USDT that streams the given klines in order
+    pub fn new(symbol: &str, balance: f32, klines: Vec<Candlestick>) -> Self {
+        Self {
+            symbol: symbol.to_string(),
+            quote_asset: "USDT".to_string(),
+            upcoming_klines: klines.into(),
+            klines: Vec::new(),
+            balance,
+            fee_rate: 0.0,
+            leverage: 1,
+            position_quantity: 0.0,
+            entry_price: 0.0,
+            order_book: OrderBook::new(),
+            orders: Vec::new(),
+        }
+    }
+
+    pub fn fee_rate(mut self, fee_rate: f32) -> Self {
+        self.fee_rate = fee_rate;
+        self
+    }
+
+    // every order placed, including filled and cancelled ones
+    pub fn orders(&self) -> &[ExchangeOrder] {
+        &self.orders
+    }
+
+    fn check_symbol(&self, symbol: &str) -> Result<(), ExchangeError> {
+        if symbol == self.symbol {
+            Ok(())
+        } else {
+            Err(ExchangeError::Api { code: -1121, message: "Invalid symbol.".to_string() })
+        }
+    }
+
+    fn last_price(&self) -> Option<f32> {
+        self.klines.last().map(|kline| kline.close)
+    }
+
+    fn order_mut(&mut self, id: u64) -> &mut ExchangeOrder {
+        self.orders.iter_mut().find(|order| order.id == id).unwrap()
+    }
+
+    // quantity of a reduce only order that can still be filled
+    fn reducible_quantity(&self, side: OrderSide, quantity: f32) -> f32 {
+        let reduces = (side == OrderSide::Sell && self.position_quantity > 0.0) || (side == OrderSide::Buy && self.position_quantity < 0.0);
+        if reduces { quantity.min(self.position_quantity.abs()) } else { 0.0 }
+    }
+
+    // updates the net position and the balance with a fill
+    fn apply_fill(&mut self, side: OrderSide, quantity: f32, price: f32) {
+        let signed_quantity = if side == OrderSide::Buy { quantity } else { -quantity };
+        self.balance -= quantity * price * self.fee_rate;
+
+        if self.position_quantity == 0.0 || self.position_quantity.signum() == signed_quantity.signum() {
+            let total = self.position_quantity + signed_quantity;
+            self.entry_price = (self.entry_price * self.position_quantity + price * signed_quantity) / total;
+            self.position_quantity = total;
+            return;
+        }
+
+        let closed = quantity.min(self.position_quantity.abs());
+        self.balance += (price - self.entry_price) * closed * self.position_quantity.signum();
+        let remaining = self.position_quantity + signed_quantity;
+        if remaining.abs() < 1e-6 {
+            self.position_quantity = 0.0;
+            self.entry_price = 0.0;
+        } else {
+            if remaining.signum() != self.position_quantity.signum() {
+                self.entry_price = price;
+            }
+            self.position_quantity = remaining;
+        }
+    }
+}
+
+impl ExchangeClient for MockExchange {
+    fn place_order(&mut self, request: &OrderRequest) -> Result<ExchangeOrder, ExchangeError> {
+        self.check_symbol(&request.symbol)?;
+        if request.quantity <= 0.0 {
+            return Err(ExchangeError::Api { code: -4003, message: "Quantity less than or equal to zero.".to_string() });
+        }
+        if request.reduce_only && self.reducible_quantity(request.side, request.quantity) == 0.0 {
+            return Err(ExchangeError::Api { code: -2022, message: "ReduceOnly Order is rejected.".to_string() });
+        }
+
+        let mut order = Order::new(request.side, request.kind).time_in_force(request.time_in_force);
+        if request.reduce_only {
+            order = order.reduce_only();
+        }
+        if let (OrderKind::TrailingStop { .. }, Some(price)) = (request.kind, self.last_price()) {
+            order = order.trailing_from(price);
+        }
+
+        let mut placed = ExchangeOrder {
+            id: 0,
+            symbol: request.symbol.clone(),
+            side: request.side,
+            kind: request.kind,
+            quantity: request.quantity,
+            filled_quantity: 0.0,
+            average_price: 0.0,
+            reduce_only: request.reduce_only,
+            status: OrderStatus::New,
+        };
+
+        if request.kind == OrderKind::Market {
+            // market orders fill right away on the last price
+            let price = self.last_price().ok_or_else(|| ExchangeError::Api { code: -1, message: "No price available.".to_string() })?;
+            let quantity = if request.reduce_only { self.reducible_quantity(request.side, request.quantity) } else { request.quantity };
+            self.apply_fill(request.side, quantity, price);
+            placed.id = self.orders.len() as u64 + 1_000_000;
+            placed.filled_quantity = quantity;
+            placed.average_price = price;
+            placed.status = OrderStatus::Filled;
+        } else {
+            placed.id = self.order_book.submit(order) as u64;
+        }
+        self.orders.push(placed.clone());
+        Ok(placed)
+    }
+
+    fn cancel_order(&mut self, symbol: &str, order_id: u64) -> Result<(), ExchangeError> {
+        self.check_symbol(symbol)?;
+        if order_id > u32::MAX as u64 || !self.order_book.cancel(order_id as u32) {
+            return Err(ExchangeError::Api { code: -2011, message: "Unknown order sent.".to_string() });
+        }
+        self.order_book.take_cancelled();
+        self.order_mut(order_id).status = OrderStatus::Cancelled;
+        Ok(())
+    }
+
+    fn open_orders(&mut self, symbol: &str) -> Result<Vec<ExchangeOrder>, ExchangeError> {
+        self.check_symbol(symbol)?;
+        Ok(self.orders.iter().filter(|order| order.status == OrderStatus::New).cloned().collect())
+    }
+
+    fn position(&mut self, symbol: &str) -> Result<Option<ExchangePosition>, ExchangeError> {
+        self.check_symbol(symbol)?;
+        if self.position_quantity == 0.0 {
+            return Ok(None);
+        }
+        let price = self.last_price().unwrap_or(self.entry_price);
+        Ok(Some(ExchangePosition {
+            symbol: symbol.to_string(),
+            quantity: self.position_quantity,
+            entry_price: self.entry_price,
+            unrealized_profit: (price - self.entry_price) * self.position_quantity,
+            liquidation_price: 0.0,
+            leverage: self.leverage,
+        }))
+    }
+
+    fn balance(&mut self, asset: &str) -> Result<f32, ExchangeError> {
+        Ok(if asset == self.quote_asset { self.balance } else { 0.0 })
+    }
+
+    fn set_leverage(&mut self, symbol: &str, leverage: u8) -> Result<(), ExchangeError> {
+        self.check_symbol(symbol)?;
+        self.leverage = leverage;
+        Ok(())
+    }
+
+    fn klines(&mut self, symbol: &str, _interval: &str, limit: usize) -> Result<Vec<Candlestick>, ExchangeError> {
+        self.check_symbol(symbol)?;
+        let start = self.klines.len().saturating_sub(limit);
+        Ok(self.klines[start..].to_vec())
+    }
+
+    // matches the pending orders against the kline before it is returned
+    fn next_kline(&mut self, symbol: &str, _interval: &str) -> Result<Option<Candlestick>, ExchangeError> {
+        self.check_symbol(symbol)?;
+        let Some(kline) = self.upcoming_klines.pop_front() else {
+            return Ok(None);
+        };
+
+        for fill in self.order_book.process(&kline) {
+            let id = fill.order_id as u64;
+            let requested = self.order_mut(id).quantity;
+            let quantity = if fill.reduce_only { self.reducible_quantity(fill.side, requested) } else { requested };
+            if quantity == 0.0 {
+                self.order_mut(id).status = OrderStatus::Expired;
+                continue;
+            }
+            self.apply_fill(fill.side, quantity, fill.price);
+            let order = self.order_mut(id);
+            order.filled_quantity = quantity;
+            order.average_price = fill.price;
+            order.status = OrderStatus::Filled;
+        }
+        for id in self.order_book.take_cancelled() {
+            self.order_mut(id as u64).status = OrderStatus::Expired;
+        }
+
+        self.klines.push(kline.clone());
+        Ok(Some(kline))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kline(open: f32, high: f32, low: f32, close: f32) -> Candlestick {
+        Candlestick::new().open(open).high(high).low(low).close(close)
+    }
+
+    #[test]
+    fn market_and_limit_orders_update_the_position() {
+        let mut exchange = MockExchange::new("BTCUSDT", 1000.0, vec![kline(100.0, 100.0, 100.0, 100.0), kline(100.0, 112.0, 99.0, 110.0)]);
+        assert!(exchange.place_order(&OrderRequest::new("BTCUSDT", OrderSide::Buy, OrderKind::Market, 1.0)).is_err());
+
+        exchange.next_kline("BTCUSDT", "5m").unwrap();
+        exchange.place_order(&OrderRequest::new("BTCUSDT", OrderSide::Buy, OrderKind::Market, 2.0)).unwrap();
+        let takeprofit = exchange.place_order(&OrderRequest::new("BTCUSDT", OrderSide::Sell,
+            OrderKind::Limit { price: 110.0 }, 5.0).reduce_only()).unwrap();
+        assert_eq!(exchange.position("BTCUSDT").unwrap().unwrap().quantity, 2.0);
+        assert_eq!(exchange.open_orders("BTCUSDT").unwrap().len(), 1);
+
+        exchange.next_kline("BTCUSDT", "5m").unwrap();
+        assert!(exchange.position("BTCUSDT").unwrap().is_none());
+        assert_eq!(exchange.orders().iter().find(|order| order.id == takeprofit.id).unwrap().filled_quantity, 2.0);
+        assert_eq!(exchange.balance("USDT").unwrap(), 1020.0);
+        assert!(exchange.next_kline("BTCUSDT", "5m").unwrap().is_none());
+    }
+
+    #[test]
+    fn rejected_and_cancelled_orders() {
+        let mut exchange = MockExchange::new("BTCUSDT", 1000.0, vec![kline(100.0, 100.0, 100.0, 100.0)]);
+        exchange.next_kline("BTCUSDT", "5m").unwrap();
+        assert!(exchange.place_order(&OrderRequest::new("ETHUSDT", OrderSide::Buy, OrderKind::Market, 1.0)).is_err());
+        assert!(exchange.place_order(&OrderRequest::new("BTCUSDT", OrderSide::Sell, OrderKind::Market, 1.0).reduce_only()).is_err());
+
+        let order = exchange.place_order(&OrderRequest::new("BTCUSDT", OrderSide::Buy, OrderKind::Limit { price: 90.0 }, 1.0)).unwrap();
+        exchange.cancel_order("BTCUSDT", order.id).unwrap();
+        assert!(exchange.cancel_order("BTCUSDT", order.id).is_err());
+        assert!(exchange.open_orders("BTCUSDT").unwrap().is_empty());
+    }
+}
@@ -0,0 +1,536 @@
+use rayon::prelude::*;
+use rand::prelude::*;
+use rand_pcg::{Pcg64, Lcg128Xsl64};
+use std::time::Instant;
+use crate::backtest::{Backtest, RunMode};
+use crate::backtest::rule_strategy::{RuleStrategy, RuleStrategyConfig};
+use crate::indicators::price_source::PriceSource;
+use crate::rules::compiler::{Rule, MOVING_AVERAGES};
+use crate::rules::parser::{Expression, Operator};
+
+/// Settings of a `GeneticProgramming` search
+#[derive(Debug, Clone, PartialEq)]
+pub struct GpConfig {
+    pub population_size: usize,
+    pub generations: usize,
+    pub tournament_size: usize, // individuals competing to be a parent, larger tournaments favor the best ones more
+    pub crossover_rate: f32, // chance of a child being a crossover of two parents instead of a mutation of one
+    pub elite_count: usize, // best individuals copied unchanged to the next generation
+    pub initial_depth: usize, // maximum levels of `and`, `or` and `not` of the rules of the first generation
+    pub max_depth: usize, // children with deeper rules are replaced by their parent, so the rules don't bloat
+    pub max_nodes: usize, // children with larger rules are replaced by their parent
+    pub parsimony: f32, // fitness lost for each node of the rules when selecting parents
+    pub short_entries: bool, // evolves a rule for short trades besides the rule for long trades
+}
+
+impl Default for GpConfig {
+    fn default() -> Self {
+        Self {
+            population_size: 500,
+            generations: 50,
+            tournament_size: 4,
+            crossover_rate: 0.8,
+            elite_count: 2,
+            initial_depth: 3,
+            max_depth: 12,
+            max_nodes: 60,
+            parsimony: 0.0,
+            short_entries: true,
+        }
+    }
+}
+
+/// Entry rules of a `RuleStrategy` evolved by the search
+#[derive(Debug, Clone, PartialEq)]
+pub struct RuleIndividual {
+    pub long_entry: Expression,
+    pub short_entry: Option<Expression>,
+    pub fitness: Option<f32>,
+}
+
+impl RuleIndividual {
+    pub fn new(long_entry: Expression, short_entry: Option<Expression>) -> Self {
+        Self { long_entry, short_entry, fitness: None }
+    }
+
+    /// nodes of both rules
+    pub fn size(&self) -> usize {
+        self.long_entry.size() + self.short_entry.as_ref().map_or(0, Expression::size)
+    }
+
+    pub fn depth(&self) -> usize {
+        self.long_entry.depth().max(self.short_entry.as_ref().map_or(0, Expression::depth))
+    }
+
+    /// strategy config entering on the rules, with the targets and sizing of `template`
+    pub fn config(&self, template: &RuleStrategyConfig) -> RuleStrategyConfig {
+        RuleStrategyConfig {
+            long_entry: Some(self.long_entry.to_string()),
+            short_entry: self.short_entry.as_ref().map(Expression::to_string),
+            ..template.clone()
+        }
+    }
+
+    fn rules_mut(&mut self) -> Vec<&mut Expression> {
+        std::iter::once(&mut self.long_entry).chain(self.short_entry.as_mut()).collect()
+    }
+}
+
+/// Backtests the rules of an individual with a `RuleStrategy`
+pub struct RuleFitness {
+    backtester: Backtest,
+    mode: RunMode,
+    strategy: RuleStrategyConfig,
+}
+
+impl RuleFitness {
+    pub fn new(backtester: Backtest, mode: RunMode) -> Self {
+        Self {
+            backtester,
+            mode,
+            strategy: RuleStrategyConfig::default(),
+        }
+    }
+
+    /// targets, leverage and sizing of the strategies the evolved rules are tested with
+    pub fn strategy(mut self, strategy: RuleStrategyConfig) -> Self {
+        self.strategy = strategy;
+        self
+    }
+
+    pub fn calculate_fitness(&self, individual: &RuleIndividual) -> f32 {
+        self.fitness(self.mode, individual)
+    }
+
+    pub fn validation_fitness(&self, individual: &RuleIndividual) -> f32 {
+        self.fitness(RunMode::Validation, individual)
+    }
+
+    fn fitness(&self, mode: RunMode, individual: &RuleIndividual) -> f32 {
+        let long_entry = Rule::compile(&individual.long_entry);
+        let short_entry = individual.short_entry.as_ref().map(Rule::compile).transpose();
+        match (long_entry, short_entry) {
+            (Ok(long_entry), Ok(short_entry)) => {
+                let mut strategy = RuleStrategy::from_rules(Some(long_entry), short_entry, &self.strategy);
+                self.backtester.run(mode, &mut strategy)
+            }
+            // the operators only build valid rules, but a rule that can't be traded is never selected
+            _ => f32::MIN,
+        }
+    }
+}
+
+// longest period a mutation can reach, twice the periods of the random series so they can still grow
+const MAX_PERIOD: f32 = 200.0;
+
+// indicators compared with constants and the range their values fall in
+const OSCILLATORS: [(&str, f32, f32); 7] = [
+    ("rsi", 0.0, 100.0),
+    ("stoch_k", 0.0, 100.0),
+    ("adx", 0.0, 100.0),
+    ("willr", -100.0, 0.0),
+    ("cci", -200.0, 200.0),
+    ("rvol", 0.0, 3.0),
+    ("macd_hist", 0.0, 0.0),
+];
+
+/// Genetic programming search evolving the structure of the entry rules of a `RuleStrategy`: trees of
+/// indicators, comparisons and logic operators written in the rule language, instead of the thresholds
+/// of a fixed rule tuned by the `BRKGA`.
+///
+/// Parents are picked by tournaments, children are a subtree crossover of two parents or a mutation of one,
+/// replacing a condition by a random one or changing a number. Rules deeper or larger than the limits of the
+/// config are discarded, and a parsimony pressure can favor the smaller rules among similar fitnesses
+pub struct GeneticProgramming {
+    config: GpConfig,
+    rng: Lcg128Xsl64,
+    population: Vec<RuleIndividual>, // sorted from the best to the worst after every evaluation
+    fitness_executor: RuleFitness,
+}
+
+impl GeneticProgramming {
+    pub fn new(seed: u64, config: GpConfig, fitness_executor: RuleFitness) -> Self {
+        Self {
+            config,
+            rng: Pcg64::seed_from_u64(seed),
+            population: vec![],
+            fitness_executor,
+        }
+    }
+
+    /// runs every generation and returns the best individual found
+    pub fn run(&mut self) -> RuleIndividual {
+        println!("Starting genetic programming with a population of {}", self.config.population_size);
+        let start = Instant::now();
+        self.population = self.initial_population();
+
+        for generation in 0..self.config.generations {
+            self.calculate_population_fitness();
+            self.show_details(generation);
+            if generation + 1 < self.config.generations {
+                self.population = self.evolve_population();
+            }
+        }
+
+        println!("Time elapsed is: {:?}", start.elapsed());
+        self.population[0].clone()
+    }
+
+    // ramped half and half, full trees and trees of random shapes with depths up to the initial depth
+    fn initial_population(&mut self) -> Vec<RuleIndividual> {
+        (0..self.config.population_size).map(|i| {
+            let depth = 1 + i % self.config.initial_depth.max(1);
+            let full = i % 2 == 0;
+            let long_entry = self.random_condition(depth, full);
+            let short_entry = if self.config.short_entries { Some(self.random_condition(depth, full)) } else { None };
+            RuleIndividual::new(long_entry, short_entry)
+        }).collect()
+    }
+
+    pub fn random_individual(&mut self) -> RuleIndividual {
+        let long_entry = self.random_condition(self.config.initial_depth, false);
+        let short_entry = if self.config.short_entries { Some(self.random_condition(self.config.initial_depth, false)) } else { None };
+        RuleIndividual::new(long_entry, short_entry)
+    }
+
+    fn calculate_population_fitness(&mut self) {
+        self.population.par_iter_mut()
+            .filter(|individual| individual.fitness.is_none())
+            .for_each(|individual| {
+                individual.fitness = Some(self.fitness_executor.calculate_fitness(individual));
+            });
+
+        let parsimony = self.config.parsimony;
+        // a fitness that isn't a number ranks like a rule that can't be traded
+        let score = |individual: &RuleIndividual| individual.fitness.filter(|fitness| !fitness.is_nan()).unwrap_or(f32::MIN)
+            - parsimony * individual.size() as f32;
+        // the smaller rule wins between equal scores
+        self.population.sort_by(|a, b| score(b).total_cmp(&score(a)).then(a.size().cmp(&b.size())));
+    }
+
+    fn evolve_population(&mut self) -> Vec<RuleIndividual> {
+        let mut new_population: Vec<RuleIndividual> = self.population.iter().take(self.config.elite_count).cloned().collect();
+
+        while new_population.len() < self.config.population_size {
+            let parent = self.tournament();
+            let child = if self.rng.gen_range(0.0..1.0) < self.config.crossover_rate {
+                let other_parent = self.tournament();
+                self.crossover(parent, other_parent)
+            } else {
+                self.mutate(parent)
+            };
+
+            // bloat control, a child over the limits is replaced by its parent
+            if child.depth() > self.config.max_depth || child.size() > self.config.max_nodes {
+                new_population.push(self.population[parent].clone());
+            } else {
+                new_population.push(child);
+            }
+        }
+        new_population
+    }
+
+    // index of the best of `tournament_size` random individuals, the population is sorted so it is the lowest index
+    fn tournament(&mut self) -> usize {
+        (0..self.config.tournament_size.max(1))
+            .map(|_| self.rng.gen_range(0..self.population.len()))
+            .min()
+            .unwrap_or(0)
+    }
+
+    // copy of the first parent with a random condition replaced by a random condition of the second parent
+    fn crossover(&mut self, parent: usize, other_parent: usize) -> RuleIndividual {
+        let mut child = self.population[parent].clone();
+        child.fitness = None;
+        let donors: Vec<Expression> = {
+            let other = &self.population[other_parent];
+            std::iter::once(&other.long_entry).chain(other.short_entry.as_ref()).cloned().collect()
+        };
+
+        let mut rules = child.rules_mut();
+        let index = self.rng.gen_range(0..rules.len());
+        let rule = &mut rules[index];
+        // rules of the same direction are exchanged, so long and short logic don't mix
+        let donor = &donors[index.min(donors.len() - 1)];
+        let donor_conditions = conditions(donor);
+        let replacement = donor_conditions[self.rng.gen_range(0..donor_conditions.len())].clone();
+        let target = self.rng.gen_range(0..conditions(rule).len());
+        replace_condition(rule, &mut target.clone(), &replacement);
+        child
+    }
+
+    fn mutate(&mut self, parent: usize) -> RuleIndividual {
+        let mut child = self.population[parent].clone();
+        child.fitness = None;
+        let point_mutation = self.rng.gen_bool(0.5);
+        let replacement = self.random_condition(2, false);
+        let factor = self.rng.gen_range(0.7..1.4);
+
+        let mut rules = child.rules_mut();
+        let index = self.rng.gen_range(0..rules.len());
+        let rule = &mut rules[index];
+        let mut values = Vec::new();
+        numbers(rule, false, &mut values);
+        if point_mutation && !values.is_empty() {
+            let (value, period) = values.swap_remove(self.rng.gen_range(0..values.len()));
+            *value = mutate_number(*value, period, factor);
+        } else {
+            let target = self.rng.gen_range(0..conditions(rule).len());
+            replace_condition(rule, &mut target.clone(), &replacement);
+        }
+        child
+    }
+
+    // condition with up to `depth` levels of logic operators, with all of them when `full`
+    fn random_condition(&mut self, depth: usize, full: bool) -> Expression {
+        if depth <= 1 || (!full && self.rng.gen_bool(0.3)) {
+            return self.random_comparison();
+        }
+        match self.rng.gen_range(0..5) {
+            0 => Expression::Not(Box::new(self.random_condition(depth - 1, full))),
+            choice => {
+                let operator = if choice < 3 { Operator::And } else { Operator::Or };
+                let left = self.random_condition(depth - 1, full);
+                Expression::binary(operator, left, self.random_condition(depth - 1, full))
+            }
+        }
+    }
+
+    // comparisons and crosses between values of the same scale, prices with prices and oscillators with constants
+    fn random_comparison(&mut self) -> Expression {
+        let cross = self.rng.gen_bool(0.3);
+        let above = self.rng.gen_bool(0.5);
+        let (left, right) = if self.rng.gen_bool(0.5) {
+            (self.random_price(), self.random_price())
+        } else {
+            let (oscillator, low, high) = self.random_oscillator();
+            let threshold = if low < high { round(self.rng.gen_range(low..high)) } else { low };
+            (oscillator, Expression::Number(threshold))
+        };
+
+        if cross {
+            Expression::call(if above { "crosses_above" } else { "crosses_below" }, vec![left, right])
+        } else {
+            Expression::binary(if above { Operator::Greater } else { Operator::Less }, left, right)
+        }
+    }
+
+    fn random_price(&mut self) -> Expression {
+        let source = *[PriceSource::Close, PriceSource::Open, PriceSource::High, PriceSource::Low, PriceSource::Hl2, PriceSource::Hlc3]
+            .choose(&mut self.rng)
+            .unwrap();
+        let period = Expression::Number(self.rng.gen_range(2..=100) as f32);
+        match self.rng.gen_range(0..10) {
+            0..=1 => Expression::Price(source),
+            2..=6 => {
+                let (name, _) = MOVING_AVERAGES[self.rng.gen_range(0..MOVING_AVERAGES.len())];
+                let mut arguments = vec![period];
+                if source != PriceSource::Close {
+                    arguments.push(Expression::Price(source));
+                }
+                Expression::call(name, arguments)
+            }
+            7 => {
+                let band = if self.rng.gen_bool(0.5) { "bb_upper" } else { "bb_lower" };
+                Expression::call(band, vec![period, Expression::Number(2.0)])
+            }
+            8 => Expression::call(if self.rng.gen_bool(0.5) { "highest" } else { "lowest" }, vec![period]),
+            _ => Expression::call("vwap", vec![]),
+        }
+    }
+
+    fn random_oscillator(&mut self) -> (Expression, f32, f32) {
+        let (name, low, high) = OSCILLATORS[self.rng.gen_range(0..OSCILLATORS.len())];
+        let period = self.rng.gen_range(2..=50) as f32;
+        let arguments = match name {
+            "stoch_k" => vec![Expression::Number(period), Expression::Number(3.0)],
+            "macd_hist" => {
+                let fast = self.rng.gen_range(2..=30) as f32;
+                let slow = fast + self.rng.gen_range(2..=40) as f32;
+                vec![Expression::Number(fast), Expression::Number(slow), Expression::Number(self.rng.gen_range(2..=20) as f32)]
+            }
+            _ => vec![Expression::Number(period)],
+        };
+        (Expression::call(name, arguments), low, high)
+    }
+
+    fn show_details(&self, generation: usize) {
+        let best = &self.population[0];
+        let median = &self.population[self.population.len() / 2];
+        print!("Generation {}: ", generation);
+        print!("best fitness: {} | ", best.fitness.unwrap());
+        print!("median fitness: {} | ", median.fitness.unwrap());
+        print!("best size: {} | ", best.size());
+        println!("validation fitness best of gen {} is {}", generation, self.fitness_executor.validation_fitness(best));
+        println!("long entry: {}", best.long_entry);
+        if let Some(short_entry) = &best.short_entry {
+            println!("short entry: {}", short_entry);
+        }
+    }
+}
+
+fn round(value: f32) -> f32 {
+    (value * 10.0).round() / 10.0
+}
+
+// number scaled by the factor of a point mutation, periods stay whole and between 2 and `MAX_PERIOD`
+fn mutate_number(value: f32, period: bool, factor: f32) -> f32 {
+    if period {
+        (value * factor).round().clamp(2.0, MAX_PERIOD)
+    } else if value == 0.0 {
+        factor - 1.0
+    } else {
+        round(value * factor)
+    }
+}
+
+// conditions of the rule in pre-order, the nodes crossover and mutation replace so the rule stays a valid condition
+fn conditions(expression: &Expression) -> Vec<&Expression> {
+    let mut nodes = vec![expression];
+    match expression {
+        Expression::Binary(operator, left, right) if operator.is_logical() => {
+            nodes.extend(conditions(left));
+            nodes.extend(conditions(right));
+        }
+        Expression::Not(condition) => nodes.extend(conditions(condition)),
+        _ => {}
+    }
+    nodes
+}
+
+// replaces the condition at `index` in the order of `conditions`, returns true once it was replaced
+fn replace_condition(expression: &mut Expression, index: &mut usize, replacement: &Expression) -> bool {
+    if *index == 0 {
+        *expression = replacement.clone();
+        return true;
+    }
+    *index -= 1;
+    match expression {
+        Expression::Binary(operator, left, right) if operator.is_logical() => {
+            replace_condition(left, index, replacement) || replace_condition(right, index, replacement)
+        }
+        Expression::Not(condition) => replace_condition(condition, index, replacement),
+        _ => false,
+    }
+}
+
+// numbers of the rule, flagged when they are an argument of a call like a period
+fn numbers<'a>(expression: &'a mut Expression, argument: bool, values: &mut Vec<(&'a mut f32, bool)>) {
+    match expression {
+        Expression::Number(value) => values.push((value, argument)),
+        Expression::Price(_) => {}
+        Expression::Call(name, arguments) => {
+            let crosses = name.starts_with("crosses_");
+            for value in arguments.iter_mut() {
+                numbers(value, !crosses, values);
+            }
+        }
+        Expression::Negate(operand) | Expression::Not(operand) => numbers(operand, argument, values),
+        Expression::Binary(_, left, right) => {
+            numbers(left, argument, values);
+            numbers(right, argument, values);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::candlestick::Candlestick;
+
+    // rising prices with waves
+    fn trending_candlesticks() -> Vec<Candlestick> {
+        (0..1200).map(|i| {
+            let price = 100.0 * 1.002_f32.powi(i) * (1.0 + 0.05 * (i as f32 / 3.0).sin());
+            Candlestick::new().open(price).high(price + 2.0).low(price - 2.0).close(price + 1.0)
+        }).collect()
+    }
+
+    fn search(seed: u64, config: GpConfig) -> GeneticProgramming {
+        let backtest_engine = Backtest::new(trending_candlesticks(), 4, 0.0, 0.001);
+        GeneticProgramming::new(seed, config, RuleFitness::new(backtest_engine, RunMode::Training))
+    }
+
+    fn small_config() -> GpConfig {
+        GpConfig { population_size: 24, generations: 4, max_depth: 8, max_nodes: 30, ..GpConfig::default() }
+    }
+
+    #[test]
+    fn test_operators_keep_valid_rules() {
+        let mut gp = search(7, small_config());
+        gp.population = gp.initial_population();
+        for _ in 0..300 {
+            let (first, second) = (gp.tournament(), gp.tournament());
+            let children = [gp.crossover(first, second), gp.mutate(first)];
+            for child in children {
+                assert!(Rule::compile(&child.long_entry).is_ok(), "{}", child.long_entry);
+                assert!(child.short_entry.as_ref().is_some_and(|rule| Rule::compile(rule).is_ok()));
+                // the rules are written in the rule language and read back the same
+                assert_eq!(Expression::parse(&child.long_entry.to_string()).unwrap(), child.long_entry);
+            }
+        }
+    }
+
+    #[test]
+    fn test_fitness_not_a_number() {
+        let mut gp = search(5, small_config());
+        gp.population = gp.initial_population();
+        for (index, individual) in gp.population.iter_mut().enumerate() {
+            individual.fitness = Some(if index % 2 == 0 { f32::NAN } else { index as f32 });
+        }
+        gp.calculate_population_fitness();
+        assert_eq!(gp.population[0].fitness, Some(23.0));
+        assert!(gp.population[12..].iter().all(|individual| individual.fitness.unwrap().is_nan()));
+    }
+
+    #[test]
+    fn test_mutated_periods_stay_in_range() {
+        let (mut growing, mut shrinking) = (100.0, 100.0);
+        for _ in 0..50 {
+            growing = mutate_number(growing, true, 1.39);
+            shrinking = mutate_number(shrinking, true, 0.7);
+        }
+        assert_eq!((growing, shrinking), (MAX_PERIOD, 2.0));
+        // constants aren't periods, they can leave the range
+        assert_eq!(mutate_number(200.0, false, 1.25), 250.0);
+    }
+
+    #[test]
+    fn test_replace_condition() {
+        let mut rule = Expression::parse("rsi(14) < 30 and not close > ema(9)").unwrap();
+        assert_eq!(conditions(&rule).len(), 4);
+        replace_condition(&mut rule, &mut 3, &Expression::parse("crosses_above(close, vwap())").unwrap());
+        assert_eq!(rule.to_string(), "rsi(14) < 30 and not crosses_above(close, vwap())");
+    }
+
+    #[test]
+    fn test_numbers() {
+        let mut rule = Expression::parse("rsi(14) < 30 and crosses_below(close, 101.5)").unwrap();
+        let mut values = Vec::new();
+        numbers(&mut rule, false, &mut values);
+        let values: Vec<(f32, bool)> = values.into_iter().map(|(value, period)| (*value, period)).collect();
+        assert_eq!(values, vec![(14.0, true), (30.0, false), (101.5, false)]);
+    }
+
+    #[test]
+    fn test_run() {
+        let config = small_config();
+        let best = search(1223, config.clone()).run();
+        assert!(best.fitness.is_some());
+        assert!(best.depth() <= config.max_depth);
+
+        // the elite is kept, so the best fitness never decreases, and the same seed finds the same rules
+        let mut gp = search(1223, config.clone());
+        gp.population = gp.initial_population();
+        gp.calculate_population_fitness();
+        assert!(best.fitness >= gp.population[0].fitness);
+        assert_eq!(search(1223, config).run(), best);
+    }
+
+    #[test]
+    fn test_long_only() {
+        let mut gp = search(3, GpConfig { short_entries: false, ..small_config() });
+        assert!(gp.random_individual().short_entry.is_none());
+        assert!(gp.run().config(&RuleStrategyConfig::default()).short_entry.is_none());
+    }
+}
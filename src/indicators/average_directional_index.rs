@@ -0,0 +1,162 @@
+use crate::candlestick::{Close, High, Low};
+use crate::indicators::traits::{Indicator, Next, Period, Reset};
+use crate::indicators::true_range::TrueRange;
+use std::fmt;
+
+/// Strength of the trend (ADX) and the directional indicators (+DI and -DI) of Welles Wilder, from 0 to 100.
+/// All values are smoothed with the moving average of Wilder, started on the first value
+pub struct AverageDirectionalIndex {
+    period: usize,
+    true_range: TrueRange,
+    prev_high_low: Option<(f32, f32)>,
+    smoothed_true_range: Option<f32>,
+    smoothed_plus_dm: Option<f32>,
+    smoothed_minus_dm: Option<f32>,
+    smoothed_dx: Option<f32>,
+    current: AverageDirectionalIndexOutput,
+    count: usize, // inputs received since the last reset
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct AverageDirectionalIndexOutput {
+    pub adx: f32,
+    pub plus_di: f32,
+    pub minus_di: f32,
+}
+
+// moving average of Wilder, an exponential average with alpha = 1 / period
+fn wilder_smoothing(previous: &mut Option<f32>, value: f32, period: usize) -> f32 {
+    let smoothed = match *previous {
+        Some(previous) => previous + (value - previous) / period as f32,
+        None => value,
+    };
+    *previous = Some(smoothed);
+    smoothed
+}
+
+impl AverageDirectionalIndex {
+    pub fn new(period: usize) -> Self {
+        Self {
+            period: period.max(1),
+            true_range: TrueRange::new(),
+            prev_high_low: None,
+            smoothed_true_range: None,
+            smoothed_plus_dm: None,
+            smoothed_minus_dm: None,
+            smoothed_dx: None,
+            current: AverageDirectionalIndexOutput::default(),
+            count: 0,
+        }
+    }
+
+    pub fn value(&self) -> AverageDirectionalIndexOutput {
+        self.current
+    }
+
+    pub fn reset(&mut self) {
+        self.true_range.reset();
+        self.prev_high_low = None;
+        self.smoothed_true_range = None;
+        self.smoothed_plus_dm = None;
+        self.smoothed_minus_dm = None;
+        self.smoothed_dx = None;
+        self.current = AverageDirectionalIndexOutput::default();
+        self.count = 0;
+    }
+}
+
+impl Period for AverageDirectionalIndex {
+    fn period(&self) -> usize {
+        self.period
+    }
+}
+
+impl<T: High + Low + Close> Next<&T> for AverageDirectionalIndex {
+    type Output = AverageDirectionalIndexOutput;
+
+    fn next(&mut self, input: &T) -> Self::Output {
+        self.count += 1;
+        let (plus_dm, minus_dm) = match self.prev_high_low {
+            Some((prev_high, prev_low)) => {
+                let up_move = input.high() - prev_high;
+                let down_move = prev_low - input.low();
+                (
+                    if up_move > down_move && up_move > 0.0 { up_move } else { 0.0 },
+                    if down_move > up_move && down_move > 0.0 { down_move } else { 0.0 },
+                )
+            }
+            None => (0.0, 0.0),
+        };
+        self.prev_high_low = Some((input.high(), input.low()));
+
+        let true_range = wilder_smoothing(&mut self.smoothed_true_range, self.true_range.next(input), self.period);
+        let plus_dm = wilder_smoothing(&mut self.smoothed_plus_dm, plus_dm, self.period);
+        let minus_dm = wilder_smoothing(&mut self.smoothed_minus_dm, minus_dm, self.period);
+
+        let (plus_di, minus_di) = if true_range > 0.0 {
+            (100.0 * plus_dm / true_range, 100.0 * minus_dm / true_range)
+        } else {
+            (0.0, 0.0)
+        };
+        let dx = if plus_di + minus_di > 0.0 { 100.0 * (plus_di - minus_di).abs() / (plus_di + minus_di) } else { 0.0 };
+
+        self.current = AverageDirectionalIndexOutput {
+            adx: wilder_smoothing(&mut self.smoothed_dx, dx, self.period),
+            plus_di,
+            minus_di,
+        };
+        self.current
+    }
+}
+
+impl<T: High + Low + Close> Indicator<&T> for AverageDirectionalIndex {
+    fn value(&self) -> AverageDirectionalIndexOutput {
+        self.current
+    }
+
+    fn is_ready(&self) -> bool {
+        self.count >= Indicator::<&T>::warmup_period(self)
+    }
+
+    fn warmup_period(&self) -> usize {
+        // the directional movement is smoothed over a period and the adx smooths it again
+        2 * self.period
+    }
+}
+
+impl Reset for AverageDirectionalIndex {
+    fn reset(&mut self) {
+        self.reset();
+    }
+}
+
+impl fmt::Display for AverageDirectionalIndex {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "ADX({})", self.period)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::candlestick::Candlestick;
+
+    fn round(output: AverageDirectionalIndexOutput) -> (f32, f32, f32) {
+        let round = |value: f32| (value * 100.0).round() / 100.0;
+        (round(output.adx), round(output.plus_di), round(output.minus_di))
+    }
+
+    #[test]
+    fn test_next() {
+        let mut adx = AverageDirectionalIndex::new(3);
+        assert_eq!(round(adx.next(&Candlestick::new().high(10.0).low(8.0).close(9.0))), (0.0, 0.0, 0.0));
+        assert_eq!(round(adx.next(&Candlestick::new().high(12.0).low(9.0).close(11.5))), (33.33, 28.57, 0.0));
+        assert_eq!(round(adx.next(&Candlestick::new().high(13.0).low(10.5).close(12.0))), (55.56, 32.56, 0.0));
+        assert_eq!(round(adx.next(&Candlestick::new().high(12.5).low(8.5).close(9.0))), (41.2, 17.72, 22.78));
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(format!("{}", AverageDirectionalIndex::new(14)), "ADX(14)");
+    }
+}
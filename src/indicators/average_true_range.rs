@@ -1,22 +1,32 @@
 use crate::candlestick::{Close, High, Low};
-use crate::indicators::traits::{Next, Period};
-use crate::indicators::exponential_moving_average::ExponentialMovingAverage as Ema;
+use crate::indicators::traits::{Indicator, MovingAverage, Next, Period, Reset};
+use crate::indicators::moving_average::Smoothing;
 use crate::indicators::true_range::TrueRange;
 use std::fmt;
 
+/// Average true range, by default smoothed with an ema starting on the range of the first candle.
+/// `Smoothing::Wilder` gives the standard atr of TA-Lib, which starts on the second candle as the first one has no previous close
 pub struct AverageTrueRange {
     true_range: TrueRange,
-    ema: Ema,
+    smoothing: Smoothing,
+    ema: Box<dyn MovingAverage>,
 }
 
 impl AverageTrueRange {
     pub fn new(period: usize) -> Self {
         Self {
             true_range: TrueRange::new(),
-            ema: Ema::new(period),
+            smoothing: Smoothing::Ema,
+            ema: Smoothing::Ema.build(period),
         }
     }
 
+    pub fn smoothing(mut self, smoothing: Smoothing) -> Self {
+        self.smoothing = smoothing;
+        self.ema = smoothing.build(self.ema.period());
+        self
+    }
+
     pub fn value(&self) -> f32 {
         self.ema.value()
     }
@@ -25,6 +35,14 @@ impl AverageTrueRange {
         self.true_range.reset();
         self.ema.reset();
     }
+
+    // averages the last true range, except the one of the first candle when it is skipped
+    fn smooth(&mut self, true_range: f32, first_candle: bool) -> f32 {
+        if first_candle && self.smoothing == Smoothing::Wilder {
+            return self.ema.value();
+        }
+        self.ema.next(true_range)
+    }
 }
 
 impl Period for AverageTrueRange {
@@ -37,7 +55,9 @@ impl Next<f32> for AverageTrueRange {
     type Output = f32;
 
     fn next(&mut self, input: f32) -> Self::Output {
-        self.ema.next(self.true_range.next(input))
+        let first_candle = !Indicator::<f32>::is_ready(&self.true_range);
+        let true_range = self.true_range.next(input);
+        self.smooth(true_range, first_candle)
     }
 }
 
@@ -45,13 +65,41 @@ impl<T: High + Low + Close> Next<&T> for AverageTrueRange {
     type Output = f32;
 
     fn next(&mut self, input: &T) -> Self::Output {
-        self.ema.next(self.true_range.next(input))
+        let first_candle = !Indicator::<&T>::is_ready(&self.true_range);
+        let true_range = self.true_range.next(input);
+        self.smooth(true_range, first_candle)
+    }
+}
+
+impl<T: High + Low + Close> Indicator<&T> for AverageTrueRange {
+    fn value(&self) -> f32 {
+        self.ema.value()
+    }
+
+    fn is_ready(&self) -> bool {
+        self.ema.is_ready()
+    }
+
+    fn warmup_period(&self) -> usize {
+        match self.smoothing {
+            Smoothing::Ema => self.ema.period(),
+            Smoothing::Wilder => self.ema.period() + 1,
+        }
+    }
+}
+
+impl Reset for AverageTrueRange {
+    fn reset(&mut self) {
+        self.reset();
     }
 }
 
 impl fmt::Display for AverageTrueRange {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "ATR({})", self.ema.period())
+        match self.smoothing {
+            Smoothing::Ema => write!(f, "ATR({})", self.ema.period()),
+            Smoothing::Wilder => write!(f, "ATR({}, wilder)", self.ema.period()),
+        }
     }
 }
 
@@ -78,6 +126,25 @@ mod tests {
         assert_eq!(atr.next(&candle3), 3.375);
     }
 
+    #[test]
+    fn test_wilder_matches_reference() {
+        let mut atr = AverageTrueRange::new(14).smoothing(Smoothing::Wilder);
+        let fixture = include_str!("fixtures/atr_wilder_14.csv");
+        let rows = fixture.lines().filter(|line| !line.starts_with('#')).skip(1);
+        for (i, row) in rows.enumerate() {
+            let columns: Vec<&str> = row.split(',').collect();
+            let candle = Candlestick::new()
+                .high(columns[0].parse().unwrap())
+                .low(columns[1].parse().unwrap())
+                .close(columns[2].parse().unwrap());
+            let value = atr.next(&candle);
+            assert_eq!(Indicator::<&Candlestick>::is_ready(&atr), !columns[3].is_empty(), "readiness of row {}", i);
+            if let Ok(expected) = columns[3].parse::<f32>() {
+                assert!((value - expected).abs() < 1e-4, "row {}: expected {} but was {}", i, expected, value);
+            }
+        }
+    }
+
     #[test]
     fn test_display() {
         let indicator = AverageTrueRange::new(8);
@@ -0,0 +1,45 @@
+use crate::candlestick::Candlestick;
+use crate::indicators::traits::Next;
+
+/// Computes an indicator over a whole series at once, returning one output for each input.
+/// Implemented for every indicator that takes a `f32` or a `&Candlestick`, continuing from its current state
+pub trait Batch<T> {
+    type Output;
+    fn batch(&mut self, inputs: &[T]) -> Vec<Self::Output>;
+}
+
+impl<N: Next<f32>> Batch<f32> for N {
+    type Output = N::Output;
+
+    fn batch(&mut self, inputs: &[f32]) -> Vec<Self::Output> {
+        inputs.iter().map(|input| self.next(*input)).collect()
+    }
+}
+
+impl<N, O> Batch<Candlestick> for N where N: for<'a> Next<&'a Candlestick, Output = O> {
+    type Output = O;
+
+    fn batch(&mut self, inputs: &[Candlestick]) -> Vec<Self::Output> {
+        inputs.iter().map(|input| self.next(input)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indicators::simple_moving_average::SimpleMovingAverage as Sma;
+    use crate::indicators::average_true_range::AverageTrueRange as Atr;
+
+    #[test]
+    fn test_batch_matches_next() {
+        assert_eq!(Sma::new(2).batch(&[2.0, 4.0, 8.0]), vec![2.0, 3.0, 6.0]);
+
+        let candles: Vec<Candlestick> = (0..20).map(|i| {
+            let price = 10.0 + (i % 7) as f32;
+            Candlestick::new().open(price).high(price + 2.0).low(price - 1.0).close(price + 1.0)
+        }).collect();
+        let mut streaming = Atr::new(5);
+        let expected: Vec<f32> = candles.iter().map(|candle| streaming.next(candle)).collect();
+        assert_eq!(Atr::new(5).batch(&candles), expected);
+    }
+}
@@ -0,0 +1,146 @@
+use crate::candlestick::Close;
+use crate::indicators::traits::{Indicator, Next, Period, Reset};
+use std::fmt;
+
+/// Simple moving average with bands `multiplier` standard deviations above and below it
+pub struct BollingerBands {
+    period: usize,
+    multiplier: f32,
+    index: usize,
+    count: usize,
+    deque: Box<[f32]>,
+    current: BollingerBandsOutput,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct BollingerBandsOutput {
+    pub average: f32,
+    pub upper: f32,
+    pub lower: f32,
+}
+
+impl BollingerBands {
+    pub fn new(period: usize, multiplier: f32) -> Self {
+        Self {
+            period: period.max(1),
+            multiplier,
+            index: 0,
+            count: 0,
+            deque: vec![0.0; period.max(1)].into_boxed_slice(),
+            current: BollingerBandsOutput::default(),
+        }
+    }
+
+    pub fn value(&self) -> BollingerBandsOutput {
+        self.current
+    }
+
+    pub fn reset(&mut self) {
+        self.index = 0;
+        self.count = 0;
+        self.current = BollingerBandsOutput::default();
+        for value in self.deque.iter_mut() {
+            *value = 0.0;
+        }
+    }
+}
+
+impl Period for BollingerBands {
+    fn period(&self) -> usize {
+        self.period
+    }
+}
+
+impl Next<f32> for BollingerBands {
+    type Output = BollingerBandsOutput;
+
+    fn next(&mut self, input: f32) -> Self::Output {
+        self.deque[self.index] = input;
+        self.index = (self.index + 1) % self.period;
+        if self.count < self.period {
+            self.count += 1;
+        }
+
+        // population standard deviation of the values in the window
+        let values = &self.deque[..self.count];
+        let average = values.iter().sum::<f32>() / self.count as f32;
+        let variance = values.iter().map(|value| (value - average).powi(2)).sum::<f32>() / self.count as f32;
+        let deviation = variance.sqrt() * self.multiplier;
+
+        self.current = BollingerBandsOutput {
+            average,
+            upper: average + deviation,
+            lower: average - deviation,
+        };
+        self.current
+    }
+}
+
+impl<T: Close> Next<&T> for BollingerBands {
+    type Output = BollingerBandsOutput;
+
+    fn next(&mut self, input: &T) -> Self::Output {
+        self.next(input.close())
+    }
+}
+
+impl Indicator<f32> for BollingerBands {
+    fn value(&self) -> BollingerBandsOutput {
+        self.current
+    }
+
+    fn is_ready(&self) -> bool {
+        self.count == self.period
+    }
+
+    fn warmup_period(&self) -> usize {
+        self.period
+    }
+}
+
+impl Reset for BollingerBands {
+    fn reset(&mut self) {
+        self.reset();
+    }
+}
+
+impl fmt::Display for BollingerBands {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "BB({}, {})", self.period, self.multiplier)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::candlestick::Candlestick;
+
+    fn round(output: BollingerBandsOutput) -> (f32, f32, f32) {
+        let round = |value: f32| (value * 100.0).round() / 100.0;
+        (round(output.average), round(output.upper), round(output.lower))
+    }
+
+    #[test]
+    fn test_next() {
+        let mut bb = BollingerBands::new(3, 2.0);
+        assert_eq!(round(bb.next(2.0)), (2.0, 2.0, 2.0));
+        assert_eq!(round(bb.next(5.0)), (3.5, 6.5, 0.5));
+        assert_eq!(round(bb.next(1.0)), (2.67, 6.07, -0.73));
+        assert_eq!(round(bb.next(6.25)), (4.08, 8.56, -0.4));
+        assert_eq!(round(bb.next(&Candlestick::new().close(4.0))), (3.75, 8.05, -0.55));
+    }
+
+    #[test]
+    fn test_reset() {
+        let mut bb = BollingerBands::new(5, 2.0);
+        bb.next(3.0);
+        bb.next(8.0);
+        bb.reset();
+        assert_eq!(bb.next(4.0).average, 4.0);
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(format!("{}", BollingerBands::new(20, 2.5)), "BB(20, 2.5)");
+    }
+}
@@ -0,0 +1,163 @@
+use crate::candlestick::Candlestick;
+use crate::indicators::average_true_range::AverageTrueRange as Atr;
+use crate::indicators::batch::Batch;
+use crate::indicators::moving_average::MovingAverageType;
+use crate::indicators::price_source::PriceSource;
+use crate::indicators::relative_strength_index::RelativeStrengthIndex as Rsi;
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+/// Indicator series that can be precomputed and shared between strategies
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum IndicatorKey {
+    MovingAverage(MovingAverageType, usize, PriceSource),
+    Rsi(usize, PriceSource),
+    Atr(usize),
+}
+
+impl IndicatorKey {
+    /// computes the indicator from the first candle, one value for each candle
+    pub fn compute(&self, candles: &[Candlestick]) -> Vec<f32> {
+        let prices = |source: &PriceSource| candles.iter().map(|candle| source.of(candle)).collect::<Vec<f32>>();
+        match self {
+            IndicatorKey::MovingAverage(kind, period, source) => {
+                let mut average = kind.build(*period);
+                prices(source).into_iter().map(|price| average.next(price)).collect()
+            }
+            IndicatorKey::Rsi(period, source) => Rsi::new(*period).batch(&prices(source)),
+            IndicatorKey::Atr(period) => Atr::new(*period).batch(candles),
+        }
+    }
+}
+
+// a series is identified by the position of the candles it was computed on and the indicator
+type SeriesId = (usize, usize, IndicatorKey);
+
+/// Precomputed indicator series shared between threads, so individuals using the same indicators
+/// over the same candles compute them only once. Series are computed on the first request
+pub struct IndicatorCache {
+    series: RwLock<HashMap<SeriesId, Arc<[f32]>>>,
+    max_series: usize,
+}
+
+impl IndicatorCache {
+    pub fn new() -> Self {
+        Self {
+            series: RwLock::new(HashMap::new()),
+            max_series: 4096,
+        }
+    }
+
+    /// amount of series kept in memory, once it is reached new series are computed on each request without being stored
+    pub fn max_series(mut self, max_series: usize) -> Self {
+        self.max_series = max_series;
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.series.read().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn clear(&mut self) {
+        self.series.write().unwrap().clear();
+    }
+
+    /// view of the cache for `candles`, that start at the position `offset` of the full candle series
+    pub fn range<'a>(&'a self, candles: &'a [Candlestick], offset: usize) -> RangeCache<'a> {
+        RangeCache { cache: self, candles, offset }
+    }
+
+    fn get(&self, candles: &[Candlestick], offset: usize, key: IndicatorKey) -> Arc<[f32]> {
+        let id = (offset, candles.len(), key);
+        if let Some(series) = self.series.read().unwrap().get(&id) {
+            return series.clone();
+        }
+
+        // computed without holding the lock, two threads may compute the same series but only the first is kept
+        let series: Arc<[f32]> = key.compute(candles).into();
+        let mut all_series = self.series.write().unwrap();
+        if all_series.len() >= self.max_series && !all_series.contains_key(&id) {
+            return series;
+        }
+        all_series.entry(id).or_insert(series).clone()
+    }
+}
+
+impl Default for IndicatorCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Indicator series of a single range of candles, the value at index `i` is the one of the indicator after its `i`th candle
+pub struct RangeCache<'a> {
+    cache: &'a IndicatorCache,
+    candles: &'a [Candlestick],
+    offset: usize,
+}
+
+impl RangeCache<'_> {
+    pub fn get(&self, key: IndicatorKey) -> Arc<[f32]> {
+        self.cache.get(self.candles, self.offset, key)
+    }
+
+    pub fn len(&self) -> usize {
+        self.candles.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.candles.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candles() -> Vec<Candlestick> {
+        (0..30).map(|i| {
+            let price = 10.0 + (i % 9) as f32;
+            Candlestick::new().open(price).high(price + 2.0).low(price - 1.0).close(price + 1.0)
+        }).collect()
+    }
+
+    #[test]
+    fn test_compute_matches_streaming() {
+        let candles = candles();
+        let mut ema = MovingAverageType::Ema.build(5);
+        let expected: Vec<f32> = candles.iter().map(|candle| ema.next((candle.high + candle.low) / 2.0)).collect();
+        assert_eq!(IndicatorKey::MovingAverage(MovingAverageType::Ema, 5, PriceSource::Hl2).compute(&candles), expected);
+    }
+
+    #[test]
+    fn test_series_are_shared() {
+        let candles = candles();
+        let cache = IndicatorCache::new();
+        let first = cache.range(&candles[..20], 0).get(IndicatorKey::Atr(3));
+        let second = cache.range(&candles[..20], 0).get(IndicatorKey::Atr(3));
+        assert!(Arc::ptr_eq(&first, &second));
+        assert_eq!(first.len(), 20);
+
+        // the same indicator on other candles is a different series
+        let other = cache.range(&candles[10..], 10).get(IndicatorKey::Atr(3));
+        assert!(!Arc::ptr_eq(&first, &other));
+        assert_eq!(cache.len(), 2);
+    }
+
+    #[test]
+    fn test_max_series() {
+        let candles = candles();
+        let cache = IndicatorCache::new().max_series(1);
+        let range = cache.range(&candles, 0);
+        range.get(IndicatorKey::Rsi(14, PriceSource::Close));
+        let first = range.get(IndicatorKey::Atr(3));
+        let second = range.get(IndicatorKey::Atr(3));
+        assert!(!Arc::ptr_eq(&first, &second));
+        assert_eq!(first, second);
+        assert_eq!(cache.len(), 1);
+    }
+}
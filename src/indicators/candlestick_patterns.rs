@@ -0,0 +1,748 @@
+use crate::candlestick::Candlestick;
+use crate::indicators::traits::{Indicator, Next, Reset};
+use std::collections::VecDeque;
+use std::fmt;
+
+// Every detector returns a signal strength between -1.0 and 1.0 for the last candle received: positive values are
+// bullish patterns, negative values bearish ones and 0.0 means the pattern was not found. Doji and inside bars
+// don't point to a direction, so they only return values between 0.0 and 1.0
+
+/// Type alias of any pattern detector, so a rule can hold detectors without knowing their type
+pub type PatternDetector = Box<dyn for<'a> Indicator<&'a Candlestick, Output = f32> + Send>;
+
+// body and shadows of a candle
+#[derive(Debug, Clone, Copy)]
+struct Shape {
+    open: f32,
+    high: f32,
+    low: f32,
+    close: f32,
+}
+
+impl Shape {
+    fn new(candle: &Candlestick) -> Self {
+        Self { open: candle.open, high: candle.high, low: candle.low, close: candle.close }
+    }
+
+    fn body(&self) -> f32 {
+        (self.close - self.open).abs()
+    }
+
+    fn range(&self) -> f32 {
+        self.high - self.low
+    }
+
+    fn body_top(&self) -> f32 {
+        self.open.max(self.close)
+    }
+
+    fn body_bottom(&self) -> f32 {
+        self.open.min(self.close)
+    }
+
+    fn upper_shadow(&self) -> f32 {
+        self.high - self.body_top()
+    }
+
+    fn lower_shadow(&self) -> f32 {
+        self.body_bottom() - self.low
+    }
+
+    fn is_bullish(&self) -> bool {
+        self.close > self.open
+    }
+
+    fn is_bearish(&self) -> bool {
+        self.close < self.open
+    }
+
+    fn midpoint(&self) -> f32 {
+        (self.open + self.close) / 2.0
+    }
+}
+
+// keeps the last `size` shapes received
+struct Window {
+    size: usize,
+    shapes: VecDeque<Shape>,
+}
+
+impl Window {
+    fn new(size: usize) -> Self {
+        Self { size, shapes: VecDeque::with_capacity(size) }
+    }
+
+    // adds the candle and returns true once the window is full
+    fn push(&mut self, candle: &Candlestick) -> bool {
+        if self.shapes.len() == self.size {
+            self.shapes.pop_front();
+        }
+        self.shapes.push_back(Shape::new(candle));
+        self.shapes.len() == self.size
+    }
+
+    fn is_full(&self) -> bool {
+        self.shapes.len() == self.size
+    }
+
+    fn get(&self, index: usize) -> Shape {
+        self.shapes[index]
+    }
+
+    fn clear(&mut self) {
+        self.shapes.clear();
+    }
+}
+
+/// Engulfing pattern, the body of the candle covers the whole body of the previous candle of the opposite color.
+/// The strength grows with the size of the body compared to the engulfed one, reaching 1.0 at twice its size
+pub struct Engulfing {
+    window: Window,
+    current_value: f32,
+}
+
+impl Engulfing {
+    pub fn new() -> Self {
+        Self { window: Window::new(2), current_value: 0.0 }
+    }
+
+    pub fn value(&self) -> f32 {
+        self.current_value
+    }
+
+    pub fn reset(&mut self) {
+        self.window.clear();
+        self.current_value = 0.0;
+    }
+}
+
+impl Default for Engulfing {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Next<&Candlestick> for Engulfing {
+    type Output = f32;
+
+    fn next(&mut self, input: &Candlestick) -> Self::Output {
+        self.current_value = 0.0;
+        if !self.window.push(input) {
+            return self.current_value;
+        }
+
+        let previous = self.window.get(0);
+        let current = self.window.get(1);
+        let engulfs = current.body_top() >= previous.body_top() && current.body_bottom() <= previous.body_bottom()
+            && current.body() > previous.body();
+        if engulfs {
+            let strength = if previous.body() > 0.0 { (current.body() / (2.0 * previous.body())).min(1.0) } else { 1.0 };
+            if previous.is_bearish() && current.is_bullish() {
+                self.current_value = strength;
+            } else if previous.is_bullish() && current.is_bearish() {
+                self.current_value = -strength;
+            }
+        }
+        self.current_value
+    }
+}
+
+impl Indicator<&Candlestick> for Engulfing {
+    fn value(&self) -> f32 {
+        self.current_value
+    }
+
+    fn is_ready(&self) -> bool {
+        self.window.is_full()
+    }
+
+    fn warmup_period(&self) -> usize {
+        self.window.size
+    }
+}
+
+impl Reset for Engulfing {
+    fn reset(&mut self) {
+        self.reset();
+    }
+}
+
+impl fmt::Display for Engulfing {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "ENGULFING")
+    }
+}
+
+/// Hammer, a small body at the top of the candle with a lower shadow at least twice the body and almost no upper shadow.
+/// The strength is the part of the candle range taken by the lower shadow
+pub struct Hammer {
+    current_value: f32,
+}
+
+impl Hammer {
+    pub fn new() -> Self {
+        Self { current_value: 0.0 }
+    }
+
+    pub fn value(&self) -> f32 {
+        self.current_value
+    }
+
+    pub fn reset(&mut self) {
+        self.current_value = 0.0;
+    }
+}
+
+impl Default for Hammer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Next<&Candlestick> for Hammer {
+    type Output = f32;
+
+    fn next(&mut self, input: &Candlestick) -> Self::Output {
+        let shape = Shape::new(input);
+        let range = shape.range();
+        let is_hammer = range > 0.0
+            && shape.lower_shadow() >= 2.0 * shape.body()
+            && shape.upper_shadow() <= 0.1 * range;
+        self.current_value = if is_hammer { shape.lower_shadow() / range } else { 0.0 };
+        self.current_value
+    }
+}
+
+impl Indicator<&Candlestick> for Hammer {
+    fn value(&self) -> f32 {
+        self.current_value
+    }
+
+    fn is_ready(&self) -> bool {
+        // the pattern only looks at the last candle, there is nothing to warm up
+        true
+    }
+
+    fn warmup_period(&self) -> usize {
+        0
+    }
+}
+
+impl Reset for Hammer {
+    fn reset(&mut self) {
+        self.reset();
+    }
+}
+
+impl fmt::Display for Hammer {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "HAMMER")
+    }
+}
+
+/// Doji, the open and close are almost the same (the body is at most `max_body` of the candle range, 0.1 by default).
+/// It shows indecision so it has no direction, the strength is 1.0 when open and close are equal
+pub struct Doji {
+    max_body: f32,
+    current_value: f32,
+}
+
+impl Doji {
+    pub fn new() -> Self {
+        Self { max_body: 0.1, current_value: 0.0 }
+    }
+
+    /// largest body as a fraction of the candle range still considered a doji
+    pub fn max_body(mut self, fraction: f32) -> Self {
+        self.max_body = fraction;
+        self
+    }
+
+    pub fn value(&self) -> f32 {
+        self.current_value
+    }
+
+    pub fn reset(&mut self) {
+        self.current_value = 0.0;
+    }
+}
+
+impl Default for Doji {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Next<&Candlestick> for Doji {
+    type Output = f32;
+
+    fn next(&mut self, input: &Candlestick) -> Self::Output {
+        let shape = Shape::new(input);
+        let range = shape.range();
+        self.current_value = if range > 0.0 && shape.body() <= self.max_body * range {
+            1.0 - shape.body() / (self.max_body * range)
+        } else {
+            0.0
+        };
+        self.current_value
+    }
+}
+
+impl Indicator<&Candlestick> for Doji {
+    fn value(&self) -> f32 {
+        self.current_value
+    }
+
+    fn is_ready(&self) -> bool {
+        // the pattern only looks at the last candle, there is nothing to warm up
+        true
+    }
+
+    fn warmup_period(&self) -> usize {
+        0
+    }
+}
+
+impl Reset for Doji {
+    fn reset(&mut self) {
+        self.reset();
+    }
+}
+
+impl fmt::Display for Doji {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "DOJI({})", self.max_body)
+    }
+}
+
+/// Morning star (bullish) and evening star (bearish): a long candle, a small body beyond its close and a candle of the
+/// opposite color closing past the middle of the first body. The strength is how deep the last close goes into the first body
+pub struct Star {
+    window: Window,
+    current_value: f32,
+}
+
+impl Star {
+    pub fn new() -> Self {
+        Self { window: Window::new(3), current_value: 0.0 }
+    }
+
+    pub fn value(&self) -> f32 {
+        self.current_value
+    }
+
+    pub fn reset(&mut self) {
+        self.window.clear();
+        self.current_value = 0.0;
+    }
+}
+
+impl Default for Star {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Next<&Candlestick> for Star {
+    type Output = f32;
+
+    fn next(&mut self, input: &Candlestick) -> Self::Output {
+        self.current_value = 0.0;
+        if !self.window.push(input) {
+            return self.current_value;
+        }
+
+        let first = self.window.get(0);
+        let star = self.window.get(1);
+        let last = self.window.get(2);
+        let body = first.body();
+        if body == 0.0 || star.body() > 0.3 * body {
+            return self.current_value;
+        }
+
+        let penetration = ((last.close - first.close) / (first.open - first.close)).min(1.0);
+        if first.is_bearish() && star.midpoint() < first.close && last.is_bullish() && last.close > first.midpoint() {
+            self.current_value = penetration;
+        } else if first.is_bullish() && star.midpoint() > first.close && last.is_bearish() && last.close < first.midpoint() {
+            self.current_value = -penetration;
+        }
+        self.current_value
+    }
+}
+
+impl Indicator<&Candlestick> for Star {
+    fn value(&self) -> f32 {
+        self.current_value
+    }
+
+    fn is_ready(&self) -> bool {
+        self.window.is_full()
+    }
+
+    fn warmup_period(&self) -> usize {
+        self.window.size
+    }
+}
+
+impl Reset for Star {
+    fn reset(&mut self) {
+        self.reset();
+    }
+}
+
+impl fmt::Display for Star {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "STAR")
+    }
+}
+
+/// Inside bar, the whole range of the candle is inside the range of the previous one.
+/// It has no direction, the strength is how much smaller the range got
+pub struct InsideBar {
+    window: Window,
+    current_value: f32,
+}
+
+impl InsideBar {
+    pub fn new() -> Self {
+        Self { window: Window::new(2), current_value: 0.0 }
+    }
+
+    pub fn value(&self) -> f32 {
+        self.current_value
+    }
+
+    pub fn reset(&mut self) {
+        self.window.clear();
+        self.current_value = 0.0;
+    }
+}
+
+impl Default for InsideBar {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Next<&Candlestick> for InsideBar {
+    type Output = f32;
+
+    fn next(&mut self, input: &Candlestick) -> Self::Output {
+        self.current_value = 0.0;
+        if !self.window.push(input) {
+            return self.current_value;
+        }
+
+        let previous = self.window.get(0);
+        let current = self.window.get(1);
+        if current.high < previous.high && current.low > previous.low {
+            self.current_value = 1.0 - current.range() / previous.range();
+        }
+        self.current_value
+    }
+}
+
+impl Indicator<&Candlestick> for InsideBar {
+    fn value(&self) -> f32 {
+        self.current_value
+    }
+
+    fn is_ready(&self) -> bool {
+        self.window.is_full()
+    }
+
+    fn warmup_period(&self) -> usize {
+        self.window.size
+    }
+}
+
+impl Reset for InsideBar {
+    fn reset(&mut self) {
+        self.reset();
+    }
+}
+
+impl fmt::Display for InsideBar {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "INSIDE_BAR")
+    }
+}
+
+/// Outside bar, the range of the candle covers the whole range of the previous one. The direction comes from the
+/// color of the candle and the strength is how much bigger the range got, reaching 1.0 at twice the previous range
+pub struct OutsideBar {
+    window: Window,
+    current_value: f32,
+}
+
+impl OutsideBar {
+    pub fn new() -> Self {
+        Self { window: Window::new(2), current_value: 0.0 }
+    }
+
+    pub fn value(&self) -> f32 {
+        self.current_value
+    }
+
+    pub fn reset(&mut self) {
+        self.window.clear();
+        self.current_value = 0.0;
+    }
+}
+
+impl Default for OutsideBar {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Next<&Candlestick> for OutsideBar {
+    type Output = f32;
+
+    fn next(&mut self, input: &Candlestick) -> Self::Output {
+        self.current_value = 0.0;
+        if !self.window.push(input) {
+            return self.current_value;
+        }
+
+        let previous = self.window.get(0);
+        let current = self.window.get(1);
+        if current.high > previous.high && current.low < previous.low {
+            let strength = (1.0 - previous.range() / current.range()) * 2.0;
+            if current.is_bullish() {
+                self.current_value = strength.min(1.0);
+            } else if current.is_bearish() {
+                self.current_value = -strength.min(1.0);
+            }
+        }
+        self.current_value
+    }
+}
+
+impl Indicator<&Candlestick> for OutsideBar {
+    fn value(&self) -> f32 {
+        self.current_value
+    }
+
+    fn is_ready(&self) -> bool {
+        self.window.is_full()
+    }
+
+    fn warmup_period(&self) -> usize {
+        self.window.size
+    }
+}
+
+impl Reset for OutsideBar {
+    fn reset(&mut self) {
+        self.reset();
+    }
+}
+
+impl fmt::Display for OutsideBar {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "OUTSIDE_BAR")
+    }
+}
+
+/// Three white soldiers (bullish) and three black crows (bearish): three candles of the same color, each one closing
+/// beyond the previous close and opening inside the previous body. The strength is the mean part of the range taken by the bodies
+pub struct ThreeSoldiers {
+    window: Window,
+    current_value: f32,
+}
+
+impl ThreeSoldiers {
+    pub fn new() -> Self {
+        Self { window: Window::new(3), current_value: 0.0 }
+    }
+
+    pub fn value(&self) -> f32 {
+        self.current_value
+    }
+
+    pub fn reset(&mut self) {
+        self.window.clear();
+        self.current_value = 0.0;
+    }
+}
+
+impl Default for ThreeSoldiers {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Next<&Candlestick> for ThreeSoldiers {
+    type Output = f32;
+
+    fn next(&mut self, input: &Candlestick) -> Self::Output {
+        self.current_value = 0.0;
+        if !self.window.push(input) {
+            return self.current_value;
+        }
+
+        let shapes = [self.window.get(0), self.window.get(1), self.window.get(2)];
+        let opens_inside = |i: usize| {
+            shapes[i].open >= shapes[i - 1].body_bottom() && shapes[i].open <= shapes[i - 1].body_top()
+        };
+        let soldiers = shapes.iter().all(Shape::is_bullish)
+            && (1..3).all(|i| shapes[i].close > shapes[i - 1].close && opens_inside(i));
+        let crows = shapes.iter().all(Shape::is_bearish)
+            && (1..3).all(|i| shapes[i].close < shapes[i - 1].close && opens_inside(i));
+
+        if soldiers || crows {
+            let strength = shapes.iter().map(|shape| shape.body() / shape.range()).sum::<f32>() / 3.0;
+            self.current_value = if soldiers { strength } else { -strength };
+        }
+        self.current_value
+    }
+}
+
+impl Indicator<&Candlestick> for ThreeSoldiers {
+    fn value(&self) -> f32 {
+        self.current_value
+    }
+
+    fn is_ready(&self) -> bool {
+        self.window.is_full()
+    }
+
+    fn warmup_period(&self) -> usize {
+        self.window.size
+    }
+}
+
+impl Reset for ThreeSoldiers {
+    fn reset(&mut self) {
+        self.reset();
+    }
+}
+
+impl fmt::Display for ThreeSoldiers {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "THREE_SOLDIERS")
+    }
+}
+
+/// Kind of candlestick pattern, so a trade rule can pick the pattern it uses as entry condition
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CandlestickPattern {
+    Engulfing,
+    Hammer,
+    Doji,
+    Star,
+    InsideBar,
+    OutsideBar,
+    ThreeSoldiers,
+}
+
+impl CandlestickPattern {
+    pub fn build(&self) -> PatternDetector {
+        match self {
+            CandlestickPattern::Engulfing => Box::new(Engulfing::new()),
+            CandlestickPattern::Hammer => Box::new(Hammer::new()),
+            CandlestickPattern::Doji => Box::new(Doji::new()),
+            CandlestickPattern::Star => Box::new(Star::new()),
+            CandlestickPattern::InsideBar => Box::new(InsideBar::new()),
+            CandlestickPattern::OutsideBar => Box::new(OutsideBar::new()),
+            CandlestickPattern::ThreeSoldiers => Box::new(ThreeSoldiers::new()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candle(open: f32, high: f32, low: f32, close: f32) -> Candlestick {
+        Candlestick::new().open(open).high(high).low(low).close(close)
+    }
+
+    #[test]
+    fn test_engulfing() {
+        let mut engulfing = Engulfing::new();
+        assert_eq!(engulfing.next(&candle(10.0, 10.5, 8.5, 9.0)), 0.0);
+        assert_eq!(engulfing.next(&candle(8.5, 11.0, 8.0, 10.5)), 1.0);
+        assert_eq!(engulfing.next(&candle(10.5, 11.0, 7.5, 8.0)), -0.625);
+        // a bullish candle inside the body of the previous one
+        assert_eq!(engulfing.next(&candle(8.5, 9.0, 8.0, 9.0)), 0.0);
+
+        let mut engulfing = Engulfing::new();
+        engulfing.next(&candle(10.0, 10.0, 9.0, 9.0));
+        assert_eq!(engulfing.next(&candle(9.0, 10.5, 9.0, 10.5)), 0.75);
+    }
+
+    #[test]
+    fn test_hammer() {
+        let mut hammer = Hammer::new();
+        assert_eq!(hammer.next(&candle(9.0, 10.0, 6.0, 10.0)), 0.75);
+        // the upper shadow is too long
+        assert_eq!(hammer.next(&candle(9.0, 11.0, 6.0, 10.0)), 0.0);
+        // the body is too long
+        assert_eq!(hammer.next(&candle(7.0, 10.0, 6.0, 10.0)), 0.0);
+    }
+
+    #[test]
+    fn test_doji() {
+        let mut doji = Doji::new().max_body(0.25);
+        assert_eq!(doji.next(&candle(10.0, 12.0, 8.0, 10.0)), 1.0);
+        assert_eq!(doji.next(&candle(10.0, 12.0, 8.0, 10.5)), 0.5);
+        assert_eq!(doji.next(&candle(10.0, 12.0, 8.0, 12.0)), 0.0);
+        assert_eq!(doji.next(&candle(10.0, 10.0, 10.0, 10.0)), 0.0);
+        assert_eq!(format!("{}", doji), "DOJI(0.25)");
+    }
+
+    #[test]
+    fn test_morning_and_evening_star() {
+        let mut star = Star::new();
+        star.next(&candle(20.0, 20.5, 15.5, 16.0));
+        star.next(&candle(15.0, 15.5, 14.0, 14.5));
+        assert_eq!(star.next(&candle(15.0, 19.5, 15.0, 19.0)), 0.75);
+
+        let mut star = Star::new();
+        star.next(&candle(16.0, 20.5, 15.5, 20.0));
+        star.next(&candle(21.0, 22.0, 20.5, 21.5));
+        assert_eq!(star.next(&candle(21.0, 21.0, 15.5, 16.0)), -1.0);
+        // the second candle body is too long
+        star.next(&candle(16.0, 20.5, 15.5, 20.0));
+        assert_eq!(star.next(&candle(18.0, 19.5, 15.5, 17.0)), 0.0);
+    }
+
+    #[test]
+    fn test_inside_and_outside_bars() {
+        let mut inside = InsideBar::new();
+        let mut outside = OutsideBar::new();
+        let candles = [
+            candle(10.0, 12.0, 8.0, 11.0),
+            candle(11.0, 11.0, 9.0, 10.0),
+            candle(10.0, 13.0, 7.0, 8.0),
+            candle(8.0, 14.0, 6.0, 13.0),
+        ];
+        let inside_values: Vec<f32> = candles.iter().map(|c| inside.next(c)).collect();
+        let outside_values: Vec<f32> = candles.iter().map(|c| outside.next(c)).collect();
+        assert_eq!(inside_values, vec![0.0, 0.5, 0.0, 0.0]);
+        assert_eq!(outside_values, vec![0.0, 0.0, -1.0, 0.5]);
+    }
+
+    #[test]
+    fn test_three_soldiers_and_crows() {
+        let mut soldiers = ThreeSoldiers::new();
+        soldiers.next(&candle(10.0, 12.0, 10.0, 12.0));
+        soldiers.next(&candle(11.0, 14.0, 10.0, 13.0));
+        assert_eq!(soldiers.next(&candle(12.0, 16.0, 12.0, 15.0)), 0.75);
+        // opens above the previous body
+        assert_eq!(soldiers.next(&candle(16.0, 17.0, 16.0, 17.0)), 0.0);
+
+        soldiers.reset();
+        soldiers.next(&candle(15.0, 15.0, 13.0, 13.0));
+        soldiers.next(&candle(14.0, 14.0, 11.0, 11.0));
+        assert_eq!(soldiers.next(&candle(12.0, 12.0, 9.0, 9.0)), -1.0);
+    }
+
+    #[test]
+    fn test_build() {
+        let mut detector = CandlestickPattern::Hammer.build();
+        assert_eq!(detector.next(&candle(9.0, 10.0, 6.0, 10.0)), 0.75);
+    }
+}
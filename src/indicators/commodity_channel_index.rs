@@ -0,0 +1,108 @@
+use crate::candlestick::{Close, High, Low};
+use crate::indicators::traits::{Indicator, Next, Period, Reset};
+use std::collections::VecDeque;
+use std::fmt;
+
+/// Distance of the typical price from its moving average, measured in mean absolute deviations divided by 0.015
+pub struct CommodityChannelIndex {
+    period: usize,
+    window: VecDeque<f32>, // typical prices (high + low + close) / 3
+    current_value: f32,
+}
+
+impl CommodityChannelIndex {
+    pub fn new(period: usize) -> Self {
+        Self {
+            period: period.max(1),
+            window: VecDeque::with_capacity(period.max(1)),
+            current_value: 0.0,
+        }
+    }
+
+    pub fn value(&self) -> f32 {
+        self.current_value
+    }
+
+    pub fn reset(&mut self) {
+        self.window.clear();
+        self.current_value = 0.0;
+    }
+}
+
+impl Period for CommodityChannelIndex {
+    fn period(&self) -> usize {
+        self.period
+    }
+}
+
+// the input is used as the typical price
+impl Next<f32> for CommodityChannelIndex {
+    type Output = f32;
+
+    fn next(&mut self, input: f32) -> Self::Output {
+        if self.window.len() == self.period {
+            self.window.pop_front();
+        }
+        self.window.push_back(input);
+
+        let count = self.window.len() as f32;
+        let average = self.window.iter().sum::<f32>() / count;
+        let mean_deviation = self.window.iter().map(|price| (price - average).abs()).sum::<f32>() / count;
+        self.current_value = if mean_deviation > 0.0 { (input - average) / (0.015 * mean_deviation) } else { 0.0 };
+        self.current_value
+    }
+}
+
+impl<T: High + Low + Close> Next<&T> for CommodityChannelIndex {
+    type Output = f32;
+
+    fn next(&mut self, input: &T) -> Self::Output {
+        self.next((input.high() + input.low() + input.close()) / 3.0)
+    }
+}
+
+impl<T: High + Low + Close> Indicator<&T> for CommodityChannelIndex {
+    fn value(&self) -> f32 {
+        self.current_value
+    }
+
+    fn is_ready(&self) -> bool {
+        self.window.len() == self.period
+    }
+
+    fn warmup_period(&self) -> usize {
+        self.period
+    }
+}
+
+impl Reset for CommodityChannelIndex {
+    fn reset(&mut self) {
+        self.reset();
+    }
+}
+
+impl fmt::Display for CommodityChannelIndex {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "CCI({})", self.period)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::candlestick::Candlestick;
+
+    #[test]
+    fn test_next() {
+        let mut cci = CommodityChannelIndex::new(3);
+        assert_eq!(cci.next(&Candlestick::new().high(12.0).low(6.0).close(9.0)), 0.0);
+        assert_eq!(cci.next(&Candlestick::new().high(15.0).low(9.0).close(12.0)).round(), 67.0);
+        assert_eq!(cci.next(&Candlestick::new().high(9.0).low(6.0).close(7.0)).round(), -83.0);
+        assert_eq!(cci.next(14.0).round(), 76.0);
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(format!("{}", CommodityChannelIndex::new(20)), "CCI(20)");
+    }
+}
@@ -0,0 +1,509 @@
+use crate::indicators::traits::{Indicator, Next, Period, Reset};
+use std::collections::VecDeque;
+use std::fmt;
+
+/// Feeds the output of an indicator to another one, e.g. `OnBalanceVolume::new().then(Rsi::new(14))` is the rsi of the obv
+pub struct Chain<A, B> {
+    first: A,
+    second: B,
+    count: usize, // inputs received since the last reset
+}
+
+impl<A, B> Chain<A, B> {
+    pub fn new(first: A, second: B) -> Self {
+        Self { first, second, count: 0 }
+    }
+}
+
+impl<T, A: Next<T, Output = f32>, B: Next<f32>> Next<T> for Chain<A, B> {
+    type Output = B::Output;
+
+    fn next(&mut self, input: T) -> Self::Output {
+        self.count += 1;
+        self.second.next(self.first.next(input))
+    }
+}
+
+impl<T, A: Indicator<T, Output = f32>, B: Indicator<f32>> Indicator<T> for Chain<A, B> {
+    fn value(&self) -> Self::Output {
+        self.second.value()
+    }
+
+    fn is_ready(&self) -> bool {
+        self.count >= Indicator::<T>::warmup_period(self)
+    }
+
+    fn warmup_period(&self) -> usize {
+        // the second indicator starts warming up with the first output that is ready
+        let (first, second) = (self.first.warmup_period(), self.second.warmup_period());
+        if first == 0 || second == 0 { first + second } else { first + second - 1 }
+    }
+}
+
+impl<A: Reset, B: Reset> Reset for Chain<A, B> {
+    fn reset(&mut self) {
+        self.first.reset();
+        self.second.reset();
+        self.count = 0;
+    }
+}
+
+impl<A: fmt::Display, B: fmt::Display> fmt::Display for Chain<A, B> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}[{}]", self.second, self.first)
+    }
+}
+
+/// Adds `then` to every indicator, so indicators of indicators read in the order they are computed
+pub trait Then: Sized {
+    fn then<B>(self, second: B) -> Chain<Self, B> {
+        Chain::new(self, second)
+    }
+}
+
+impl<A> Then for A {}
+
+/// Indicator that always returns the same value, e.g. a threshold for `Cross`
+pub struct Constant {
+    value: f32,
+}
+
+impl Constant {
+    pub fn new(value: f32) -> Self {
+        Self { value }
+    }
+}
+
+impl<T> Next<T> for Constant {
+    type Output = f32;
+
+    fn next(&mut self, _input: T) -> Self::Output {
+        self.value
+    }
+}
+
+impl<T> Indicator<T> for Constant {
+    fn value(&self) -> f32 {
+        self.value
+    }
+
+    fn is_ready(&self) -> bool {
+        true
+    }
+
+    fn warmup_period(&self) -> usize {
+        0
+    }
+}
+
+impl Reset for Constant {
+    fn reset(&mut self) {}
+}
+
+impl fmt::Display for Constant {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.value)
+    }
+}
+
+/// Output of the first indicator minus the output of the second one, both receive the same input.
+/// e.g. the spread between a fast and a slow moving average
+pub struct Difference<A, B> {
+    first: A,
+    second: B,
+}
+
+impl<A, B> Difference<A, B> {
+    pub fn new(first: A, second: B) -> Self {
+        Self { first, second }
+    }
+}
+
+impl<T: Copy, A: Next<T, Output = f32>, B: Next<T, Output = f32>> Next<T> for Difference<A, B> {
+    type Output = f32;
+
+    fn next(&mut self, input: T) -> Self::Output {
+        self.first.next(input) - self.second.next(input)
+    }
+}
+
+impl<T: Copy, A: Indicator<T, Output = f32>, B: Indicator<T, Output = f32>> Indicator<T> for Difference<A, B> {
+    fn value(&self) -> f32 {
+        self.first.value() - self.second.value()
+    }
+
+    fn is_ready(&self) -> bool {
+        self.first.is_ready() && self.second.is_ready()
+    }
+
+    fn warmup_period(&self) -> usize {
+        self.first.warmup_period().max(self.second.warmup_period())
+    }
+}
+
+impl<A: Reset, B: Reset> Reset for Difference<A, B> {
+    fn reset(&mut self) {
+        self.first.reset();
+        self.second.reset();
+    }
+}
+
+impl<A: fmt::Display, B: fmt::Display> fmt::Display for Difference<A, B> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "({} - {})", self.first, self.second)
+    }
+}
+
+/// Output of the first indicator divided by the output of the second one, both receive the same input.
+/// Returns 0.0 while the second output is 0.0
+pub struct Ratio<A, B> {
+    first: A,
+    second: B,
+}
+
+impl<A, B> Ratio<A, B> {
+    pub fn new(first: A, second: B) -> Self {
+        Self { first, second }
+    }
+}
+
+impl<T: Copy, A: Next<T, Output = f32>, B: Next<T, Output = f32>> Next<T> for Ratio<A, B> {
+    type Output = f32;
+
+    fn next(&mut self, input: T) -> Self::Output {
+        let numerator = self.first.next(input);
+        let denominator = self.second.next(input);
+        if denominator != 0.0 { numerator / denominator } else { 0.0 }
+    }
+}
+
+impl<T: Copy, A: Indicator<T, Output = f32>, B: Indicator<T, Output = f32>> Indicator<T> for Ratio<A, B> {
+    fn value(&self) -> f32 {
+        let denominator = self.second.value();
+        if denominator != 0.0 { self.first.value() / denominator } else { 0.0 }
+    }
+
+    fn is_ready(&self) -> bool {
+        self.first.is_ready() && self.second.is_ready()
+    }
+
+    fn warmup_period(&self) -> usize {
+        self.first.warmup_period().max(self.second.warmup_period())
+    }
+}
+
+impl<A: Reset, B: Reset> Reset for Ratio<A, B> {
+    fn reset(&mut self) {
+        self.first.reset();
+        self.second.reset();
+    }
+}
+
+impl<A: fmt::Display, B: fmt::Display> fmt::Display for Ratio<A, B> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "({} / {})", self.first, self.second)
+    }
+}
+
+/// Detects when the first indicator crosses the second one: 1.0 when it crosses over, -1.0 when it crosses under
+/// and 0.0 otherwise. Touching the second line is not a cross until the first one moves to the other side
+pub struct Cross<A, B> {
+    first: A,
+    second: B,
+    previous_side: Option<f32>, // sign of the last difference that was not 0.0
+    current_value: f32,
+    count: usize, // inputs received since the last reset
+}
+
+impl<A, B> Cross<A, B> {
+    pub fn new(first: A, second: B) -> Self {
+        Self { first, second, previous_side: None, current_value: 0.0, count: 0 }
+    }
+}
+
+impl<T: Copy, A: Next<T, Output = f32>, B: Next<T, Output = f32>> Next<T> for Cross<A, B> {
+    type Output = f32;
+
+    fn next(&mut self, input: T) -> Self::Output {
+        self.count += 1;
+        let difference = self.first.next(input) - self.second.next(input);
+        self.current_value = 0.0;
+        if difference != 0.0 {
+            let side = difference.signum();
+            if self.previous_side.is_some_and(|previous| previous != side) {
+                self.current_value = side;
+            }
+            self.previous_side = Some(side);
+        }
+        self.current_value
+    }
+}
+
+impl<T: Copy, A: Indicator<T, Output = f32>, B: Indicator<T, Output = f32>> Indicator<T> for Cross<A, B> {
+    fn value(&self) -> f32 {
+        self.current_value
+    }
+
+    fn is_ready(&self) -> bool {
+        self.count >= Indicator::<T>::warmup_period(self)
+    }
+
+    fn warmup_period(&self) -> usize {
+        // a cross needs a ready value of both indicators before the current one
+        self.first.warmup_period().max(self.second.warmup_period()) + 1
+    }
+}
+
+impl<A: Reset, B: Reset> Reset for Cross<A, B> {
+    fn reset(&mut self) {
+        self.first.reset();
+        self.second.reset();
+        self.previous_side = None;
+        self.current_value = 0.0;
+        self.count = 0;
+    }
+}
+
+impl<A: fmt::Display, B: fmt::Display> fmt::Display for Cross<A, B> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "CROSS({}, {})", self.first, self.second)
+    }
+}
+
+/// Rolling z-score, how many standard deviations the value is away from the mean of the last `period` values.
+/// Returns 0.0 while all values of the window are equal
+pub struct ZScore {
+    period: usize,
+    window: VecDeque<f32>,
+    current_value: f32,
+}
+
+impl ZScore {
+    pub fn new(period: usize) -> Self {
+        Self {
+            period: period.max(1),
+            window: VecDeque::with_capacity(period.max(1)),
+            current_value: 0.0,
+        }
+    }
+
+    pub fn value(&self) -> f32 {
+        self.current_value
+    }
+}
+
+impl Period for ZScore {
+    fn period(&self) -> usize {
+        self.period
+    }
+}
+
+impl Next<f32> for ZScore {
+    type Output = f32;
+
+    fn next(&mut self, input: f32) -> Self::Output {
+        if self.window.len() == self.period {
+            self.window.pop_front();
+        }
+        self.window.push_back(input);
+
+        // population standard deviation of the values in the window
+        let count = self.window.len() as f32;
+        let mean = self.window.iter().sum::<f32>() / count;
+        let deviation = (self.window.iter().map(|value| (value - mean).powi(2)).sum::<f32>() / count).sqrt();
+        self.current_value = if deviation > 0.0 { (input - mean) / deviation } else { 0.0 };
+        self.current_value
+    }
+}
+
+impl Indicator<f32> for ZScore {
+    fn value(&self) -> f32 {
+        self.current_value
+    }
+
+    fn is_ready(&self) -> bool {
+        self.window.len() == self.period
+    }
+
+    fn warmup_period(&self) -> usize {
+        self.period
+    }
+}
+
+impl Reset for ZScore {
+    fn reset(&mut self) {
+        self.window.clear();
+        self.current_value = 0.0;
+    }
+}
+
+impl fmt::Display for ZScore {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "ZSCORE({})", self.period)
+    }
+}
+
+/// Rolling percentile rank, percentage (0 to 100) of the previous `period` values lower than or equal to the current one
+pub struct PercentileRank {
+    period: usize,
+    window: VecDeque<f32>,
+    current_value: f32,
+    count: usize, // inputs received since the last reset
+}
+
+impl PercentileRank {
+    pub fn new(period: usize) -> Self {
+        Self {
+            period: period.max(1),
+            window: VecDeque::with_capacity(period.max(1)),
+            current_value: 0.0,
+            count: 0,
+        }
+    }
+
+    pub fn value(&self) -> f32 {
+        self.current_value
+    }
+}
+
+impl Period for PercentileRank {
+    fn period(&self) -> usize {
+        self.period
+    }
+}
+
+impl Next<f32> for PercentileRank {
+    type Output = f32;
+
+    fn next(&mut self, input: f32) -> Self::Output {
+        self.count += 1;
+        // until there are previous values the rank is 0.0
+        if !self.window.is_empty() {
+            let lower = self.window.iter().filter(|value| **value <= input).count();
+            self.current_value = 100.0 * lower as f32 / self.window.len() as f32;
+        }
+
+        if self.window.len() == self.period {
+            self.window.pop_front();
+        }
+        self.window.push_back(input);
+        self.current_value
+    }
+}
+
+impl Indicator<f32> for PercentileRank {
+    fn value(&self) -> f32 {
+        self.current_value
+    }
+
+    fn is_ready(&self) -> bool {
+        self.count > self.period
+    }
+
+    fn warmup_period(&self) -> usize {
+        // the rank compares the value with `period` previous ones
+        self.period + 1
+    }
+}
+
+impl Reset for PercentileRank {
+    fn reset(&mut self) {
+        self.window.clear();
+        self.current_value = 0.0;
+        self.count = 0;
+    }
+}
+
+impl fmt::Display for PercentileRank {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "PERCENTRANK({})", self.period)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::candlestick::Candlestick;
+    use crate::indicators::average_true_range::AverageTrueRange as Atr;
+    use crate::indicators::exponential_moving_average::ExponentialMovingAverage as Ema;
+    use crate::indicators::on_balance_volume::OnBalanceVolume as Obv;
+    use crate::indicators::relative_strength_index::RelativeStrengthIndex as Rsi;
+    use crate::indicators::simple_moving_average::SimpleMovingAverage as Sma;
+
+    fn candle(close: f32, volume: f32) -> Candlestick {
+        Candlestick::new().open(close).high(close + 1.0).low(close - 1.0).close(close).volume(volume)
+    }
+
+    #[test]
+    fn test_chain() {
+        let candles = [candle(10.0, 100.0), candle(11.0, 50.0), candle(10.5, 20.0), candle(12.0, 30.0)];
+        let mut obv = Obv::new();
+        let mut rsi = Rsi::new(3);
+        let expected: Vec<f32> = candles.iter().map(|c| rsi.next(obv.next(c))).collect();
+
+        let mut rsi_of_obv = Obv::new().then(Rsi::new(3));
+        let values: Vec<f32> = candles.iter().map(|c| rsi_of_obv.next(c)).collect();
+        assert_eq!(values, expected);
+
+        rsi_of_obv.reset();
+        assert_eq!(rsi_of_obv.next(&candles[0]), expected[0]);
+        assert_eq!(format!("{}", Atr::new(14).then(Ema::new(5))), "EMA(5)[ATR(14)]");
+    }
+
+    #[test]
+    fn test_chain_warmup() {
+        let mut chain = Sma::new(3).then(Sma::new(2));
+        assert_eq!(chain.warmup_period(), 4);
+        (0..3).for_each(|price| { chain.next(price as f32); });
+        assert!(!chain.is_ready());
+        chain.next(3.0);
+        assert!(chain.is_ready());
+
+        let cross = Cross::new(Sma::new(3), Constant::new(50.0));
+        assert_eq!(Indicator::<f32>::warmup_period(&cross), 4);
+    }
+
+    #[test]
+    fn test_difference_and_ratio() {
+        let mut spread = Difference::new(Sma::new(1), Sma::new(2));
+        assert_eq!(spread.next(2.0), 0.0);
+        assert_eq!(spread.next(4.0), 1.0);
+        assert_eq!(format!("{}", spread), "(SMA(1) - SMA(2))");
+
+        let mut ratio = Ratio::new(Sma::new(1), Sma::new(2));
+        assert_eq!(ratio.next(0.0), 0.0);
+        assert_eq!(ratio.next(4.0), 2.0);
+        assert_eq!(ratio.next(4.0), 1.0);
+    }
+
+    #[test]
+    fn test_cross() {
+        let mut cross = Cross::new(Sma::new(1), Constant::new(50.0));
+        let values: Vec<f32> = [40.0, 60.0, 70.0, 50.0, 45.0, 50.0, 55.0].iter().map(|v| cross.next(*v)).collect();
+        assert_eq!(values, vec![0.0, 1.0, 0.0, 0.0, -1.0, 0.0, 1.0]);
+
+        cross.reset();
+        assert_eq!(cross.next(60.0), 0.0);
+        assert_eq!(format!("{}", cross), "CROSS(SMA(1), 50)");
+    }
+
+    #[test]
+    fn test_zscore() {
+        let round = |value: f32| (value * 1000.0).round() / 1000.0;
+        let mut zscore = ZScore::new(4);
+        assert_eq!(zscore.next(2.0), 0.0);
+        assert_eq!(zscore.next(2.0), 0.0);
+        assert_eq!(round(zscore.next(4.0)), 1.414);
+        // window of 2, 4, 4, 6 with mean 4 and deviation sqrt(2)
+        zscore.next(4.0);
+        assert_eq!(round(zscore.next(6.0)), 1.414);
+    }
+
+    #[test]
+    fn test_percentile_rank() {
+        let mut rank = PercentileRank::new(4);
+        let values: Vec<f32> = [5.0, 3.0, 4.0, 8.0, 1.0, 6.0].iter().map(|v| rank.next(*v)).collect();
+        assert_eq!(values, vec![0.0, 0.0, 50.0, 100.0, 0.0, 75.0]);
+        assert_eq!(format!("{}", rank), "PERCENTRANK(4)");
+    }
+}
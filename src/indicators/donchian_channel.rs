@@ -0,0 +1,114 @@
+use crate::candlestick::{High, Low};
+use crate::indicators::traits::{Indicator, Next, Period, Reset};
+use crate::indicators::highest_lowest::{Highest, Lowest};
+use std::fmt;
+
+/// Highest high and lowest low of the last `period` candles, breakouts of the channel signal a new trend
+pub struct DonchianChannel {
+    highest: Highest,
+    lowest: Lowest,
+    current: DonchianChannelOutput,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct DonchianChannelOutput {
+    pub upper: f32,
+    pub middle: f32,
+    pub lower: f32,
+}
+
+impl DonchianChannel {
+    pub fn new(period: usize) -> Self {
+        Self {
+            highest: Highest::new(period),
+            lowest: Lowest::new(period),
+            current: DonchianChannelOutput::default(),
+        }
+    }
+
+    pub fn value(&self) -> DonchianChannelOutput {
+        self.current
+    }
+
+    pub fn reset(&mut self) {
+        self.highest.reset();
+        self.lowest.reset();
+        self.current = DonchianChannelOutput::default();
+    }
+}
+
+impl Period for DonchianChannel {
+    fn period(&self) -> usize {
+        self.highest.period()
+    }
+}
+
+impl Next<f32> for DonchianChannel {
+    type Output = DonchianChannelOutput;
+
+    fn next(&mut self, input: f32) -> Self::Output {
+        let upper = self.highest.next(input);
+        let lower = self.lowest.next(input);
+        self.current = DonchianChannelOutput { upper, middle: (upper + lower) / 2.0, lower };
+        self.current
+    }
+}
+
+impl<T: High + Low> Next<&T> for DonchianChannel {
+    type Output = DonchianChannelOutput;
+
+    fn next(&mut self, input: &T) -> Self::Output {
+        let upper = self.highest.next(input.high());
+        let lower = self.lowest.next(input.low());
+        self.current = DonchianChannelOutput { upper, middle: (upper + lower) / 2.0, lower };
+        self.current
+    }
+}
+
+impl<T: High + Low> Indicator<&T> for DonchianChannel {
+    fn value(&self) -> DonchianChannelOutput {
+        self.current
+    }
+
+    fn is_ready(&self) -> bool {
+        self.highest.is_ready()
+    }
+
+    fn warmup_period(&self) -> usize {
+        self.highest.period()
+    }
+}
+
+impl Reset for DonchianChannel {
+    fn reset(&mut self) {
+        self.reset();
+    }
+}
+
+impl fmt::Display for DonchianChannel {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "DC({})", self.highest.period())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::candlestick::Candlestick;
+
+    #[test]
+    fn test_next() {
+        let mut dc = DonchianChannel::new(2);
+        let output = dc.next(&Candlestick::new().high(10.0).low(8.0));
+        assert_eq!((output.upper, output.middle, output.lower), (10.0, 9.0, 8.0));
+        let output = dc.next(&Candlestick::new().high(12.0).low(9.0));
+        assert_eq!((output.upper, output.middle, output.lower), (12.0, 10.0, 8.0));
+        let output = dc.next(&Candlestick::new().high(11.0).low(10.0));
+        assert_eq!((output.upper, output.middle, output.lower), (12.0, 10.5, 9.0));
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(format!("{}", DonchianChannel::new(20)), "DC(20)");
+    }
+}
@@ -0,0 +1,107 @@
+use crate::candlestick::Close;
+use crate::indicators::traits::{Indicator, MovingAverage, Next, Period, Reset};
+use crate::indicators::exponential_moving_average::ExponentialMovingAverage as Ema;
+use std::fmt;
+
+/// Double exponential moving average, 2 * EMA - EMA(EMA), reduces the lag of a single EMA
+pub struct DoubleExponentialMovingAverage {
+    ema: Ema,
+    ema_of_ema: Ema,
+    current_value: f32,
+    count: usize, // inputs received since the last reset
+}
+
+impl DoubleExponentialMovingAverage {
+    pub fn new(period: usize) -> Self {
+        Self {
+            ema: Ema::new(period),
+            ema_of_ema: Ema::new(period),
+            current_value: 0.0,
+            count: 0,
+        }
+    }
+
+    pub fn value(&self) -> f32 {
+        self.current_value
+    }
+
+    pub fn reset(&mut self) {
+        self.ema.reset();
+        self.ema_of_ema.reset();
+        self.current_value = 0.0;
+        self.count = 0;
+    }
+}
+
+impl Period for DoubleExponentialMovingAverage {
+    fn period(&self) -> usize {
+        self.ema.period()
+    }
+}
+
+impl Next<f32> for DoubleExponentialMovingAverage {
+    type Output = f32;
+
+    fn next(&mut self, input: f32) -> Self::Output {
+        self.count += 1;
+        let ema = self.ema.next(input);
+        self.current_value = 2.0 * ema - self.ema_of_ema.next(ema);
+        self.current_value
+    }
+}
+
+impl<T: Close> Next<&T> for DoubleExponentialMovingAverage {
+    type Output = f32;
+
+    fn next(&mut self, input: &T) -> Self::Output {
+        self.next(input.close())
+    }
+}
+
+impl Indicator<f32> for DoubleExponentialMovingAverage {
+    fn value(&self) -> f32 {
+        self.current_value
+    }
+
+    fn is_ready(&self) -> bool {
+        self.count >= self.warmup_period()
+    }
+
+    fn warmup_period(&self) -> usize {
+        2 * self.ema.period() - 1
+    }
+}
+
+impl MovingAverage for DoubleExponentialMovingAverage {}
+
+impl Reset for DoubleExponentialMovingAverage {
+    fn reset(&mut self) {
+        self.reset();
+    }
+}
+
+impl fmt::Display for DoubleExponentialMovingAverage {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "DEMA({})", self.ema.period())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_next() {
+        // ema of 2.0, 3.5, 2.25 and 4.25
+        let mut dema = DoubleExponentialMovingAverage::new(3);
+        assert_eq!(dema.next(2.0), 2.0);
+        assert_eq!(dema.next(5.0), 4.25);
+        assert_eq!(dema.next(1.0), 2.0);
+        assert_eq!(dema.next(6.25), 5.125);
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(format!("{}", DoubleExponentialMovingAverage::new(9)), "DEMA(9)");
+    }
+}
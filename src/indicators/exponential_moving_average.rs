@@ -1,5 +1,5 @@
 use crate::candlestick::{Candlestick, Close};
-use crate::indicators::traits::Next;
+use crate::indicators::traits::{Indicator, MovingAverage, Next, Period, Reset};
 use std::fmt;
 
 pub struct ExponentialMovingAverage {
@@ -7,6 +7,7 @@ pub struct ExponentialMovingAverage {
     k: f32,
     current: f32,
     is_new: bool,
+    count: usize, // inputs received since the last reset
 }
 
 impl ExponentialMovingAverage {
@@ -16,6 +17,7 @@ impl ExponentialMovingAverage {
             k: 2.0 / (period + 1) as f32,
             current: 0.0,
             is_new: true,
+            count: 0,
         }
     }
 
@@ -30,6 +32,13 @@ impl ExponentialMovingAverage {
     pub fn reset(&mut self) {
         self.current = 0.0;
         self.is_new = true;
+        self.count = 0;
+    }
+}
+
+impl Period for ExponentialMovingAverage {
+    fn period(&self) -> usize {
+        self.period
     }
 }
 
@@ -37,6 +46,7 @@ impl Next<f32> for ExponentialMovingAverage {
     type Output = f32;
 
     fn next(&mut self, close_value: f32) -> Self::Output {
+        self.count += 1;
         if self.is_new {
             self.is_new = false;
             self.current = close_value;
@@ -63,6 +73,28 @@ impl<T: Close> Next<&T> for ExponentialMovingAverage {
     }
 }
 
+impl Indicator<f32> for ExponentialMovingAverage {
+    fn value(&self) -> f32 {
+        self.current
+    }
+
+    fn is_ready(&self) -> bool {
+        self.count >= self.period
+    }
+
+    fn warmup_period(&self) -> usize {
+        self.period
+    }
+}
+
+impl MovingAverage for ExponentialMovingAverage {}
+
+impl Reset for ExponentialMovingAverage {
+    fn reset(&mut self) {
+        self.reset();
+    }
+}
+
 impl fmt::Display for ExponentialMovingAverage {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "EMA({})", self.period)
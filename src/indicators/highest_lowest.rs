@@ -0,0 +1,187 @@
+use crate::candlestick::{High, Low};
+use crate::indicators::rolling::{RollingMax, RollingMin};
+use crate::indicators::traits::{Indicator, Next, Period, Reset};
+use std::fmt;
+
+/// Highest value of the last `period` inputs, of the high prices when given candlesticks, the highest high
+pub struct Highest {
+    period: usize,
+    max: RollingMax,
+    current_value: f32,
+}
+
+impl Highest {
+    pub fn new(period: usize) -> Self {
+        Self {
+            period: period.max(1),
+            max: RollingMax::new(period),
+            current_value: 0.0,
+        }
+    }
+
+    pub fn value(&self) -> f32 {
+        self.current_value
+    }
+
+    pub fn reset(&mut self) {
+        self.max.reset();
+        self.current_value = 0.0;
+    }
+}
+
+impl Period for Highest {
+    fn period(&self) -> usize {
+        self.period
+    }
+}
+
+impl Next<f32> for Highest {
+    type Output = f32;
+
+    fn next(&mut self, input: f32) -> Self::Output {
+        self.current_value = self.max.next(input);
+        self.current_value
+    }
+}
+
+impl<T: High> Next<&T> for Highest {
+    type Output = f32;
+
+    fn next(&mut self, input: &T) -> Self::Output {
+        self.next(input.high())
+    }
+}
+
+impl Indicator<f32> for Highest {
+    fn value(&self) -> f32 {
+        self.current_value
+    }
+
+    fn is_ready(&self) -> bool {
+        self.max.is_ready()
+    }
+
+    fn warmup_period(&self) -> usize {
+        self.period
+    }
+}
+
+impl Reset for Highest {
+    fn reset(&mut self) {
+        self.reset();
+    }
+}
+
+impl fmt::Display for Highest {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "HIGHEST({})", self.period)
+    }
+}
+
+/// Lowest value of the last `period` inputs, of the low prices when given candlesticks, the lowest low
+pub struct Lowest {
+    period: usize,
+    min: RollingMin,
+    current_value: f32,
+}
+
+impl Lowest {
+    pub fn new(period: usize) -> Self {
+        Self {
+            period: period.max(1),
+            min: RollingMin::new(period),
+            current_value: 0.0,
+        }
+    }
+
+    pub fn value(&self) -> f32 {
+        self.current_value
+    }
+
+    pub fn reset(&mut self) {
+        self.min.reset();
+        self.current_value = 0.0;
+    }
+}
+
+impl Period for Lowest {
+    fn period(&self) -> usize {
+        self.period
+    }
+}
+
+impl Next<f32> for Lowest {
+    type Output = f32;
+
+    fn next(&mut self, input: f32) -> Self::Output {
+        self.current_value = self.min.next(input);
+        self.current_value
+    }
+}
+
+impl<T: Low> Next<&T> for Lowest {
+    type Output = f32;
+
+    fn next(&mut self, input: &T) -> Self::Output {
+        self.next(input.low())
+    }
+}
+
+impl Indicator<f32> for Lowest {
+    fn value(&self) -> f32 {
+        self.current_value
+    }
+
+    fn is_ready(&self) -> bool {
+        self.min.is_ready()
+    }
+
+    fn warmup_period(&self) -> usize {
+        self.period
+    }
+}
+
+impl Reset for Lowest {
+    fn reset(&mut self) {
+        self.reset();
+    }
+}
+
+impl fmt::Display for Lowest {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "LOWEST({})", self.period)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::candlestick::Candlestick;
+
+    #[test]
+    fn test_highest() {
+        let mut highest = Highest::new(3);
+        assert_eq!(highest.next(4.0), 4.0);
+        assert_eq!(highest.next(2.0), 4.0);
+        assert_eq!(highest.next(3.0), 4.0);
+        assert_eq!(highest.next(1.0), 3.0);
+        assert_eq!(highest.next(&Candlestick::new().high(5.0).low(0.0)), 5.0);
+    }
+
+    #[test]
+    fn test_lowest() {
+        let mut lowest = Lowest::new(2);
+        assert_eq!(lowest.next(4.0), 4.0);
+        assert_eq!(lowest.next(2.0), 2.0);
+        assert_eq!(lowest.next(3.0), 2.0);
+        assert_eq!(lowest.next(&Candlestick::new().high(5.0).low(4.0)), 3.0);
+        lowest.reset();
+        assert_eq!(lowest.next(9.0), 9.0);
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(format!("{}", Highest::new(5)), "HIGHEST(5)");
+        assert_eq!(format!("{}", Lowest::new(5)), "LOWEST(5)");
+    }
+}
@@ -0,0 +1,124 @@
+use crate::candlestick::Close;
+use crate::indicators::traits::{Indicator, MovingAverage, Next, Period, Reset};
+use crate::indicators::weighted_moving_average::WeightedMovingAverage as Wma;
+use std::fmt;
+
+/// Hull moving average, WMA(2 * WMA(n / 2) - WMA(n)) over sqrt(n) values, follows the price with very little lag
+pub struct HullMovingAverage {
+    period: usize,
+    half_wma: Wma,
+    full_wma: Wma,
+    smoothing_wma: Wma,
+    current_value: f32,
+    count: usize, // inputs received since the last reset
+}
+
+impl HullMovingAverage {
+    pub fn new(period: usize) -> Self {
+        let period = period.max(1);
+        Self {
+            period,
+            half_wma: Wma::new(period / 2),
+            full_wma: Wma::new(period),
+            smoothing_wma: Wma::new((period as f32).sqrt().round() as usize),
+            current_value: 0.0,
+            count: 0,
+        }
+    }
+
+    pub fn value(&self) -> f32 {
+        self.current_value
+    }
+
+    pub fn reset(&mut self) {
+        self.half_wma.reset();
+        self.full_wma.reset();
+        self.smoothing_wma.reset();
+        self.current_value = 0.0;
+        self.count = 0;
+    }
+}
+
+impl Period for HullMovingAverage {
+    fn period(&self) -> usize {
+        self.period
+    }
+}
+
+impl Next<f32> for HullMovingAverage {
+    type Output = f32;
+
+    fn next(&mut self, input: f32) -> Self::Output {
+        self.count += 1;
+        let difference = 2.0 * self.half_wma.next(input) - self.full_wma.next(input);
+        self.current_value = self.smoothing_wma.next(difference);
+        self.current_value
+    }
+}
+
+impl<T: Close> Next<&T> for HullMovingAverage {
+    type Output = f32;
+
+    fn next(&mut self, input: &T) -> Self::Output {
+        self.next(input.close())
+    }
+}
+
+impl Indicator<f32> for HullMovingAverage {
+    fn value(&self) -> f32 {
+        self.current_value
+    }
+
+    fn is_ready(&self) -> bool {
+        self.count >= self.warmup_period()
+    }
+
+    fn warmup_period(&self) -> usize {
+        // the smoothing average starts once the full average is ready
+        self.period + self.smoothing_wma.period() - 1
+    }
+}
+
+impl MovingAverage for HullMovingAverage {}
+
+impl Reset for HullMovingAverage {
+    fn reset(&mut self) {
+        self.reset();
+    }
+}
+
+impl fmt::Display for HullMovingAverage {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "HMA({})", self.period)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_next() {
+        let round = |value: f32| (value * 100.0).round() / 100.0;
+        let mut hma = HullMovingAverage::new(4);
+        assert_eq!(round(hma.next(2.0)), 2.0);
+        assert_eq!(round(hma.next(5.0)), 3.33);
+        assert_eq!(round(hma.next(1.0)), 2.78);
+        assert_eq!(round(hma.next(8.0)), 5.14);
+    }
+
+    #[test]
+    fn test_follows_a_linear_trend_without_lag() {
+        let mut hma = HullMovingAverage::new(9);
+        let mut value = 0.0;
+        for i in 0..20 {
+            value = hma.next(i as f32);
+        }
+        assert!((value - 19.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(format!("{}", HullMovingAverage::new(9)), "HMA(9)");
+    }
+}
@@ -0,0 +1,135 @@
+use crate::candlestick::{Close, High, Low};
+use crate::indicators::traits::{Indicator, Next, Period, Reset};
+use crate::indicators::highest_lowest::{Highest, Lowest};
+use std::fmt;
+
+/// Ichimoku Kinko Hyo lines computed on the current candle. The leading spans are usually plotted
+/// `base_period` candles ahead and the lagging span `base_period` candles behind, that shift is left to the caller
+pub struct IchimokuCloud {
+    conversion_highest: Highest,
+    conversion_lowest: Lowest,
+    base_highest: Highest,
+    base_lowest: Lowest,
+    span_b_highest: Highest,
+    span_b_lowest: Lowest,
+    current: IchimokuCloudOutput,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct IchimokuCloudOutput {
+    pub conversion_line: f32, // tenkan-sen
+    pub base_line: f32, // kijun-sen
+    pub leading_span_a: f32, // senkou span A
+    pub leading_span_b: f32, // senkou span B
+    pub lagging_span: f32, // chikou span
+}
+
+impl IchimokuCloud {
+    pub fn new(conversion_period: usize, base_period: usize, span_b_period: usize) -> Self {
+        Self {
+            conversion_highest: Highest::new(conversion_period),
+            conversion_lowest: Lowest::new(conversion_period),
+            base_highest: Highest::new(base_period),
+            base_lowest: Lowest::new(base_period),
+            span_b_highest: Highest::new(span_b_period),
+            span_b_lowest: Lowest::new(span_b_period),
+            current: IchimokuCloudOutput::default(),
+        }
+    }
+
+    pub fn value(&self) -> IchimokuCloudOutput {
+        self.current
+    }
+
+    pub fn reset(&mut self) {
+        self.conversion_highest.reset();
+        self.conversion_lowest.reset();
+        self.base_highest.reset();
+        self.base_lowest.reset();
+        self.span_b_highest.reset();
+        self.span_b_lowest.reset();
+        self.current = IchimokuCloudOutput::default();
+    }
+}
+
+// the usual periods of 9, 26 and 52 candles
+impl Default for IchimokuCloud {
+    fn default() -> Self {
+        Self::new(9, 26, 52)
+    }
+}
+
+impl Period for IchimokuCloud {
+    fn period(&self) -> usize {
+        self.base_highest.period()
+    }
+}
+
+impl<T: High + Low + Close> Next<&T> for IchimokuCloud {
+    type Output = IchimokuCloudOutput;
+
+    fn next(&mut self, input: &T) -> Self::Output {
+        let conversion_line = (self.conversion_highest.next(input.high()) + self.conversion_lowest.next(input.low())) / 2.0;
+        let base_line = (self.base_highest.next(input.high()) + self.base_lowest.next(input.low())) / 2.0;
+        self.current = IchimokuCloudOutput {
+            conversion_line,
+            base_line,
+            leading_span_a: (conversion_line + base_line) / 2.0,
+            leading_span_b: (self.span_b_highest.next(input.high()) + self.span_b_lowest.next(input.low())) / 2.0,
+            lagging_span: input.close(),
+        };
+        self.current
+    }
+}
+
+impl<T: High + Low + Close> Indicator<&T> for IchimokuCloud {
+    fn value(&self) -> IchimokuCloudOutput {
+        self.current
+    }
+
+    fn is_ready(&self) -> bool {
+        self.conversion_highest.is_ready() && self.base_highest.is_ready() && self.span_b_highest.is_ready()
+    }
+
+    fn warmup_period(&self) -> usize {
+        self.conversion_highest.period().max(self.base_highest.period()).max(self.span_b_highest.period())
+    }
+}
+
+impl Reset for IchimokuCloud {
+    fn reset(&mut self) {
+        self.reset();
+    }
+}
+
+impl fmt::Display for IchimokuCloud {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "ICHIMOKU({}, {}, {})", self.conversion_highest.period(), self.base_highest.period(), self.span_b_highest.period())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::candlestick::Candlestick;
+
+    #[test]
+    fn test_next() {
+        let mut ichimoku = IchimokuCloud::new(1, 2, 3);
+        ichimoku.next(&Candlestick::new().high(10.0).low(8.0).close(9.0));
+        ichimoku.next(&Candlestick::new().high(14.0).low(12.0).close(13.0));
+        let output = ichimoku.next(&Candlestick::new().high(11.0).low(10.0).close(10.5));
+        assert_eq!(output, IchimokuCloudOutput {
+            conversion_line: 10.5,
+            base_line: 12.0,
+            leading_span_a: 11.25,
+            leading_span_b: 11.0,
+            lagging_span: 10.5,
+        });
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(format!("{}", IchimokuCloud::default()), "ICHIMOKU(9, 26, 52)");
+    }
+}
@@ -0,0 +1,130 @@
+use crate::candlestick::Close;
+use crate::indicators::traits::{Indicator, MovingAverage, Next, Period, Reset};
+use std::collections::VecDeque;
+use std::fmt;
+
+/// Kaufman adaptive moving average, moves like a fast EMA when the price trends and like a slow EMA when it is noisy.
+/// The efficiency ratio of the last `period` changes picks the smoothing between the fast and slow periods
+pub struct KaufmanAdaptiveMovingAverage {
+    period: usize,
+    fast_constant: f32,
+    slow_constant: f32,
+    history: VecDeque<f32>, // last `period + 1` values
+    current: Option<f32>,
+}
+
+impl KaufmanAdaptiveMovingAverage {
+    /// the usual fast and slow periods are 2 and 30
+    pub fn new(period: usize, fast_period: usize, slow_period: usize) -> Self {
+        Self {
+            period: period.max(1),
+            fast_constant: 2.0 / (fast_period + 1) as f32,
+            slow_constant: 2.0 / (slow_period + 1) as f32,
+            history: VecDeque::with_capacity(period.max(1) + 1),
+            current: None,
+        }
+    }
+
+    pub fn value(&self) -> f32 {
+        self.current.unwrap_or(0.0)
+    }
+
+    pub fn reset(&mut self) {
+        self.history.clear();
+        self.current = None;
+    }
+
+    // net change divided by the sum of the absolute changes, 1.0 on a straight line
+    fn efficiency_ratio(&self) -> f32 {
+        let change = (self.history[self.history.len() - 1] - self.history[0]).abs();
+        let volatility: f32 = self.history.iter().zip(self.history.iter().skip(1)).map(|(a, b)| (b - a).abs()).sum();
+        if volatility > 0.0 { change / volatility } else { 0.0 }
+    }
+}
+
+impl Period for KaufmanAdaptiveMovingAverage {
+    fn period(&self) -> usize {
+        self.period
+    }
+}
+
+impl Next<f32> for KaufmanAdaptiveMovingAverage {
+    type Output = f32;
+
+    fn next(&mut self, input: f32) -> Self::Output {
+        if self.history.len() > self.period {
+            self.history.pop_front();
+        }
+        self.history.push_back(input);
+
+        let value = match self.current {
+            Some(current) => {
+                let smoothing = (self.efficiency_ratio() * (self.fast_constant - self.slow_constant) + self.slow_constant).powi(2);
+                current + smoothing * (input - current)
+            }
+            None => input,
+        };
+        self.current = Some(value);
+        value
+    }
+}
+
+impl<T: Close> Next<&T> for KaufmanAdaptiveMovingAverage {
+    type Output = f32;
+
+    fn next(&mut self, input: &T) -> Self::Output {
+        self.next(input.close())
+    }
+}
+
+impl Indicator<f32> for KaufmanAdaptiveMovingAverage {
+    fn value(&self) -> f32 {
+        self.value()
+    }
+
+    fn is_ready(&self) -> bool {
+        self.history.len() > self.period
+    }
+
+    fn warmup_period(&self) -> usize {
+        // the efficiency ratio needs `period` changes
+        self.period + 1
+    }
+}
+
+impl MovingAverage for KaufmanAdaptiveMovingAverage {}
+
+impl Reset for KaufmanAdaptiveMovingAverage {
+    fn reset(&mut self) {
+        self.reset();
+    }
+}
+
+impl fmt::Display for KaufmanAdaptiveMovingAverage {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let fast_period = (2.0 / self.fast_constant - 1.0).round();
+        let slow_period = (2.0 / self.slow_constant - 1.0).round();
+        write!(f, "KAMA({}, {}, {})", self.period, fast_period, slow_period)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_next() {
+        let mut kama = KaufmanAdaptiveMovingAverage::new(2, 1, 3);
+        assert_eq!(kama.next(2.0), 2.0);
+        // a straight move uses the fast constant of 1.0
+        assert_eq!(kama.next(4.0), 4.0);
+        // a full reversal has an efficiency ratio of 0.0 and uses the slow constant of 0.5
+        assert_eq!(kama.next(2.0), 3.5);
+        assert_eq!((kama.next(5.0) * 100.0).round() / 100.0, 4.04);
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(format!("{}", KaufmanAdaptiveMovingAverage::new(10, 2, 30)), "KAMA(10, 2, 30)");
+    }
+}
@@ -0,0 +1,110 @@
+use crate::candlestick::{Close, High, Low};
+use crate::indicators::traits::{Indicator, Next, Period, Reset};
+use crate::indicators::exponential_moving_average::ExponentialMovingAverage as Ema;
+use crate::indicators::average_true_range::AverageTrueRange as ATR;
+use std::fmt;
+
+/// Exponential moving average of the close with bands `multiplier` average true ranges above and below it
+pub struct KeltnerChannel {
+    ema: Ema,
+    atr: ATR,
+    multiplier: f32,
+    current: KeltnerChannelOutput,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct KeltnerChannelOutput {
+    pub average: f32,
+    pub upper: f32,
+    pub lower: f32,
+}
+
+impl KeltnerChannel {
+    pub fn new(period: usize, atr_period: usize, multiplier: f32) -> Self {
+        Self {
+            ema: Ema::new(period),
+            atr: ATR::new(atr_period),
+            multiplier,
+            current: KeltnerChannelOutput::default(),
+        }
+    }
+
+    pub fn value(&self) -> KeltnerChannelOutput {
+        self.current
+    }
+
+    pub fn reset(&mut self) {
+        self.ema.reset();
+        self.atr.reset();
+        self.current = KeltnerChannelOutput::default();
+    }
+}
+
+impl Period for KeltnerChannel {
+    fn period(&self) -> usize {
+        self.ema.period()
+    }
+}
+
+impl<T: High + Low + Close> Next<&T> for KeltnerChannel {
+    type Output = KeltnerChannelOutput;
+
+    fn next(&mut self, input: &T) -> Self::Output {
+        let average = self.ema.next(input.close());
+        let distance = self.atr.next(input) * self.multiplier;
+        self.current = KeltnerChannelOutput {
+            average,
+            upper: average + distance,
+            lower: average - distance,
+        };
+        self.current
+    }
+}
+
+impl<T: High + Low + Close> Indicator<&T> for KeltnerChannel {
+    fn value(&self) -> KeltnerChannelOutput {
+        self.current
+    }
+
+    fn is_ready(&self) -> bool {
+        self.ema.is_ready() && Indicator::<&T>::is_ready(&self.atr)
+    }
+
+    fn warmup_period(&self) -> usize {
+        self.ema.period().max(self.atr.period())
+    }
+}
+
+impl Reset for KeltnerChannel {
+    fn reset(&mut self) {
+        self.reset();
+    }
+}
+
+impl fmt::Display for KeltnerChannel {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "KC({}, {}, {})", self.ema.period(), self.atr.period(), self.multiplier)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::candlestick::Candlestick;
+
+    #[test]
+    fn test_next() {
+        let mut kc = KeltnerChannel::new(3, 3, 2.0);
+        let output = kc.next(&Candlestick::new().high(10.0).low(7.5).close(9.0));
+        assert_eq!((output.average, output.upper, output.lower), (9.0, 14.0, 4.0));
+        let output = kc.next(&Candlestick::new().high(11.0).low(9.0).close(9.5));
+        assert_eq!((output.average, output.upper, output.lower), (9.25, 13.75, 4.75));
+        let output = kc.next(&Candlestick::new().high(9.0).low(5.0).close(8.0));
+        assert_eq!((output.average, output.upper, output.lower), (8.625, 15.375, 1.875));
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(format!("{}", KeltnerChannel::new(20, 10, 2.0)), "KC(20, 10, 2)");
+    }
+}
@@ -4,4 +4,32 @@ pub mod relative_strength_index;
 pub mod simple_moving_average;
 pub mod average_true_range;
 pub mod true_range;
-pub mod traits;
\ No newline at end of file
+pub mod highest_lowest;
+pub mod bollinger_bands;
+pub mod stochastic_oscillator;
+pub mod average_directional_index;
+pub mod commodity_channel_index;
+pub mod williams_r;
+pub mod on_balance_volume;
+pub mod volume_weighted_average_price;
+pub mod ichimoku_cloud;
+pub mod supertrend;
+pub mod keltner_channel;
+pub mod donchian_channel;
+pub mod weighted_moving_average;
+pub mod hull_moving_average;
+pub mod double_exponential_moving_average;
+pub mod triple_exponential_moving_average;
+pub mod kaufman_adaptive_moving_average;
+pub mod wilder_moving_average;
+pub mod zero_lag_exponential_moving_average;
+pub mod moving_average;
+pub mod candlestick_patterns;
+pub mod price_source;
+pub mod batch;
+pub mod cache;
+pub mod composition;
+pub mod rolling;
+pub mod volume;
+pub mod regime;
+pub mod traits;
@@ -0,0 +1,98 @@
+use crate::indicators::double_exponential_moving_average::DoubleExponentialMovingAverage;
+use crate::indicators::exponential_moving_average::ExponentialMovingAverage;
+use crate::indicators::hull_moving_average::HullMovingAverage;
+use crate::indicators::kaufman_adaptive_moving_average::KaufmanAdaptiveMovingAverage;
+use crate::indicators::simple_moving_average::SimpleMovingAverage;
+use crate::indicators::traits::MovingAverage;
+use crate::indicators::triple_exponential_moving_average::TripleExponentialMovingAverage;
+use crate::indicators::weighted_moving_average::WeightedMovingAverage;
+use crate::indicators::wilder_moving_average::WilderMovingAverage;
+use crate::indicators::zero_lag_exponential_moving_average::ZeroLagExponentialMovingAverage;
+
+/// Kind of moving average, so a strategy can pick the average it uses from a gene
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MovingAverageType {
+    Sma,
+    Ema,
+    Wma,
+    Hma,
+    Dema,
+    Tema,
+    Kama,
+    Rma,
+    Zlema,
+}
+
+impl MovingAverageType {
+    pub const ALL: [MovingAverageType; 9] = [
+        MovingAverageType::Sma,
+        MovingAverageType::Ema,
+        MovingAverageType::Wma,
+        MovingAverageType::Hma,
+        MovingAverageType::Dema,
+        MovingAverageType::Tema,
+        MovingAverageType::Kama,
+        MovingAverageType::Rma,
+        MovingAverageType::Zlema,
+    ];
+
+    /// names of the types in the order of `ALL`, the options of the genes picking an average
+    pub const NAMES: [&'static str; 9] = ["sma", "ema", "wma", "hma", "dema", "tema", "kama", "rma", "zlema"];
+
+    pub fn build(&self, period: usize) -> Box<dyn MovingAverage> {
+        match self {
+            MovingAverageType::Sma => Box::new(SimpleMovingAverage::new(period)),
+            MovingAverageType::Ema => Box::new(ExponentialMovingAverage::new(period)),
+            MovingAverageType::Wma => Box::new(WeightedMovingAverage::new(period)),
+            MovingAverageType::Hma => Box::new(HullMovingAverage::new(period)),
+            MovingAverageType::Dema => Box::new(DoubleExponentialMovingAverage::new(period)),
+            MovingAverageType::Tema => Box::new(TripleExponentialMovingAverage::new(period)),
+            MovingAverageType::Kama => Box::new(KaufmanAdaptiveMovingAverage::new(period, 2, 30)),
+            MovingAverageType::Rma => Box::new(WilderMovingAverage::new(period)),
+            MovingAverageType::Zlema => Box::new(ZeroLagExponentialMovingAverage::new(period)),
+        }
+    }
+}
+
+/// Average used to smooth the gains and losses of the RSI and the true ranges of the ATR
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Smoothing {
+    /// exponential average starting on the first value, the smoothing the strategies were optimized with
+    Ema,
+    /// average of Welles Wilder seeded with a simple average, the reference definition matching TA-Lib and TradingView
+    Wilder,
+}
+
+impl Smoothing {
+    pub fn build(&self, period: usize) -> Box<dyn MovingAverage> {
+        match self {
+            Smoothing::Ema => Box::new(ExponentialMovingAverage::new(period)),
+            Smoothing::Wilder => Box::new(WilderMovingAverage::new(period).seeded()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_names() {
+        for (kind, name) in MovingAverageType::ALL.iter().zip(MovingAverageType::NAMES) {
+            assert!(kind.build(5).to_string().to_lowercase().starts_with(&format!("{}(", name)), "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_build() {
+        let names: Vec<String> = MovingAverageType::ALL.iter().map(|kind| kind.build(5).to_string()).collect();
+        assert_eq!(names[0], "SMA(5)");
+        assert_eq!(names[1], "EMA(5)");
+        assert_eq!(names[6], "KAMA(5, 2, 30)");
+        for kind in MovingAverageType::ALL {
+            let mut average = kind.build(3);
+            average.next(4.0);
+            assert_eq!(average.value(), 4.0, "{}", average);
+        }
+    }
+}
@@ -1,5 +1,5 @@
 use crate::candlestick::{Candlestick, Close};
-use crate::indicators::traits::Next;
+use crate::indicators::traits::{Indicator, Next, Reset};
 use crate::indicators::exponential_moving_average::ExponentialMovingAverage as Ema;
 use std::fmt;
 
@@ -7,7 +7,8 @@ pub struct MovingAverageConvergenceDivergence {
     fast_ema: Ema,
     slow_ema: Ema,
     signal_ema: Ema,
-    last_signal: f32,
+    current: MovingAverageConvergenceDivergenceOutput,
+    count: usize, // inputs received since the last reset
 }
 
 impl MovingAverageConvergenceDivergence {
@@ -16,7 +17,8 @@ impl MovingAverageConvergenceDivergence {
             fast_ema: Ema::new(fast_period),
             slow_ema: Ema::new(slow_period),
             signal_ema: Ema::new(signal_period),
-            last_signal: 0.0,
+            current: MovingAverageConvergenceDivergenceOutput::default(),
+            count: 0,
         }
     }
 
@@ -24,15 +26,16 @@ impl MovingAverageConvergenceDivergence {
         self.fast_ema.reset();
         self.slow_ema.reset();
         self.signal_ema.reset();
-        self.last_signal = 0.0;
+        self.current = MovingAverageConvergenceDivergenceOutput::default();
+        self.count = 0;
     }
 
     pub fn value(&self) -> f32 {
-        self.last_signal
+        self.current.signal
     }
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Default)]
 pub struct MovingAverageConvergenceDivergenceOutput {
     pub macd: f32,
     pub signal: f32,
@@ -56,13 +59,13 @@ impl Next<f32> for MovingAverageConvergenceDivergence {
         let signal = self.signal_ema.next(macd);
         let histogram = macd - signal;
 
-        self.last_signal = signal;
-
-        MovingAverageConvergenceDivergenceOutput {
+        self.count += 1;
+        self.current = MovingAverageConvergenceDivergenceOutput {
             macd,
             signal,
             histogram,
-        }
+        };
+        self.current.clone()
     }
 }
 
@@ -82,6 +85,27 @@ impl<T: Close> Next<&T> for MovingAverageConvergenceDivergence {
     }
 }
 
+impl Indicator<f32> for MovingAverageConvergenceDivergence {
+    fn value(&self) -> MovingAverageConvergenceDivergenceOutput {
+        self.current.clone()
+    }
+
+    fn is_ready(&self) -> bool {
+        self.count >= self.warmup_period()
+    }
+
+    fn warmup_period(&self) -> usize {
+        // the signal average starts once the slow average is ready
+        self.fast_ema.period().max(self.slow_ema.period()) + self.signal_ema.period() - 1
+    }
+}
+
+impl Reset for MovingAverageConvergenceDivergence {
+    fn reset(&mut self) {
+        self.reset();
+    }
+}
+
 impl fmt::Display for MovingAverageConvergenceDivergence {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(
@@ -120,6 +144,19 @@ mod tests {
         assert_eq!(round(macd.next(6.5).into()), (0.94, 0.87, 0.07));
     }
     
+    #[test]
+    fn test_is_ready() {
+        let mut macd = Macd::new(3, 6, 4);
+        assert_eq!(macd.warmup_period(), 9);
+        for price in 0..8 {
+            macd.next(price as f32);
+        }
+        assert!(!macd.is_ready());
+        let output = macd.next(8.0);
+        assert!(macd.is_ready());
+        assert_eq!(Indicator::value(&macd), output);
+    }
+
     #[test]
     fn test_display() {
         let indicator = Macd::new(13, 30, 10);
@@ -0,0 +1,98 @@
+use crate::candlestick::{Close, Volume};
+use crate::indicators::traits::{Indicator, Next, Reset};
+use std::fmt;
+
+/// Running total of the volume, added on candles that close higher and subtracted on candles that close lower
+pub struct OnBalanceVolume {
+    prev_close: Option<f32>,
+    current_value: f32,
+}
+
+impl OnBalanceVolume {
+    pub fn new() -> Self {
+        Self {
+            prev_close: None,
+            current_value: 0.0,
+        }
+    }
+
+    pub fn value(&self) -> f32 {
+        self.current_value
+    }
+
+    pub fn reset(&mut self) {
+        self.prev_close = None;
+        self.current_value = 0.0;
+    }
+}
+
+impl Default for OnBalanceVolume {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// the first candle is only the reference for the next close
+impl<T: Close + Volume> Next<&T> for OnBalanceVolume {
+    type Output = f32;
+
+    fn next(&mut self, candle: &T) -> Self::Output {
+        if let Some(prev_close) = self.prev_close {
+            if candle.close() > prev_close {
+                self.current_value += candle.volume();
+            } else if candle.close() < prev_close {
+                self.current_value -= candle.volume();
+            }
+        }
+        self.prev_close = Some(candle.close());
+        self.current_value
+    }
+}
+
+impl<T: Close + Volume> Indicator<&T> for OnBalanceVolume {
+    fn value(&self) -> f32 {
+        self.current_value
+    }
+
+    fn is_ready(&self) -> bool {
+        self.prev_close.is_some()
+    }
+
+    fn warmup_period(&self) -> usize {
+        1
+    }
+}
+
+impl Reset for OnBalanceVolume {
+    fn reset(&mut self) {
+        self.reset();
+    }
+}
+
+impl fmt::Display for OnBalanceVolume {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "OBV")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::candlestick::Candlestick;
+
+    #[test]
+    fn test_next() {
+        let mut obv = OnBalanceVolume::new();
+        assert_eq!(obv.next(&Candlestick::new().close(10.0).volume(100.0)), 0.0);
+        assert_eq!(obv.next(&Candlestick::new().close(11.0).volume(50.0)), 50.0);
+        assert_eq!(obv.next(&Candlestick::new().close(11.0).volume(30.0)), 50.0);
+        assert_eq!(obv.next(&Candlestick::new().close(9.0).volume(80.0)), -30.0);
+        obv.reset();
+        assert_eq!(obv.next(&Candlestick::new().close(9.0).volume(80.0)), 0.0);
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(format!("{}", OnBalanceVolume::new()), "OBV");
+    }
+}
@@ -0,0 +1,158 @@
+use crate::candlestick::{Close, High, Low, Open, Volume};
+use crate::indicators::traits::{Indicator, Next, Period, Reset};
+use std::fmt;
+
+/// Value of a candle an indicator is computed on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PriceSource {
+    Open,
+    High,
+    Low,
+    Close,
+    /// median price, (high + low) / 2
+    Hl2,
+    /// typical price, (high + low + close) / 3
+    Hlc3,
+    /// average price, (open + high + low + close) / 4
+    Ohlc4,
+    Volume,
+}
+
+impl PriceSource {
+    pub const ALL: [PriceSource; 8] = [
+        PriceSource::Open,
+        PriceSource::High,
+        PriceSource::Low,
+        PriceSource::Close,
+        PriceSource::Hl2,
+        PriceSource::Hlc3,
+        PriceSource::Ohlc4,
+        PriceSource::Volume,
+    ];
+
+    /// names of the sources in the order of `ALL`, the options of the genes picking a source
+    pub const NAMES: [&'static str; 8] = ["open", "high", "low", "close", "hl2", "hlc3", "ohlc4", "volume"];
+
+    pub fn of<T: Open + High + Low + Close + Volume>(&self, candle: &T) -> f32 {
+        match self {
+            PriceSource::Open => candle.open(),
+            PriceSource::High => candle.high(),
+            PriceSource::Low => candle.low(),
+            PriceSource::Close => candle.close(),
+            PriceSource::Hl2 => (candle.high() + candle.low()) / 2.0,
+            PriceSource::Hlc3 => (candle.high() + candle.low() + candle.close()) / 3.0,
+            PriceSource::Ohlc4 => (candle.open() + candle.high() + candle.low() + candle.close()) / 4.0,
+            PriceSource::Volume => candle.volume(),
+        }
+    }
+}
+
+impl fmt::Display for PriceSource {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            PriceSource::Open => "open",
+            PriceSource::High => "high",
+            PriceSource::Low => "low",
+            PriceSource::Close => "close",
+            PriceSource::Hl2 => "hl2",
+            PriceSource::Hlc3 => "hlc3",
+            PriceSource::Ohlc4 => "ohlc4",
+            PriceSource::Volume => "volume",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Feeds an indicator that takes a `f32` with the given source of every candle,
+/// e.g. `Sourced::new(SimpleMovingAverage::new(20), PriceSource::Hlc3)` is the moving average of the typical price
+pub struct Sourced<I> {
+    indicator: I,
+    source: PriceSource,
+}
+
+impl<I> Sourced<I> {
+    pub fn new(indicator: I, source: PriceSource) -> Self {
+        Self { indicator, source }
+    }
+
+    pub fn source(&self) -> PriceSource {
+        self.source
+    }
+
+    pub fn indicator(&self) -> &I {
+        &self.indicator
+    }
+
+    pub fn indicator_mut(&mut self) -> &mut I {
+        &mut self.indicator
+    }
+}
+
+impl<I: Period> Period for Sourced<I> {
+    fn period(&self) -> usize {
+        self.indicator.period()
+    }
+}
+
+impl<I: Next<f32>, T: Open + High + Low + Close + Volume> Next<&T> for Sourced<I> {
+    type Output = I::Output;
+
+    fn next(&mut self, input: &T) -> Self::Output {
+        self.indicator.next(self.source.of(input))
+    }
+}
+
+impl<I: Indicator<f32>, T: Open + High + Low + Close + Volume> Indicator<&T> for Sourced<I> {
+    fn value(&self) -> I::Output {
+        self.indicator.value()
+    }
+
+    fn is_ready(&self) -> bool {
+        self.indicator.is_ready()
+    }
+
+    fn warmup_period(&self) -> usize {
+        self.indicator.warmup_period()
+    }
+}
+
+impl<I: Reset> Reset for Sourced<I> {
+    fn reset(&mut self) {
+        self.indicator.reset();
+    }
+}
+
+impl<I: fmt::Display> fmt::Display for Sourced<I> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}[{}]", self.indicator, self.source)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::candlestick::Candlestick;
+    use crate::indicators::simple_moving_average::SimpleMovingAverage as Sma;
+
+    #[test]
+    fn test_of() {
+        let candle = Candlestick::new().open(4.0).high(12.0).low(2.0).close(10.0).volume(100.0);
+        let values: Vec<f32> = PriceSource::ALL.iter().map(|source| source.of(&candle)).collect();
+        assert_eq!(values, vec![4.0, 12.0, 2.0, 10.0, 7.0, 8.0, 7.0, 100.0]);
+    }
+
+    #[test]
+    fn test_names() {
+        let names: Vec<String> = PriceSource::ALL.iter().map(PriceSource::to_string).collect();
+        assert_eq!(names, PriceSource::NAMES);
+    }
+
+    #[test]
+    fn test_sourced() {
+        let mut sma = Sourced::new(Sma::new(2), PriceSource::Hl2);
+        assert_eq!(sma.next(&Candlestick::new().high(4.0).low(2.0).close(2.0)), 3.0);
+        assert_eq!(sma.next(&Candlestick::new().high(10.0).low(8.0).close(8.0)), 6.0);
+        assert_eq!(sma.period(), 2);
+        assert_eq!(format!("{}", sma), "SMA(2)[hl2]");
+    }
+}
@@ -0,0 +1,316 @@
+use crate::candlestick::{Close, High, Low};
+use crate::indicators::average_directional_index::AverageDirectionalIndex as Adx;
+use crate::indicators::average_true_range::AverageTrueRange as Atr;
+use crate::indicators::composition::PercentileRank;
+use crate::indicators::traits::{Indicator, Next, Period, Reset};
+use std::fmt;
+
+/// State of the market a candle belongs to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Regime {
+    /// the price moves in one direction with a strong trend
+    Trending,
+    /// the price moves sideways without a trend
+    #[default]
+    Ranging,
+    /// the volatility is unusually high, whatever the trend
+    Volatile,
+}
+
+impl Regime {
+    pub const ALL: [Regime; 3] = [Regime::Trending, Regime::Ranging, Regime::Volatile];
+
+    /// position of the regime in `ALL`
+    pub fn index(&self) -> usize {
+        match self {
+            Regime::Trending => 0,
+            Regime::Ranging => 1,
+            Regime::Volatile => 2,
+        }
+    }
+}
+
+impl fmt::Display for Regime {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Regime::Trending => "trending",
+            Regime::Ranging => "ranging",
+            Regime::Volatile => "volatile",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Labels every candle with a `Regime`. A candle is volatile when the atr relative to the price is in the highest
+/// percentiles of the last `lookback` candles (or when the hidden markov model is in its volatile state),
+/// otherwise it is trending when the adx shows a strong trend and ranging when it doesn't
+pub struct RegimeClassifier {
+    atr: Atr,
+    volatility_rank: PercentileRank,
+    adx: Adx,
+    hidden_markov: Option<HiddenMarkovModel>,
+    volatility_threshold: f32,
+    trend_threshold: f32,
+    previous_close: Option<f32>,
+    current: Regime,
+}
+
+impl RegimeClassifier {
+    /// `period` of the atr and adx, compared with the volatility of the last `lookback` candles
+    pub fn new(period: usize, lookback: usize) -> Self {
+        Self {
+            atr: Atr::new(period),
+            volatility_rank: PercentileRank::new(lookback),
+            adx: Adx::new(period),
+            hidden_markov: None,
+            volatility_threshold: 80.0,
+            trend_threshold: 25.0,
+            previous_close: None,
+            current: Regime::default(),
+        }
+    }
+
+    /// percentile (0 to 100) of the relative atr from which the market is volatile, 80 by default
+    pub fn volatility_threshold(mut self, percentile: f32) -> Self {
+        self.volatility_threshold = percentile;
+        self
+    }
+
+    /// adx from which the market is trending, 25 by default
+    pub fn trend_threshold(mut self, adx: f32) -> Self {
+        self.trend_threshold = adx;
+        self
+    }
+
+    /// detects volatile markets with the model, fed with the log returns of the closes, instead of the atr percentiles
+    pub fn hidden_markov(mut self, model: HiddenMarkovModel) -> Self {
+        self.hidden_markov = Some(model);
+        self
+    }
+
+    pub fn value(&self) -> Regime {
+        self.current
+    }
+
+    pub fn reset(&mut self) {
+        self.atr.reset();
+        self.volatility_rank.reset();
+        self.adx.reset();
+        if let Some(model) = self.hidden_markov.as_mut() {
+            model.reset();
+        }
+        self.previous_close = None;
+        self.current = Regime::default();
+    }
+}
+
+impl Default for RegimeClassifier {
+    fn default() -> Self {
+        Self::new(14, 100)
+    }
+}
+
+impl<T: High + Low + Close> Next<&T> for RegimeClassifier {
+    type Output = Regime;
+
+    fn next(&mut self, input: &T) -> Self::Output {
+        let atr = self.atr.next(input);
+        let relative_atr = if input.close() != 0.0 { atr / input.close() } else { 0.0 };
+        let volatility = self.volatility_rank.next(relative_atr);
+        let adx = self.adx.next(input).adx;
+
+        let volatile = match self.hidden_markov.as_mut() {
+            Some(model) => {
+                let log_return = match self.previous_close {
+                    Some(previous) if previous > 0.0 && input.close() > 0.0 => (input.close() / previous).ln(),
+                    _ => 0.0,
+                };
+                model.next(log_return) > 0.5
+            }
+            None => volatility >= self.volatility_threshold,
+        };
+        self.previous_close = Some(input.close());
+
+        self.current = if volatile {
+            Regime::Volatile
+        } else if adx >= self.trend_threshold {
+            Regime::Trending
+        } else {
+            Regime::Ranging
+        };
+        self.current
+    }
+}
+
+impl<T: High + Low + Close> Indicator<&T> for RegimeClassifier {
+    fn value(&self) -> Regime {
+        self.current
+    }
+
+    fn is_ready(&self) -> bool {
+        Indicator::<&T>::is_ready(&self.adx) && self.volatility_rank.is_ready()
+    }
+
+    fn warmup_period(&self) -> usize {
+        // the percentile rank starts with the first atr
+        Indicator::<&T>::warmup_period(&self.adx).max(self.volatility_rank.warmup_period())
+    }
+}
+
+impl Reset for RegimeClassifier {
+    fn reset(&mut self) {
+        self.reset();
+    }
+}
+
+impl fmt::Display for RegimeClassifier {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "REGIME({}, {})", self.adx.period(), self.volatility_rank.period())
+    }
+}
+
+/// Hidden markov model with a calm and a volatile state, both emitting returns from a normal distribution centered on 0.0
+/// with their own standard deviation. Returns the probability of being in the volatile state given the returns received
+pub struct HiddenMarkovModel {
+    deviations: [f32; 2], // of the calm and the volatile state
+    stay_probability: f32,
+    probabilities: [f32; 2],
+    received: bool,
+}
+
+impl HiddenMarkovModel {
+    /// standard deviations of the returns of the calm and the volatile state, e.g. 0.005 and 0.02 for 0.5% and 2% moves
+    pub fn new(calm_deviation: f32, volatile_deviation: f32) -> Self {
+        Self {
+            deviations: [calm_deviation.max(f32::EPSILON), volatile_deviation.max(f32::EPSILON)],
+            stay_probability: 0.95,
+            probabilities: [0.5, 0.5],
+            received: false,
+        }
+    }
+
+    /// probability of staying in the same state on the next return, 0.95 by default. Higher values change state less often
+    pub fn stay_probability(mut self, probability: f32) -> Self {
+        self.stay_probability = probability.clamp(0.0, 1.0);
+        self
+    }
+
+    pub fn value(&self) -> f32 {
+        self.probabilities[1]
+    }
+
+    pub fn reset(&mut self) {
+        self.probabilities = [0.5, 0.5];
+        self.received = false;
+    }
+}
+
+impl Next<f32> for HiddenMarkovModel {
+    type Output = f32;
+
+    // one step of the forward algorithm
+    fn next(&mut self, input: f32) -> Self::Output {
+        self.received = true;
+        let switch_probability = 1.0 - self.stay_probability;
+        let prior = [
+            self.probabilities[0] * self.stay_probability + self.probabilities[1] * switch_probability,
+            self.probabilities[1] * self.stay_probability + self.probabilities[0] * switch_probability,
+        ];
+
+        // likelihoods in logarithms, large returns would make both of them 0.0 otherwise
+        let log_likelihood = |deviation: f32| -deviation.ln() - 0.5 * (input / deviation).powi(2);
+        let logs = [log_likelihood(self.deviations[0]), log_likelihood(self.deviations[1])];
+        let highest = logs[0].max(logs[1]);
+        let posterior = [prior[0] * (logs[0] - highest).exp(), prior[1] * (logs[1] - highest).exp()];
+
+        let total = posterior[0] + posterior[1];
+        if total > 0.0 {
+            self.probabilities = [posterior[0] / total, posterior[1] / total];
+        }
+        self.probabilities[1]
+    }
+}
+
+impl Indicator<f32> for HiddenMarkovModel {
+    fn value(&self) -> f32 {
+        self.probabilities[1]
+    }
+
+    fn is_ready(&self) -> bool {
+        self.received
+    }
+
+    fn warmup_period(&self) -> usize {
+        1
+    }
+}
+
+impl Reset for HiddenMarkovModel {
+    fn reset(&mut self) {
+        self.reset();
+    }
+}
+
+impl fmt::Display for HiddenMarkovModel {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "HMM({}, {})", self.deviations[0], self.deviations[1])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::candlestick::Candlestick;
+
+    fn candle(close: f32, range: f32) -> Candlestick {
+        Candlestick::new().open(close).high(close + range).low(close - range).close(close)
+    }
+
+    #[test]
+    fn test_trending_and_ranging() {
+        let mut classifier = RegimeClassifier::new(14, 50);
+        let trend: Vec<Regime> = (0..100).map(|i| classifier.next(&candle(100.0 + i as f32, 0.5))).collect();
+        assert_eq!(trend[99], Regime::Trending);
+
+        classifier.reset();
+        let sideways: Vec<Regime> = (0..100).map(|i| classifier.next(&candle(100.0 + (i % 2) as f32, 1.0))).collect();
+        assert_eq!(sideways[99], Regime::Ranging);
+    }
+
+    #[test]
+    fn test_volatile() {
+        let mut classifier = RegimeClassifier::new(14, 50);
+        for i in 0..100 {
+            classifier.next(&candle(100.0 + (i % 2) as f32, 1.0));
+        }
+        assert_eq!(classifier.next(&candle(100.0, 8.0)), Regime::Volatile);
+        assert!(Indicator::<&Candlestick>::is_ready(&classifier));
+    }
+
+    #[test]
+    fn test_hidden_markov_model() {
+        let mut model = HiddenMarkovModel::new(0.005, 0.03);
+        for i in 0..20 {
+            model.next(if i % 2 == 0 { 0.002 } else { -0.002 });
+        }
+        assert!(model.value() < 0.1, "calm returns gave {}", model.value());
+        for i in 0..5 {
+            model.next(if i % 2 == 0 { 0.04 } else { -0.04 });
+        }
+        assert!(model.value() > 0.9, "large returns gave {}", model.value());
+        // a single calm return doesn't switch back right away
+        assert!(model.next(0.0) > 0.5);
+    }
+
+    #[test]
+    fn test_classifier_with_hidden_markov_model() {
+        let mut classifier = RegimeClassifier::default().hidden_markov(HiddenMarkovModel::new(0.005, 0.03));
+        let mut price = 100.0;
+        for i in 0..10 {
+            price *= if i % 2 == 0 { 1.05 } else { 0.95 };
+            classifier.next(&candle(price, 1.0));
+        }
+        assert_eq!(classifier.value(), Regime::Volatile);
+        assert_eq!(format!("{}", classifier), "REGIME(14, 100)");
+    }
+}
@@ -1,30 +1,43 @@
 use crate::candlestick::{Candlestick, Close};
-use crate::indicators::exponential_moving_average::ExponentialMovingAverage as Ema;
-use crate::indicators::traits::Next;
+use crate::indicators::moving_average::Smoothing;
+use crate::indicators::traits::{Indicator, MovingAverage, Next, Reset};
 
 use std::fmt;
 
+/// Relative strength index, by default smoothed with an ema seeded with small gains and losses.
+/// `Smoothing::Wilder` gives the standard rsi of TA-Lib and TradingView
 pub struct RelativeStrengthIndex {
     period: usize,
-    up_ema_indicator: Ema,
-    down_ema_indicator: Ema,
+    smoothing: Smoothing,
+    up_ema_indicator: Box<dyn MovingAverage>,
+    down_ema_indicator: Box<dyn MovingAverage>,
     prev_val: f32,
     is_new: bool,
     current_rsi: f32,
+    count: usize, // inputs received since the last reset
 }
 
 impl RelativeStrengthIndex {
     pub fn new(period: usize) -> Self {
         Self {
             period,
-            up_ema_indicator: Ema::new(period),
-            down_ema_indicator: Ema::new(period),
+            smoothing: Smoothing::Ema,
+            up_ema_indicator: Smoothing::Ema.build(period),
+            down_ema_indicator: Smoothing::Ema.build(period),
             prev_val: 0.0,
             is_new: true,
             current_rsi: 0.0,
+            count: 0,
         }
     }
 
+    pub fn smoothing(mut self, smoothing: Smoothing) -> Self {
+        self.smoothing = smoothing;
+        self.up_ema_indicator = smoothing.build(self.period);
+        self.down_ema_indicator = smoothing.build(self.period);
+        self
+    }
+
     pub fn value(&self) -> f32 {
         self.current_rsi
     }
@@ -33,6 +46,7 @@ impl RelativeStrengthIndex {
         self.is_new = true;
         self.prev_val = 0.0;
         self.current_rsi = 0.0;
+        self.count = 0;
         self.up_ema_indicator.reset();
         self.down_ema_indicator.reset();
     }
@@ -42,11 +56,17 @@ impl Next<f32> for RelativeStrengthIndex {
     type Output = f32;
 
     fn next(&mut self, close_value: f32) -> Self::Output {
+        self.count += 1;
         let mut up = 0.0;
         let mut down = 0.0;
 
         if self.is_new {
             self.is_new = false;
+            self.prev_val = close_value;
+            if self.smoothing == Smoothing::Wilder {
+                // there is no change yet, the averages start with the second value
+                return self.current_rsi;
+            }
             // Initialize with some small seed numbers to avoid division by zero
             up = 0.1;
             down = 0.1;
@@ -61,7 +81,8 @@ impl Next<f32> for RelativeStrengthIndex {
         self.prev_val = close_value;
         let up_ema = self.up_ema_indicator.next(up);
         let down_ema = self.down_ema_indicator.next(down);
-        self.current_rsi = 100.0 * up_ema / (up_ema + down_ema);
+        // like TA-Lib a series without changes has a rsi of 0.0
+        self.current_rsi = if up_ema + down_ema > 0.0 { 100.0 * up_ema / (up_ema + down_ema) } else { 0.0 };
         self.current_rsi
     }
 }
@@ -82,9 +103,33 @@ impl<T: Close> Next<&T> for RelativeStrengthIndex {
     }
 }
 
+impl Indicator<f32> for RelativeStrengthIndex {
+    fn value(&self) -> f32 {
+        self.current_rsi
+    }
+
+    fn is_ready(&self) -> bool {
+        self.count >= self.warmup_period()
+    }
+
+    fn warmup_period(&self) -> usize {
+        // the first value has no previous one to compare with
+        self.period + 1
+    }
+}
+
+impl Reset for RelativeStrengthIndex {
+    fn reset(&mut self) {
+        self.reset();
+    }
+}
+
 impl fmt::Display for RelativeStrengthIndex {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "RSI({})", self.period)
+        match self.smoothing {
+            Smoothing::Ema => write!(f, "RSI({})", self.period),
+            Smoothing::Wilder => write!(f, "RSI({}, wilder)", self.period),
+        }
     }
 }
 
@@ -107,9 +152,25 @@ mod tests {
         assert_eq!(rsi.next(&Candlestick::new().close(9.5)).round(), 16.0);
     }
 
+    #[test]
+    fn test_wilder_matches_reference() {
+        let mut rsi = RelativeStrengthIndex::new(14).smoothing(Smoothing::Wilder);
+        let fixture = include_str!("fixtures/rsi_wilder_14.csv");
+        let rows = fixture.lines().filter(|line| !line.starts_with('#')).skip(1);
+        for (i, row) in rows.enumerate() {
+            let (close, expected) = row.split_once(',').unwrap();
+            let value = rsi.next(close.parse::<f32>().unwrap());
+            assert_eq!(rsi.is_ready(), !expected.is_empty(), "readiness of row {}", i);
+            if let Ok(expected) = expected.parse::<f32>() {
+                assert!((value - expected).abs() < 1e-3, "row {}: expected {} but was {}", i, expected, value);
+            }
+        }
+    }
+
     #[test]
     fn test_display() {
         let rsi = RelativeStrengthIndex::new(16);
         assert_eq!(format!("{}", rsi), "RSI(16)");
+        assert_eq!(format!("{}", rsi.smoothing(Smoothing::Wilder)), "RSI(16, wilder)");
     }
 }
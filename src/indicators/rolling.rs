@@ -0,0 +1,531 @@
+use crate::indicators::traits::{Indicator, Next, Period, Reset};
+use std::collections::VecDeque;
+use std::fmt;
+
+// Statistics over the last `period` values of a series. Until `period` values were received they are computed over
+// the values received so far, never over empty slots, and `is_ready` tells when the window is full.
+// Highest high and lowest low are `Highest` and `Lowest`, built on `RollingMax` and `RollingMin`
+
+/// Last `period` values of a series, the oldest one is dropped when a new value arrives on a full window
+#[derive(Debug, Clone)]
+pub struct RollingWindow {
+    period: usize,
+    values: VecDeque<f32>,
+}
+
+impl RollingWindow {
+    pub fn new(period: usize) -> Self {
+        Self {
+            period: period.max(1),
+            values: VecDeque::with_capacity(period.max(1)),
+        }
+    }
+
+    /// adds a value and returns the one that left the window, if it was full
+    pub fn push(&mut self, value: f32) -> Option<f32> {
+        let removed = if self.is_full() { self.values.pop_front() } else { None };
+        self.values.push_back(value);
+        removed
+    }
+
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.values.len() == self.period
+    }
+
+    /// values from the oldest to the newest
+    pub fn iter(&self) -> impl Iterator<Item = f32> + '_ {
+        self.values.iter().copied()
+    }
+
+    pub fn clear(&mut self) {
+        self.values.clear();
+    }
+}
+
+impl Period for RollingWindow {
+    fn period(&self) -> usize {
+        self.period
+    }
+}
+
+// monotonic deque shared by the rolling min and max: the front is the extreme of the window and every value behind
+// it is the extreme of the values received after the one in front, so each value is pushed and popped only once
+#[derive(Debug, Clone)]
+struct MonotonicDeque {
+    period: usize,
+    received: usize, // values received since the last reset, the position of the next one
+    values: VecDeque<(usize, f32)>,
+}
+
+impl MonotonicDeque {
+    fn new(period: usize) -> Self {
+        Self { period: period.max(1), received: 0, values: VecDeque::new() }
+    }
+
+    // `dominates(a, b)` is true when `a` replaces `b` as the extreme
+    fn push(&mut self, value: f32, dominates: fn(f32, f32) -> bool) -> f32 {
+        while self.values.back().is_some_and(|(_, last)| dominates(value, *last)) {
+            self.values.pop_back();
+        }
+        self.values.push_back((self.received, value));
+        self.received += 1;
+
+        while self.values.front().is_some_and(|(position, _)| position + self.period < self.received) {
+            self.values.pop_front();
+        }
+        self.extreme()
+    }
+
+    fn extreme(&self) -> f32 {
+        self.values.front().map_or(0.0, |(_, value)| *value)
+    }
+
+    fn is_full(&self) -> bool {
+        self.received >= self.period
+    }
+
+    fn clear(&mut self) {
+        self.received = 0;
+        self.values.clear();
+    }
+}
+
+/// Maximum of the last `period` values in constant time, 0.0 until a value is received
+#[derive(Debug, Clone)]
+pub struct RollingMax {
+    deque: MonotonicDeque,
+}
+
+impl RollingMax {
+    pub fn new(period: usize) -> Self {
+        Self { deque: MonotonicDeque::new(period) }
+    }
+
+    pub fn value(&self) -> f32 {
+        self.deque.extreme()
+    }
+
+    pub fn reset(&mut self) {
+        self.deque.clear();
+    }
+}
+
+/// Minimum of the last `period` values in constant time, 0.0 until a value is received
+#[derive(Debug, Clone)]
+pub struct RollingMin {
+    deque: MonotonicDeque,
+}
+
+impl RollingMin {
+    pub fn new(period: usize) -> Self {
+        Self { deque: MonotonicDeque::new(period) }
+    }
+
+    pub fn value(&self) -> f32 {
+        self.deque.extreme()
+    }
+
+    pub fn reset(&mut self) {
+        self.deque.clear();
+    }
+}
+
+impl Next<f32> for RollingMax {
+    type Output = f32;
+
+    fn next(&mut self, input: f32) -> Self::Output {
+        self.deque.push(input, |new, old| new >= old)
+    }
+}
+
+impl Next<f32> for RollingMin {
+    type Output = f32;
+
+    fn next(&mut self, input: f32) -> Self::Output {
+        self.deque.push(input, |new, old| new <= old)
+    }
+}
+
+/// Population variance and standard deviation of the last `period` values
+#[derive(Debug, Clone)]
+pub struct RollingStdDev {
+    window: RollingWindow,
+    mean: f32,
+    variance: f32,
+}
+
+impl RollingStdDev {
+    pub fn new(period: usize) -> Self {
+        Self { window: RollingWindow::new(period), mean: 0.0, variance: 0.0 }
+    }
+
+    pub fn value(&self) -> f32 {
+        self.variance.sqrt()
+    }
+
+    pub fn mean(&self) -> f32 {
+        self.mean
+    }
+
+    pub fn variance(&self) -> f32 {
+        self.variance
+    }
+
+    pub fn reset(&mut self) {
+        self.window.clear();
+        self.mean = 0.0;
+        self.variance = 0.0;
+    }
+}
+
+impl Next<f32> for RollingStdDev {
+    type Output = f32;
+
+    fn next(&mut self, input: f32) -> Self::Output {
+        self.window.push(input);
+
+        // computed over the window instead of running sums, which lose precision on long series of f32
+        let count = self.window.len() as f32;
+        self.mean = self.window.iter().sum::<f32>() / count;
+        self.variance = self.window.iter().map(|value| (value - self.mean).powi(2)).sum::<f32>() / count;
+        self.value()
+    }
+}
+
+/// Quantile of the last `period` values, interpolated between the two closest values. A quantile of 0.5 is the median
+#[derive(Debug, Clone)]
+pub struct RollingQuantile {
+    quantile: f32,
+    window: RollingWindow,
+    sorted: Vec<f32>, // values of the window in ascending order
+    current_value: f32,
+}
+
+impl RollingQuantile {
+    /// `quantile` between 0.0 and 1.0
+    pub fn new(period: usize, quantile: f32) -> Self {
+        Self {
+            quantile: quantile.clamp(0.0, 1.0),
+            window: RollingWindow::new(period),
+            sorted: Vec::with_capacity(period.max(1)),
+            current_value: 0.0,
+        }
+    }
+
+    pub fn median(period: usize) -> Self {
+        Self::new(period, 0.5)
+    }
+
+    pub fn value(&self) -> f32 {
+        self.current_value
+    }
+
+    pub fn reset(&mut self) {
+        self.window.clear();
+        self.sorted.clear();
+        self.current_value = 0.0;
+    }
+}
+
+impl Next<f32> for RollingQuantile {
+    type Output = f32;
+
+    fn next(&mut self, input: f32) -> Self::Output {
+        if let Some(removed) = self.window.push(input) {
+            let position = self.sorted.partition_point(|value| value.total_cmp(&removed).is_lt());
+            self.sorted.remove(position);
+        }
+        let position = self.sorted.partition_point(|value| value.total_cmp(&input).is_lt());
+        self.sorted.insert(position, input);
+
+        let rank = self.quantile * (self.sorted.len() - 1) as f32;
+        let (below, above) = (rank.floor() as usize, rank.ceil() as usize);
+        let weight = rank - below as f32;
+        self.current_value = self.sorted[below] * (1.0 - weight) + self.sorted[above] * weight;
+        self.current_value
+    }
+}
+
+/// Slope of the least squares line through the last `period` values, the change per input of a linear regression
+#[derive(Debug, Clone)]
+pub struct LinearRegressionSlope {
+    window: RollingWindow,
+    current_value: f32,
+}
+
+impl LinearRegressionSlope {
+    pub fn new(period: usize) -> Self {
+        Self { window: RollingWindow::new(period), current_value: 0.0 }
+    }
+
+    pub fn value(&self) -> f32 {
+        self.current_value
+    }
+
+    pub fn reset(&mut self) {
+        self.window.clear();
+        self.current_value = 0.0;
+    }
+}
+
+impl Next<f32> for LinearRegressionSlope {
+    type Output = f32;
+
+    fn next(&mut self, input: f32) -> Self::Output {
+        self.window.push(input);
+
+        // the values are placed at x = 0, 1, 2, ... so the mean of x is (n - 1) / 2
+        let count = self.window.len() as f32;
+        let mean_x = (count - 1.0) / 2.0;
+        let mean_y = self.window.iter().sum::<f32>() / count;
+        let (covariance, variance) = self.window.iter().enumerate().fold((0.0, 0.0), |(covariance, variance), (x, y)| {
+            let dx = x as f32 - mean_x;
+            (covariance + dx * (y - mean_y), variance + dx * dx)
+        });
+        self.current_value = if variance > 0.0 { covariance / variance } else { 0.0 };
+        self.current_value
+    }
+}
+
+impl Period for RollingMax {
+    fn period(&self) -> usize {
+        self.deque.period
+    }
+}
+
+impl Indicator<f32> for RollingMax {
+    fn value(&self) -> f32 {
+        self.value()
+    }
+
+    fn is_ready(&self) -> bool {
+        self.deque.is_full()
+    }
+
+    fn warmup_period(&self) -> usize {
+        self.deque.period
+    }
+}
+
+impl Reset for RollingMax {
+    fn reset(&mut self) {
+        self.reset();
+    }
+}
+
+impl fmt::Display for RollingMax {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "MAX({})", self.deque.period)
+    }
+}
+
+impl Period for RollingMin {
+    fn period(&self) -> usize {
+        self.deque.period
+    }
+}
+
+impl Indicator<f32> for RollingMin {
+    fn value(&self) -> f32 {
+        self.value()
+    }
+
+    fn is_ready(&self) -> bool {
+        self.deque.is_full()
+    }
+
+    fn warmup_period(&self) -> usize {
+        self.deque.period
+    }
+}
+
+impl Reset for RollingMin {
+    fn reset(&mut self) {
+        self.reset();
+    }
+}
+
+impl fmt::Display for RollingMin {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "MIN({})", self.deque.period)
+    }
+}
+
+impl Period for RollingStdDev {
+    fn period(&self) -> usize {
+        self.window.period
+    }
+}
+
+impl Indicator<f32> for RollingStdDev {
+    fn value(&self) -> f32 {
+        self.value()
+    }
+
+    fn is_ready(&self) -> bool {
+        self.window.is_full()
+    }
+
+    fn warmup_period(&self) -> usize {
+        self.window.period
+    }
+}
+
+impl Reset for RollingStdDev {
+    fn reset(&mut self) {
+        self.reset();
+    }
+}
+
+impl fmt::Display for RollingStdDev {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "STDDEV({})", self.window.period)
+    }
+}
+
+impl Period for LinearRegressionSlope {
+    fn period(&self) -> usize {
+        self.window.period
+    }
+}
+
+impl Indicator<f32> for LinearRegressionSlope {
+    fn value(&self) -> f32 {
+        self.value()
+    }
+
+    fn is_ready(&self) -> bool {
+        self.window.is_full()
+    }
+
+    fn warmup_period(&self) -> usize {
+        self.window.period
+    }
+}
+
+impl Reset for LinearRegressionSlope {
+    fn reset(&mut self) {
+        self.reset();
+    }
+}
+
+impl fmt::Display for LinearRegressionSlope {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "SLOPE({})", self.window.period)
+    }
+}
+
+impl Period for RollingQuantile {
+    fn period(&self) -> usize {
+        self.window.period
+    }
+}
+
+impl Indicator<f32> for RollingQuantile {
+    fn value(&self) -> f32 {
+        self.current_value
+    }
+
+    fn is_ready(&self) -> bool {
+        self.window.is_full()
+    }
+
+    fn warmup_period(&self) -> usize {
+        self.window.period
+    }
+}
+
+impl Reset for RollingQuantile {
+    fn reset(&mut self) {
+        self.reset();
+    }
+}
+
+impl fmt::Display for RollingQuantile {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "QUANTILE({}, {})", self.window.period, self.quantile)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // deterministic series with repeated values and both trends
+    fn series() -> Vec<f32> {
+        (0..200).map(|i| ((i * 37) % 23) as f32 - (i / 10) as f32).collect()
+    }
+
+    #[test]
+    fn test_min_max_match_a_full_scan() {
+        for period in [1, 2, 5, 17] {
+            let mut max = RollingMax::new(period);
+            let mut min = RollingMin::new(period);
+            let values = series();
+            for (i, value) in values.iter().enumerate() {
+                let window = &values[(i + 1).saturating_sub(period)..=i];
+                assert_eq!(max.next(*value), window.iter().copied().fold(f32::MIN, f32::max));
+                assert_eq!(min.next(*value), window.iter().copied().fold(f32::MAX, f32::min));
+            }
+        }
+    }
+
+    #[test]
+    fn test_partial_window() {
+        let mut min = RollingMin::new(5);
+        assert_eq!(min.value(), 0.0);
+        assert_eq!(min.next(3.0), 3.0);
+        assert_eq!(min.next(4.0), 3.0);
+        assert!(!min.is_ready());
+        Reset::reset(&mut min);
+        assert_eq!(min.next(7.0), 7.0);
+    }
+
+    #[test]
+    fn test_std_dev() {
+        let mut deviation = RollingStdDev::new(4);
+        assert_eq!(deviation.next(2.0), 0.0);
+        deviation.next(4.0);
+        assert_eq!(deviation.variance(), 1.0);
+        deviation.next(4.0);
+        deviation.next(6.0);
+        assert_eq!(deviation.mean(), 4.0);
+        assert_eq!(deviation.variance(), 2.0);
+        // 2.0 leaves the window
+        deviation.next(4.0);
+        assert_eq!(deviation.mean(), 4.5);
+        assert!(deviation.is_ready());
+    }
+
+    #[test]
+    fn test_quantile() {
+        let mut median = RollingQuantile::median(4);
+        let values: Vec<f32> = [5.0, 1.0, 3.0, 8.0, 2.0, 2.0].iter().map(|v| median.next(*v)).collect();
+        assert_eq!(values, vec![5.0, 3.0, 3.0, 4.0, 2.5, 2.5]);
+
+        let mut quantile = RollingQuantile::new(5, 0.25);
+        for value in [10.0, 20.0, 30.0, 40.0, 50.0] {
+            quantile.next(value);
+        }
+        assert_eq!(quantile.value(), 20.0);
+        assert_eq!(format!("{}", quantile), "QUANTILE(5, 0.25)");
+    }
+
+    #[test]
+    fn test_linear_regression_slope() {
+        let mut slope = LinearRegressionSlope::new(3);
+        assert_eq!(slope.next(1.0), 0.0);
+        assert_eq!(slope.next(3.0), 2.0);
+        assert_eq!(slope.next(5.0), 2.0);
+        assert_eq!(slope.next(2.0), -0.5);
+        assert_eq!(format!("{}", slope), "SLOPE(3)");
+    }
+}
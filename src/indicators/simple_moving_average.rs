@@ -1,5 +1,6 @@
 use crate::candlestick::{Candlestick, Close};
-use crate::indicators::traits::{ Next, Period };
+use crate::indicators::rolling::{RollingMax, RollingMin};
+use crate::indicators::traits::{ Indicator, MovingAverage, Next, Period, Reset };
 use std::fmt;
 
 pub struct SimpleMovingAverage {
@@ -9,6 +10,8 @@ pub struct SimpleMovingAverage {
     sum: f32,
     deque: Box<[f32]>,
     current_value: f32,
+    min: RollingMin,
+    max: RollingMax,
 }
 
 impl SimpleMovingAverage {
@@ -20,6 +23,8 @@ impl SimpleMovingAverage {
             sum: 0.0,
             deque: vec![0.0; period].into_boxed_slice(),
             current_value: 0.0,
+            min: RollingMin::new(period),
+            max: RollingMax::new(period),
         }
     }
 
@@ -27,26 +32,14 @@ impl SimpleMovingAverage {
         self.current_value
     }
 
+    /// lowest input of the period, only the inputs received count until the period is complete
     pub fn min_value_on_period(&self) -> f32 {
-        let mut min = self.deque[0];
-        for i in 1..self.deque.len() {
-            if self.deque[i] < min {
-                min = self.deque[i];
-            }
-        }
-
-        return min;
+        self.min.value()
     }
 
+    /// highest input of the period, only the inputs received count until the period is complete
     pub fn max_value_on_period(&self) -> f32 {
-        let mut max = self.deque[0];
-        for i in 1..self.deque.len() {
-            if self.deque[i] > max {
-                max = self.deque[i];
-            }
-        }
-
-        return max;
+        self.max.value()
     }
 
     pub fn reset(&mut self) {
@@ -54,6 +47,8 @@ impl SimpleMovingAverage {
         self.count = 0;
         self.sum = 0.0;
         self.current_value = 0.0;
+        self.min.reset();
+        self.max.reset();
         for i in 0..self.period {
             self.deque[i] = 0.0;
         }
@@ -83,6 +78,8 @@ impl Next<f32> for SimpleMovingAverage {
             self.count += 1;
         }
 
+        self.min.next(input);
+        self.max.next(input);
         self.sum = self.sum - old_val + input;
         self.current_value = self.sum / (self.count as f32);
         self.current_value
@@ -106,6 +103,28 @@ impl<T: Close> Next<&T> for SimpleMovingAverage {
     }
 }
 
+impl Indicator<f32> for SimpleMovingAverage {
+    fn value(&self) -> f32 {
+        self.current_value
+    }
+
+    fn is_ready(&self) -> bool {
+        self.count >= self.period
+    }
+
+    fn warmup_period(&self) -> usize {
+        self.period
+    }
+}
+
+impl MovingAverage for SimpleMovingAverage {}
+
+impl Reset for SimpleMovingAverage {
+    fn reset(&mut self) {
+        self.reset();
+    }
+}
+
 impl fmt::Display for SimpleMovingAverage {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "SMA({})", self.period)
@@ -134,6 +153,20 @@ mod tests {
         assert_eq!(sma.next(2.0), 5.0);
     }
 
+    #[test]
+    fn test_is_ready() {
+        let mut sma = SimpleMovingAverage::new(3);
+        assert_eq!(sma.warmup_period(), 3);
+        sma.next(1.0);
+        sma.next(2.0);
+        assert!(!sma.is_ready());
+        sma.next(3.0);
+        assert!(sma.is_ready());
+        assert_eq!(Indicator::value(&sma), 2.0);
+        Reset::reset(&mut sma);
+        assert!(!sma.is_ready());
+    }
+
     #[test]
     fn test_next_with_candlesticks() {
         let mut sma = SimpleMovingAverage::new(3);
@@ -165,6 +198,19 @@ mod tests {
         assert_eq!(sma.min_value_on_period(), 11.0);
     }
 
+    #[test]
+    fn test_min_max_before_the_period_is_complete() {
+        let mut sma = SimpleMovingAverage::new(5);
+        sma.next(3.0);
+        sma.next(4.0);
+        assert_eq!(sma.min_value_on_period(), 3.0);
+        assert_eq!(sma.max_value_on_period(), 4.0);
+
+        sma.reset();
+        sma.next(-2.0);
+        assert_eq!(sma.max_value_on_period(), -2.0);
+    }
+
     #[test]
     fn test_max_value(){
         let mut sma = SimpleMovingAverage::new(3);
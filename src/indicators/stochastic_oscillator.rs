@@ -0,0 +1,134 @@
+use crate::candlestick::{Close, High, Low};
+use crate::indicators::traits::{Indicator, Next, Period, Reset};
+use crate::indicators::highest_lowest::{Highest, Lowest};
+use crate::indicators::simple_moving_average::SimpleMovingAverage as Sma;
+use std::fmt;
+
+/// Position of the close inside the range of the last `k_period` candles (%K) and its moving average (%D), from 0 to 100
+pub struct StochasticOscillator {
+    highest: Highest,
+    lowest: Lowest,
+    d_sma: Sma,
+    current: StochasticOscillatorOutput,
+    count: usize, // inputs received since the last reset
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct StochasticOscillatorOutput {
+    pub k: f32,
+    pub d: f32,
+}
+
+impl StochasticOscillator {
+    pub fn new(k_period: usize, d_period: usize) -> Self {
+        Self {
+            highest: Highest::new(k_period),
+            lowest: Lowest::new(k_period),
+            d_sma: Sma::new(d_period.max(1)),
+            current: StochasticOscillatorOutput::default(),
+            count: 0,
+        }
+    }
+
+    pub fn value(&self) -> StochasticOscillatorOutput {
+        self.current
+    }
+
+    pub fn reset(&mut self) {
+        self.highest.reset();
+        self.lowest.reset();
+        self.d_sma.reset();
+        self.current = StochasticOscillatorOutput::default();
+        self.count = 0;
+    }
+
+    fn next_values(&mut self, high: f32, low: f32, close: f32) -> StochasticOscillatorOutput {
+        self.count += 1;
+        let highest = self.highest.next(high);
+        let lowest = self.lowest.next(low);
+        // a flat range puts the close in the middle of it
+        let k = if highest > lowest { 100.0 * (close - lowest) / (highest - lowest) } else { 50.0 };
+        self.current = StochasticOscillatorOutput { k, d: self.d_sma.next(k) };
+        self.current
+    }
+}
+
+impl Period for StochasticOscillator {
+    fn period(&self) -> usize {
+        self.highest.period()
+    }
+}
+
+impl Next<f32> for StochasticOscillator {
+    type Output = StochasticOscillatorOutput;
+
+    fn next(&mut self, input: f32) -> Self::Output {
+        self.next_values(input, input, input)
+    }
+}
+
+impl<T: High + Low + Close> Next<&T> for StochasticOscillator {
+    type Output = StochasticOscillatorOutput;
+
+    fn next(&mut self, input: &T) -> Self::Output {
+        self.next_values(input.high(), input.low(), input.close())
+    }
+}
+
+impl<T: High + Low + Close> Indicator<&T> for StochasticOscillator {
+    fn value(&self) -> StochasticOscillatorOutput {
+        self.current
+    }
+
+    fn is_ready(&self) -> bool {
+        self.count >= Indicator::<&T>::warmup_period(self)
+    }
+
+    fn warmup_period(&self) -> usize {
+        // %d averages %k once it is ready
+        self.highest.period() + self.d_sma.period() - 1
+    }
+}
+
+impl Reset for StochasticOscillator {
+    fn reset(&mut self) {
+        self.reset();
+    }
+}
+
+impl fmt::Display for StochasticOscillator {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "STOCH({}, {})", self.highest.period(), self.d_sma.period())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::candlestick::Candlestick;
+
+    #[test]
+    fn test_next() {
+        let mut stoch = StochasticOscillator::new(3, 2);
+        let output = stoch.next(&Candlestick::new().high(10.0).low(8.0).close(9.0));
+        assert_eq!((output.k, output.d), (50.0, 50.0));
+        let output = stoch.next(&Candlestick::new().high(12.0).low(9.0).close(11.0));
+        assert_eq!((output.k, output.d), (75.0, 62.5));
+        let output = stoch.next(&Candlestick::new().high(11.0).low(7.0).close(8.0));
+        assert_eq!((output.k, output.d), (20.0, 47.5));
+        // the first candle leaves the window
+        let output = stoch.next(&Candlestick::new().high(9.0).low(8.5).close(9.0));
+        assert_eq!((output.k, output.d), (40.0, 30.0));
+    }
+
+    #[test]
+    fn test_flat_range() {
+        let mut stoch = StochasticOscillator::new(5, 3);
+        assert_eq!(stoch.next(10.0).k, 50.0);
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(format!("{}", StochasticOscillator::new(14, 3)), "STOCH(14, 3)");
+    }
+}
@@ -0,0 +1,138 @@
+use crate::candlestick::{Close, High, Low};
+use crate::indicators::traits::{Indicator, Next, Period, Reset};
+use crate::indicators::average_true_range::AverageTrueRange as ATR;
+use std::fmt;
+
+/// Trailing line `multiplier` average true ranges away from the middle of the candle, below the price
+/// on uptrends and above it on downtrends. The trend flips when the close crosses the line
+pub struct Supertrend {
+    atr: ATR,
+    multiplier: f32,
+    upper_band: f32,
+    lower_band: f32,
+    prev_close: Option<f32>,
+    current: SupertrendOutput,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct SupertrendOutput {
+    pub value: f32,
+    pub uptrend: bool,
+}
+
+impl Supertrend {
+    pub fn new(period: usize, multiplier: f32) -> Self {
+        Self {
+            atr: ATR::new(period),
+            multiplier,
+            upper_band: 0.0,
+            lower_band: 0.0,
+            prev_close: None,
+            current: SupertrendOutput::default(),
+        }
+    }
+
+    pub fn value(&self) -> SupertrendOutput {
+        self.current
+    }
+
+    pub fn reset(&mut self) {
+        self.atr.reset();
+        self.upper_band = 0.0;
+        self.lower_band = 0.0;
+        self.prev_close = None;
+        self.current = SupertrendOutput::default();
+    }
+}
+
+impl Period for Supertrend {
+    fn period(&self) -> usize {
+        self.atr.period()
+    }
+}
+
+impl<T: High + Low + Close> Next<&T> for Supertrend {
+    type Output = SupertrendOutput;
+
+    fn next(&mut self, input: &T) -> Self::Output {
+        let middle = (input.high() + input.low()) / 2.0;
+        let distance = self.atr.next(input) * self.multiplier;
+        let basic_upper = middle + distance;
+        let basic_lower = middle - distance;
+        let close = input.close();
+
+        let uptrend = match self.prev_close {
+            Some(prev_close) => {
+                // the bands only move towards the price, unless the previous close already crossed them
+                if basic_upper < self.upper_band || prev_close > self.upper_band {
+                    self.upper_band = basic_upper;
+                }
+                if basic_lower > self.lower_band || prev_close < self.lower_band {
+                    self.lower_band = basic_lower;
+                }
+                if self.current.uptrend { close >= self.lower_band } else { close > self.upper_band }
+            }
+            None => {
+                self.upper_band = basic_upper;
+                self.lower_band = basic_lower;
+                close >= middle
+            }
+        };
+        self.prev_close = Some(close);
+
+        self.current = SupertrendOutput {
+            value: if uptrend { self.lower_band } else { self.upper_band },
+            uptrend,
+        };
+        self.current
+    }
+}
+
+impl<T: High + Low + Close> Indicator<&T> for Supertrend {
+    fn value(&self) -> SupertrendOutput {
+        self.current
+    }
+
+    fn is_ready(&self) -> bool {
+        Indicator::<&T>::is_ready(&self.atr)
+    }
+
+    fn warmup_period(&self) -> usize {
+        self.atr.period()
+    }
+}
+
+impl Reset for Supertrend {
+    fn reset(&mut self) {
+        self.reset();
+    }
+}
+
+impl fmt::Display for Supertrend {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "SUPERTREND({}, {})", self.atr.period(), self.multiplier)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::candlestick::Candlestick;
+
+    #[test]
+    fn test_next() {
+        let mut supertrend = Supertrend::new(3, 1.0);
+        // atr of 2.5, 2.25 and 3.375
+        assert_eq!(supertrend.next(&Candlestick::new().high(10.0).low(7.5).close(9.0)), SupertrendOutput { value: 6.25, uptrend: true });
+        assert_eq!(supertrend.next(&Candlestick::new().high(11.0).low(9.0).close(9.5)), SupertrendOutput { value: 7.75, uptrend: true });
+        // the lower band doesn't move down while the trend is up
+        assert_eq!(supertrend.next(&Candlestick::new().high(9.0).low(5.0).close(8.0)), SupertrendOutput { value: 7.75, uptrend: true });
+        // the close falls below the lower band and the line jumps to the upper band
+        assert_eq!(supertrend.next(&Candlestick::new().high(7.0).low(5.0).close(5.5)), SupertrendOutput { value: 9.1875, uptrend: false });
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(format!("{}", Supertrend::new(10, 3.0)), "SUPERTREND(10, 3)");
+    }
+}
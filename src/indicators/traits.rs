@@ -9,3 +9,23 @@ pub trait Next<T> {
     type Output;
     fn next(&mut self, input: T) -> Self::Output;
 }
+
+/// Reset clears the state of an indicator so it can be computed again on a new series
+pub trait Reset {
+    fn reset(&mut self);
+}
+
+/// Indicator is the interface shared by all indicators, the streaming `next` and `reset` plus the last value computed
+/// and how many inputs are needed before that value is meaningful, so callers can warm up any indicator the same way
+pub trait Indicator<T>: Next<T> + Reset {
+    // last value returned by `next`
+    fn value(&self) -> Self::Output;
+
+    // true once `warmup_period` inputs were received since the last reset
+    fn is_ready(&self) -> bool;
+
+    fn warmup_period(&self) -> usize;
+}
+
+/// Streaming moving average of a series of values, so different kinds of averages can be used interchangeably
+pub trait MovingAverage: Indicator<f32, Output = f32> + Period + std::fmt::Display + Send {}
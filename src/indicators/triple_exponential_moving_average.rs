@@ -0,0 +1,111 @@
+use crate::candlestick::Close;
+use crate::indicators::traits::{Indicator, MovingAverage, Next, Period, Reset};
+use crate::indicators::exponential_moving_average::ExponentialMovingAverage as Ema;
+use std::fmt;
+
+/// Triple exponential moving average, 3 * EMA - 3 * EMA(EMA) + EMA(EMA(EMA))
+pub struct TripleExponentialMovingAverage {
+    ema: Ema,
+    ema_of_ema: Ema,
+    ema_of_ema_of_ema: Ema,
+    current_value: f32,
+    count: usize, // inputs received since the last reset
+}
+
+impl TripleExponentialMovingAverage {
+    pub fn new(period: usize) -> Self {
+        Self {
+            ema: Ema::new(period),
+            ema_of_ema: Ema::new(period),
+            ema_of_ema_of_ema: Ema::new(period),
+            current_value: 0.0,
+            count: 0,
+        }
+    }
+
+    pub fn value(&self) -> f32 {
+        self.current_value
+    }
+
+    pub fn reset(&mut self) {
+        self.ema.reset();
+        self.ema_of_ema.reset();
+        self.ema_of_ema_of_ema.reset();
+        self.current_value = 0.0;
+        self.count = 0;
+    }
+}
+
+impl Period for TripleExponentialMovingAverage {
+    fn period(&self) -> usize {
+        self.ema.period()
+    }
+}
+
+impl Next<f32> for TripleExponentialMovingAverage {
+    type Output = f32;
+
+    fn next(&mut self, input: f32) -> Self::Output {
+        self.count += 1;
+        let ema = self.ema.next(input);
+        let ema_of_ema = self.ema_of_ema.next(ema);
+        let ema_of_ema_of_ema = self.ema_of_ema_of_ema.next(ema_of_ema);
+        self.current_value = 3.0 * ema - 3.0 * ema_of_ema + ema_of_ema_of_ema;
+        self.current_value
+    }
+}
+
+impl<T: Close> Next<&T> for TripleExponentialMovingAverage {
+    type Output = f32;
+
+    fn next(&mut self, input: &T) -> Self::Output {
+        self.next(input.close())
+    }
+}
+
+impl Indicator<f32> for TripleExponentialMovingAverage {
+    fn value(&self) -> f32 {
+        self.current_value
+    }
+
+    fn is_ready(&self) -> bool {
+        self.count >= self.warmup_period()
+    }
+
+    fn warmup_period(&self) -> usize {
+        3 * self.ema.period() - 2
+    }
+}
+
+impl MovingAverage for TripleExponentialMovingAverage {}
+
+impl Reset for TripleExponentialMovingAverage {
+    fn reset(&mut self) {
+        self.reset();
+    }
+}
+
+impl fmt::Display for TripleExponentialMovingAverage {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "TEMA({})", self.ema.period())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_next() {
+        let mut tema = TripleExponentialMovingAverage::new(3);
+        assert_eq!(tema.next(2.0), 2.0);
+        assert_eq!(tema.next(5.0), 4.625);
+        assert_eq!(tema.next(1.0), 1.6875);
+        assert_eq!(tema.next(6.25), 5.53125);
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(format!("{}", TripleExponentialMovingAverage::new(9)), "TEMA(9)");
+    }
+}
@@ -1,18 +1,54 @@
 use crate::candlestick::{Candlestick, Close, High, Low};
-use crate::indicators::traits::{ Next };
+use crate::indicators::traits::{ Indicator, Next, Reset };
 use std::fmt;
 
 pub struct TrueRange {
     prev_close: Option<f32>,
+    current_value: f32,
 }
 
 impl TrueRange {
     pub fn new() -> Self {
-        Self { prev_close: None }
+        Self { prev_close: None, current_value: 0.0 }
     }
 
     pub fn reset(&mut self) {
         self.prev_close = None;
+        self.current_value = 0.0;
+    }
+}
+
+impl<T: High + Low + Close> Indicator<&T> for TrueRange {
+    fn value(&self) -> f32 {
+        self.current_value
+    }
+
+    fn is_ready(&self) -> bool {
+        self.prev_close.is_some()
+    }
+
+    fn warmup_period(&self) -> usize {
+        1
+    }
+}
+
+impl Indicator<f32> for TrueRange {
+    fn value(&self) -> f32 {
+        self.current_value
+    }
+
+    fn is_ready(&self) -> bool {
+        self.prev_close.is_some()
+    }
+
+    fn warmup_period(&self) -> usize {
+        1
+    }
+}
+
+impl Reset for TrueRange {
+    fn reset(&mut self) {
+        self.reset();
     }
 }
 
@@ -31,6 +67,7 @@ impl Next<f32> for TrueRange {
             None => 0.0,
         };
         self.prev_close = Some(input);
+        self.current_value = distance;
         distance
     }
 }
@@ -49,6 +86,7 @@ impl<T: High + Low + Close> Next<&T> for TrueRange {
             None => candle.high() - candle.low(),
         };
         self.prev_close = Some(candle.close());
+        self.current_value = max_dist;
         max_dist
     }
 }
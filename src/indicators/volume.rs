@@ -0,0 +1,360 @@
+use crate::candlestick::{High, Low, QuoteVolume, Trades, Volume};
+use crate::indicators::rolling::RollingWindow;
+use crate::indicators::simple_moving_average::SimpleMovingAverage as Sma;
+use crate::indicators::traits::{Indicator, Next, Period, Reset};
+use std::collections::VecDeque;
+use std::fmt;
+
+/// Volume of the candle relative to the average volume of the last `period` candles, 2.0 is twice the usual volume.
+/// Returns 0.0 while the average volume is 0.0
+pub struct RelativeVolume {
+    average: Sma,
+    current_value: f32,
+}
+
+impl RelativeVolume {
+    pub fn new(period: usize) -> Self {
+        Self {
+            average: Sma::new(period.max(1)),
+            current_value: 0.0,
+        }
+    }
+
+    pub fn value(&self) -> f32 {
+        self.current_value
+    }
+
+    pub fn reset(&mut self) {
+        self.average.reset();
+        self.current_value = 0.0;
+    }
+}
+
+impl Period for RelativeVolume {
+    fn period(&self) -> usize {
+        self.average.period()
+    }
+}
+
+impl<T: Volume> Next<&T> for RelativeVolume {
+    type Output = f32;
+
+    fn next(&mut self, input: &T) -> Self::Output {
+        let volume = input.volume();
+        let average = self.average.next(volume);
+        self.current_value = if average > 0.0 { volume / average } else { 0.0 };
+        self.current_value
+    }
+}
+
+impl<T: Volume> Indicator<&T> for RelativeVolume {
+    fn value(&self) -> f32 {
+        self.current_value
+    }
+
+    fn is_ready(&self) -> bool {
+        self.average.is_ready()
+    }
+
+    fn warmup_period(&self) -> usize {
+        self.average.warmup_period()
+    }
+}
+
+impl Reset for RelativeVolume {
+    fn reset(&mut self) {
+        self.reset();
+    }
+}
+
+impl fmt::Display for RelativeVolume {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "RVOL({})", self.average.period())
+    }
+}
+
+/// Average size of a trade in the quote asset over the last `period` candles, the quote volume divided by the
+/// amount of trades. Large trades point to institutional activity. Returns 0.0 while there were no trades
+pub struct AverageTradeSize {
+    period: usize,
+    window: VecDeque<(f32, u64)>, // quote volume and trades of each candle
+    quote_volume: f32,
+    trades: u64,
+    current_value: f32,
+}
+
+impl AverageTradeSize {
+    pub fn new(period: usize) -> Self {
+        Self {
+            period: period.max(1),
+            window: VecDeque::with_capacity(period.max(1)),
+            quote_volume: 0.0,
+            trades: 0,
+            current_value: 0.0,
+        }
+    }
+
+    pub fn value(&self) -> f32 {
+        self.current_value
+    }
+
+    pub fn reset(&mut self) {
+        self.window.clear();
+        self.quote_volume = 0.0;
+        self.trades = 0;
+        self.current_value = 0.0;
+    }
+}
+
+impl Period for AverageTradeSize {
+    fn period(&self) -> usize {
+        self.period
+    }
+}
+
+impl<T: QuoteVolume + Trades> Next<&T> for AverageTradeSize {
+    type Output = f32;
+
+    fn next(&mut self, input: &T) -> Self::Output {
+        if self.window.len() == self.period {
+            if let Some((quote_volume, trades)) = self.window.pop_front() {
+                self.quote_volume -= quote_volume;
+                self.trades -= trades;
+            }
+        }
+        self.window.push_back((input.quote_volume(), input.trades()));
+        self.quote_volume += input.quote_volume();
+        self.trades += input.trades();
+
+        self.current_value = if self.trades > 0 { self.quote_volume.max(0.0) / self.trades as f32 } else { 0.0 };
+        self.current_value
+    }
+}
+
+impl<T: QuoteVolume + Trades> Indicator<&T> for AverageTradeSize {
+    fn value(&self) -> f32 {
+        self.current_value
+    }
+
+    fn is_ready(&self) -> bool {
+        self.window.len() == self.period
+    }
+
+    fn warmup_period(&self) -> usize {
+        self.period
+    }
+}
+
+impl Reset for AverageTradeSize {
+    fn reset(&mut self) {
+        self.reset();
+    }
+}
+
+impl fmt::Display for AverageTradeSize {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "TRADESIZE({})", self.period)
+    }
+}
+
+/// Volume traded at each price of the last `period` candles, split in `bins` levels between their lowest low and highest high.
+/// The volume of a candle is spread evenly over its range
+pub struct VolumeProfile {
+    period: usize,
+    bins: usize,
+    value_area: f32,
+    highs: RollingWindow,
+    lows: RollingWindow,
+    volumes: RollingWindow,
+    current: VolumeProfileOutput,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct VolumeProfileOutput {
+    /// middle of the level with the most volume
+    pub point_of_control: f32,
+    /// top of the levels around the point of control holding the value area share of the volume
+    pub value_area_high: f32,
+    /// bottom of the levels around the point of control holding the value area share of the volume
+    pub value_area_low: f32,
+}
+
+impl VolumeProfile {
+    pub fn new(period: usize, bins: usize) -> Self {
+        Self {
+            period: period.max(1),
+            bins: bins.max(1),
+            value_area: 0.7,
+            highs: RollingWindow::new(period),
+            lows: RollingWindow::new(period),
+            volumes: RollingWindow::new(period),
+            current: VolumeProfileOutput::default(),
+        }
+    }
+
+    /// share of the volume inside the value area, 0.7 by default
+    pub fn value_area(mut self, value_area: f32) -> Self {
+        self.value_area = value_area.clamp(0.0, 1.0);
+        self
+    }
+
+    pub fn value(&self) -> VolumeProfileOutput {
+        self.current
+    }
+
+    pub fn reset(&mut self) {
+        self.highs.clear();
+        self.lows.clear();
+        self.volumes.clear();
+        self.current = VolumeProfileOutput::default();
+    }
+
+    // volume of each level between `low` and `low + bins * size`
+    fn profile(&self, low: f32, size: f32) -> Vec<f32> {
+        let mut profile = vec![0.0; self.bins];
+        let candles = self.highs.iter().zip(self.lows.iter()).zip(self.volumes.iter());
+        for ((high, low_price), volume) in candles {
+            let position = |price: f32| (((price - low) / size) as usize).min(self.bins - 1);
+            let (first, last) = (position(low_price), position(high));
+            if high <= low_price {
+                profile[first] += volume;
+                continue;
+            }
+            // the share of each level is the part of the candle range it covers
+            for (bin, level_volume) in profile.iter_mut().enumerate().take(last + 1).skip(first) {
+                let bottom = (low + bin as f32 * size).max(low_price);
+                let top = (low + (bin + 1) as f32 * size).min(high);
+                *level_volume += volume * (top - bottom).max(0.0) / (high - low_price);
+            }
+        }
+        profile
+    }
+}
+
+impl<T: High + Low + Volume> Next<&T> for VolumeProfile {
+    type Output = VolumeProfileOutput;
+
+    fn next(&mut self, input: &T) -> Self::Output {
+        self.highs.push(input.high());
+        self.lows.push(input.low());
+        self.volumes.push(input.volume());
+
+        let high = self.highs.iter().fold(f32::MIN, f32::max);
+        let low = self.lows.iter().fold(f32::MAX, f32::min);
+        if high <= low {
+            self.current = VolumeProfileOutput { point_of_control: low, value_area_high: low, value_area_low: low };
+            return self.current;
+        }
+
+        let size = (high - low) / self.bins as f32;
+        let profile = self.profile(low, size);
+        let total: f32 = profile.iter().sum();
+        let control = profile.iter().enumerate().fold(0, |best, (bin, volume)| if *volume > profile[best] { bin } else { best });
+
+        // the value area grows from the point of control towards the neighbour level with more volume
+        let (mut bottom, mut top) = (control, control);
+        let mut volume = profile[control];
+        while volume < total * self.value_area && (bottom > 0 || top < self.bins - 1) {
+            let below = if bottom > 0 { profile[bottom - 1] } else { f32::MIN };
+            let above = if top < self.bins - 1 { profile[top + 1] } else { f32::MIN };
+            if above >= below {
+                top += 1;
+                volume += above;
+            } else {
+                bottom -= 1;
+                volume += below;
+            }
+        }
+
+        self.current = VolumeProfileOutput {
+            point_of_control: low + (control as f32 + 0.5) * size,
+            value_area_high: low + (top + 1) as f32 * size,
+            value_area_low: low + bottom as f32 * size,
+        };
+        self.current
+    }
+}
+
+impl<T: High + Low + Volume> Indicator<&T> for VolumeProfile {
+    fn value(&self) -> VolumeProfileOutput {
+        self.current
+    }
+
+    fn is_ready(&self) -> bool {
+        self.volumes.is_full()
+    }
+
+    fn warmup_period(&self) -> usize {
+        self.period
+    }
+}
+
+impl Period for VolumeProfile {
+    fn period(&self) -> usize {
+        self.period
+    }
+}
+
+impl Reset for VolumeProfile {
+    fn reset(&mut self) {
+        self.reset();
+    }
+}
+
+impl fmt::Display for VolumeProfile {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "VPROFILE({}, {})", self.period, self.bins)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::candlestick::Candlestick;
+
+    fn candle(high: f32, low: f32, volume: f32) -> Candlestick {
+        Candlestick::new().high(high).low(low).close((high + low) / 2.0).volume(volume)
+    }
+
+    #[test]
+    fn test_relative_volume() {
+        let mut relative_volume = RelativeVolume::new(3);
+        assert_eq!(relative_volume.next(&candle(2.0, 1.0, 100.0)), 1.0);
+        assert_eq!(relative_volume.next(&candle(2.0, 1.0, 100.0)), 1.0);
+        assert_eq!(relative_volume.next(&candle(2.0, 1.0, 400.0)), 2.0);
+        assert!(Indicator::<&Candlestick>::is_ready(&relative_volume));
+        assert_eq!(format!("{}", relative_volume), "RVOL(3)");
+    }
+
+    #[test]
+    fn test_average_trade_size() {
+        let mut trade_size = AverageTradeSize::new(2);
+        let trades = |quote_volume: f32, trades: u64| Candlestick::new().quote_asset_volume(quote_volume).num_trades(trades);
+        assert_eq!(trade_size.next(&trades(0.0, 0)), 0.0);
+        assert_eq!(trade_size.next(&trades(1000.0, 10)), 100.0);
+        assert_eq!(trade_size.next(&trades(2000.0, 10)), 150.0);
+        assert_eq!(trade_size.next(&trades(1000.0, 30)), 75.0);
+    }
+
+    #[test]
+    fn test_volume_profile() {
+        // most of the volume was traded between 10 and 11
+        let mut profile = VolumeProfile::new(3, 4).value_area(0.7);
+        profile.next(&candle(12.0, 8.0, 40.0));
+        profile.next(&candle(11.0, 10.0, 100.0));
+        let output = profile.next(&candle(10.0, 9.0, 20.0));
+        assert_eq!(output.point_of_control, 10.5);
+        // levels of 1.0 with volumes of 10, 30, 110 and 10, the value area takes the 30 below the control
+        assert_eq!(output.value_area_low, 9.0);
+        assert_eq!(output.value_area_high, 11.0);
+        assert!(Indicator::<&Candlestick>::is_ready(&profile));
+        assert_eq!(format!("{}", profile), "VPROFILE(3, 4)");
+    }
+
+    #[test]
+    fn test_volume_profile_of_a_flat_market() {
+        let mut profile = VolumeProfile::new(2, 10);
+        let output = profile.next(&candle(5.0, 5.0, 10.0));
+        assert_eq!(output, VolumeProfileOutput { point_of_control: 5.0, value_area_high: 5.0, value_area_low: 5.0 });
+    }
+}
@@ -0,0 +1,255 @@
+use crate::candlestick::Candlestick;
+use crate::indicators::traits::{Indicator, Next, Reset};
+use crate::indicators::price_source::PriceSource;
+use std::fmt;
+
+/// Average of the typical price (high + low + close) / 3, or any other price source, weighted by the volume of each candle.
+/// It accumulates since the last reset, or since the start of the current session when a session length is set
+pub struct VolumeWeightedAveragePrice {
+    source: PriceSource,
+    session_milliseconds: Option<u64>,
+    session: u64,
+    volume: f32,
+    price_volume: f32,
+    squared_price_volume: f32,
+    current_value: f32,
+}
+
+impl VolumeWeightedAveragePrice {
+    pub fn new() -> Self {
+        Self {
+            source: PriceSource::Hlc3,
+            session_milliseconds: None,
+            session: 0,
+            volume: 0.0,
+            price_volume: 0.0,
+            squared_price_volume: 0.0,
+            current_value: 0.0,
+        }
+    }
+
+    /// price averaged instead of the typical price
+    pub fn source(mut self, source: PriceSource) -> Self {
+        self.source = source;
+        self
+    }
+
+    // restarts the average on every session of the given length, sessions start at multiples of the length since the unix epoch
+    pub fn session(mut self, session_milliseconds: u64) -> Self {
+        self.session_milliseconds = Some(session_milliseconds.max(1));
+        self
+    }
+
+    // restarts the average every day at 00:00 UTC
+    pub fn daily() -> Self {
+        Self::new().session(24 * 60 * 60 * 1000)
+    }
+
+    pub fn value(&self) -> f32 {
+        self.current_value
+    }
+
+    /// standard deviation of the prices around the average, weighted by the volume like the average
+    pub fn deviation(&self) -> f32 {
+        if self.volume > 0.0 {
+            (self.squared_price_volume / self.volume - self.current_value.powi(2)).max(0.0).sqrt()
+        } else {
+            0.0
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.session = 0;
+        self.volume = 0.0;
+        self.price_volume = 0.0;
+        self.squared_price_volume = 0.0;
+        self.current_value = 0.0;
+    }
+}
+
+impl Default for VolumeWeightedAveragePrice {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Next<&Candlestick> for VolumeWeightedAveragePrice {
+    type Output = f32;
+
+    fn next(&mut self, candle: &Candlestick) -> Self::Output {
+        if let Some(length) = self.session_milliseconds {
+            let session = candle.open_time / length;
+            if session != self.session {
+                self.session = session;
+                self.volume = 0.0;
+                self.price_volume = 0.0;
+                self.squared_price_volume = 0.0;
+            }
+        }
+
+        let price = self.source.of(candle);
+        self.volume += candle.volume;
+        self.price_volume += price * candle.volume;
+        self.squared_price_volume += price * price * candle.volume;
+        // without any volume the price itself is the best estimate
+        self.current_value = if self.volume > 0.0 { self.price_volume / self.volume } else { price };
+        self.current_value
+    }
+}
+
+impl Indicator<&Candlestick> for VolumeWeightedAveragePrice {
+    fn value(&self) -> f32 {
+        self.current_value
+    }
+
+    fn is_ready(&self) -> bool {
+        // the average is weighted by the volume, so it needs some volume in the session
+        self.volume > 0.0
+    }
+
+    fn warmup_period(&self) -> usize {
+        1
+    }
+}
+
+impl Reset for VolumeWeightedAveragePrice {
+    fn reset(&mut self) {
+        self.reset();
+    }
+}
+
+impl fmt::Display for VolumeWeightedAveragePrice {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.source {
+            PriceSource::Hlc3 => write!(f, "VWAP"),
+            source => write!(f, "VWAP[{}]", source),
+        }
+    }
+}
+
+/// Vwap with bands `multiplier` volume weighted standard deviations above and below it
+pub struct VolumeWeightedAveragePriceBands {
+    vwap: VolumeWeightedAveragePrice,
+    multiplier: f32,
+    current: VolumeWeightedAveragePriceBandsOutput,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct VolumeWeightedAveragePriceBandsOutput {
+    pub average: f32,
+    pub upper: f32,
+    pub lower: f32,
+}
+
+impl VolumeWeightedAveragePriceBands {
+    /// bands of `vwap`, so the bands follow its source and sessions
+    pub fn new(vwap: VolumeWeightedAveragePrice, multiplier: f32) -> Self {
+        Self {
+            vwap,
+            multiplier,
+            current: VolumeWeightedAveragePriceBandsOutput::default(),
+        }
+    }
+
+    pub fn value(&self) -> VolumeWeightedAveragePriceBandsOutput {
+        self.current
+    }
+
+    pub fn reset(&mut self) {
+        self.vwap.reset();
+        self.current = VolumeWeightedAveragePriceBandsOutput::default();
+    }
+}
+
+impl Next<&Candlestick> for VolumeWeightedAveragePriceBands {
+    type Output = VolumeWeightedAveragePriceBandsOutput;
+
+    fn next(&mut self, candle: &Candlestick) -> Self::Output {
+        let average = self.vwap.next(candle);
+        let deviation = self.vwap.deviation() * self.multiplier;
+        self.current = VolumeWeightedAveragePriceBandsOutput {
+            average,
+            upper: average + deviation,
+            lower: average - deviation,
+        };
+        self.current
+    }
+}
+
+impl Indicator<&Candlestick> for VolumeWeightedAveragePriceBands {
+    fn value(&self) -> VolumeWeightedAveragePriceBandsOutput {
+        self.current
+    }
+
+    fn is_ready(&self) -> bool {
+        self.vwap.is_ready()
+    }
+
+    fn warmup_period(&self) -> usize {
+        self.vwap.warmup_period()
+    }
+}
+
+impl Reset for VolumeWeightedAveragePriceBands {
+    fn reset(&mut self) {
+        self.reset();
+    }
+}
+
+impl fmt::Display for VolumeWeightedAveragePriceBands {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}({})", self.vwap, self.multiplier)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candle(open_time: u64, high: f32, low: f32, close: f32, volume: f32) -> Candlestick {
+        let mut candle = Candlestick::new().high(high).low(low).close(close).volume(volume);
+        candle.open_time = open_time;
+        candle
+    }
+
+    #[test]
+    fn test_next() {
+        let mut vwap = VolumeWeightedAveragePrice::new();
+        assert_eq!(vwap.next(&candle(0, 12.0, 6.0, 9.0, 100.0)), 9.0);
+        assert_eq!(vwap.next(&candle(1, 15.0, 9.0, 12.0, 300.0)), 11.25);
+        assert_eq!(vwap.next(&candle(2, 10.0, 10.0, 10.0, 0.0)), 11.25);
+    }
+
+    #[test]
+    fn test_daily_session() {
+        let day = 24 * 60 * 60 * 1000;
+        let mut vwap = VolumeWeightedAveragePrice::daily();
+        vwap.next(&candle(day - 1, 12.0, 6.0, 9.0, 100.0));
+        assert_eq!(vwap.next(&candle(day, 15.0, 9.0, 12.0, 300.0)), 12.0);
+    }
+
+    #[test]
+    fn test_source() {
+        let mut vwap = VolumeWeightedAveragePrice::new().source(PriceSource::Close);
+        vwap.next(&candle(0, 12.0, 6.0, 8.0, 100.0));
+        assert_eq!(vwap.next(&candle(1, 15.0, 9.0, 12.0, 300.0)), 11.0);
+    }
+
+    #[test]
+    fn test_bands() {
+        let mut bands = VolumeWeightedAveragePriceBands::new(VolumeWeightedAveragePrice::new().source(PriceSource::Close), 2.0);
+        bands.next(&candle(0, 12.0, 6.0, 8.0, 100.0));
+        // prices 8 and 12 with a weight of 1 and 3, a mean of 11 and a variance of (9 + 3) / 4
+        let output = bands.next(&candle(1, 15.0, 9.0, 12.0, 300.0));
+        assert_eq!(output.average, 11.0);
+        assert!((output.upper - (11.0 + 2.0 * 3.0_f32.sqrt())).abs() < 1e-4);
+        assert!((output.lower - (11.0 - 2.0 * 3.0_f32.sqrt())).abs() < 1e-4);
+        assert_eq!(format!("{}", bands), "VWAP[close](2)");
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(format!("{}", VolumeWeightedAveragePrice::new()), "VWAP");
+        assert_eq!(format!("{}", VolumeWeightedAveragePrice::new().source(PriceSource::Hl2)), "VWAP[hl2]");
+    }
+}
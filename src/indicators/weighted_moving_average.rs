@@ -0,0 +1,109 @@
+use crate::candlestick::Close;
+use crate::indicators::traits::{Indicator, MovingAverage, Next, Period, Reset};
+use std::collections::VecDeque;
+use std::fmt;
+
+/// Moving average where the weight of each value decreases linearly with its age, the newest value has weight `period`
+pub struct WeightedMovingAverage {
+    period: usize,
+    window: VecDeque<f32>,
+    current_value: f32,
+}
+
+impl WeightedMovingAverage {
+    pub fn new(period: usize) -> Self {
+        Self {
+            period: period.max(1),
+            window: VecDeque::with_capacity(period.max(1)),
+            current_value: 0.0,
+        }
+    }
+
+    pub fn value(&self) -> f32 {
+        self.current_value
+    }
+
+    pub fn reset(&mut self) {
+        self.window.clear();
+        self.current_value = 0.0;
+    }
+}
+
+impl Period for WeightedMovingAverage {
+    fn period(&self) -> usize {
+        self.period
+    }
+}
+
+impl Next<f32> for WeightedMovingAverage {
+    type Output = f32;
+
+    fn next(&mut self, input: f32) -> Self::Output {
+        if self.window.len() == self.period {
+            self.window.pop_front();
+        }
+        self.window.push_back(input);
+
+        // until the window is full only the values received are weighted
+        let weighted_sum: f32 = self.window.iter().enumerate().map(|(i, value)| (i + 1) as f32 * value).sum();
+        let count = self.window.len() as f32;
+        self.current_value = weighted_sum / (count * (count + 1.0) / 2.0);
+        self.current_value
+    }
+}
+
+impl<T: Close> Next<&T> for WeightedMovingAverage {
+    type Output = f32;
+
+    fn next(&mut self, input: &T) -> Self::Output {
+        self.next(input.close())
+    }
+}
+
+impl Indicator<f32> for WeightedMovingAverage {
+    fn value(&self) -> f32 {
+        self.current_value
+    }
+
+    fn is_ready(&self) -> bool {
+        self.window.len() == self.period
+    }
+
+    fn warmup_period(&self) -> usize {
+        self.period
+    }
+}
+
+impl MovingAverage for WeightedMovingAverage {}
+
+impl Reset for WeightedMovingAverage {
+    fn reset(&mut self) {
+        self.reset();
+    }
+}
+
+impl fmt::Display for WeightedMovingAverage {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "WMA({})", self.period)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::candlestick::Candlestick;
+
+    #[test]
+    fn test_next() {
+        let mut wma = WeightedMovingAverage::new(3);
+        assert_eq!(wma.next(2.0), 2.0);
+        assert_eq!(wma.next(5.0), 4.0);
+        assert_eq!(wma.next(1.0), 2.5);
+        assert_eq!((wma.next(&Candlestick::new().close(8.0)) * 100.0).round() / 100.0, 5.17);
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(format!("{}", WeightedMovingAverage::new(9)), "WMA(9)");
+    }
+}
@@ -0,0 +1,126 @@
+use crate::candlestick::Close;
+use crate::indicators::traits::{Indicator, MovingAverage, Next, Period, Reset};
+use std::fmt;
+
+/// Moving average of Welles Wilder (RMA or SMMA), an exponential average with alpha = 1 / period
+/// used by the original RSI, ATR and ADX. Like the EMA it starts on the first value, unless it is seeded
+/// with the simple average of the first `period` values as TA-Lib and TradingView do
+pub struct WilderMovingAverage {
+    period: usize,
+    seeded: bool,
+    current: Option<f32>,
+    count: usize, // inputs received since the last reset
+}
+
+impl WilderMovingAverage {
+    pub fn new(period: usize) -> Self {
+        Self {
+            period: period.max(1),
+            seeded: false,
+            current: None,
+            count: 0,
+        }
+    }
+
+    /// averages the first `period` values before smoothing them, until then the value is the average of the values received
+    pub fn seeded(mut self) -> Self {
+        self.seeded = true;
+        self
+    }
+
+    pub fn value(&self) -> f32 {
+        self.current.unwrap_or(0.0)
+    }
+
+    pub fn reset(&mut self) {
+        self.current = None;
+        self.count = 0;
+    }
+}
+
+impl Period for WilderMovingAverage {
+    fn period(&self) -> usize {
+        self.period
+    }
+}
+
+impl Next<f32> for WilderMovingAverage {
+    type Output = f32;
+
+    fn next(&mut self, input: f32) -> Self::Output {
+        self.count += 1;
+        // a running mean of the first values is the simple average once `period` values were received
+        let weight = if self.seeded { self.count.min(self.period) } else { self.period };
+        let value = match self.current {
+            Some(current) => current + (input - current) / weight as f32,
+            None => input,
+        };
+        self.current = Some(value);
+        value
+    }
+}
+
+impl<T: Close> Next<&T> for WilderMovingAverage {
+    type Output = f32;
+
+    fn next(&mut self, input: &T) -> Self::Output {
+        self.next(input.close())
+    }
+}
+
+impl Indicator<f32> for WilderMovingAverage {
+    fn value(&self) -> f32 {
+        self.value()
+    }
+
+    fn is_ready(&self) -> bool {
+        self.count >= self.warmup_period()
+    }
+
+    fn warmup_period(&self) -> usize {
+        self.period
+    }
+}
+
+impl MovingAverage for WilderMovingAverage {}
+
+impl Reset for WilderMovingAverage {
+    fn reset(&mut self) {
+        self.reset();
+    }
+}
+
+impl fmt::Display for WilderMovingAverage {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "RMA({})", self.period)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_next() {
+        let mut rma = WilderMovingAverage::new(4);
+        assert_eq!(rma.next(2.0), 2.0);
+        assert_eq!(rma.next(6.0), 3.0);
+        assert_eq!(rma.next(1.0), 2.5);
+        rma.reset();
+        assert_eq!(rma.next(5.0), 5.0);
+    }
+
+    #[test]
+    fn test_seeded() {
+        let mut rma = WilderMovingAverage::new(3).seeded();
+        assert_eq!(rma.next(2.0), 2.0);
+        assert_eq!(rma.next(4.0), 3.0);
+        assert_eq!(rma.next(9.0), 5.0);
+        assert_eq!(rma.next(8.0), 6.0);
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(format!("{}", WilderMovingAverage::new(14)), "RMA(14)");
+    }
+}
@@ -0,0 +1,106 @@
+use crate::candlestick::{Close, High, Low};
+use crate::indicators::traits::{Indicator, Next, Period, Reset};
+use crate::indicators::highest_lowest::{Highest, Lowest};
+use std::fmt;
+
+/// Williams %R, distance of the close from the highest high of the period, from -100 (at the lowest low) to 0
+pub struct WilliamsR {
+    highest: Highest,
+    lowest: Lowest,
+    current_value: f32,
+}
+
+impl WilliamsR {
+    pub fn new(period: usize) -> Self {
+        Self {
+            highest: Highest::new(period),
+            lowest: Lowest::new(period),
+            current_value: 0.0,
+        }
+    }
+
+    pub fn value(&self) -> f32 {
+        self.current_value
+    }
+
+    pub fn reset(&mut self) {
+        self.highest.reset();
+        self.lowest.reset();
+        self.current_value = 0.0;
+    }
+
+    fn next_values(&mut self, high: f32, low: f32, close: f32) -> f32 {
+        let highest = self.highest.next(high);
+        let lowest = self.lowest.next(low);
+        self.current_value = if highest > lowest { -100.0 * (highest - close) / (highest - lowest) } else { -50.0 };
+        self.current_value
+    }
+}
+
+impl Period for WilliamsR {
+    fn period(&self) -> usize {
+        self.highest.period()
+    }
+}
+
+impl Next<f32> for WilliamsR {
+    type Output = f32;
+
+    fn next(&mut self, input: f32) -> Self::Output {
+        self.next_values(input, input, input)
+    }
+}
+
+impl<T: High + Low + Close> Next<&T> for WilliamsR {
+    type Output = f32;
+
+    fn next(&mut self, input: &T) -> Self::Output {
+        self.next_values(input.high(), input.low(), input.close())
+    }
+}
+
+impl<T: High + Low + Close> Indicator<&T> for WilliamsR {
+    fn value(&self) -> f32 {
+        self.current_value
+    }
+
+    fn is_ready(&self) -> bool {
+        self.highest.is_ready()
+    }
+
+    fn warmup_period(&self) -> usize {
+        self.highest.period()
+    }
+}
+
+impl Reset for WilliamsR {
+    fn reset(&mut self) {
+        self.reset();
+    }
+}
+
+impl fmt::Display for WilliamsR {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "WILLR({})", self.highest.period())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::candlestick::Candlestick;
+
+    #[test]
+    fn test_next() {
+        let mut willr = WilliamsR::new(2);
+        assert_eq!(willr.next(&Candlestick::new().high(10.0).low(6.0).close(9.0)), -25.0);
+        assert_eq!(willr.next(&Candlestick::new().high(12.0).low(9.0).close(7.0)), -83.333336);
+        assert_eq!(willr.next(&Candlestick::new().high(11.0).low(10.0).close(11.0)), -33.333332);
+        assert_eq!(willr.next(4.0), -100.0);
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(format!("{}", WilliamsR::new(14)), "WILLR(14)");
+    }
+}
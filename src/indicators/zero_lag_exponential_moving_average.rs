@@ -0,0 +1,113 @@
+use crate::candlestick::Close;
+use crate::indicators::traits::{Indicator, MovingAverage, Next, Period, Reset};
+use crate::indicators::exponential_moving_average::ExponentialMovingAverage as Ema;
+use std::collections::VecDeque;
+use std::fmt;
+
+/// Zero lag exponential moving average, the EMA of `2 * price - price (period - 1) / 2 values ago`
+pub struct ZeroLagExponentialMovingAverage {
+    ema: Ema,
+    lag: usize,
+    history: VecDeque<f32>, // last `lag` values
+    current_value: f32,
+    count: usize, // inputs received since the last reset
+}
+
+impl ZeroLagExponentialMovingAverage {
+    pub fn new(period: usize) -> Self {
+        let lag = period.saturating_sub(1) / 2;
+        Self {
+            ema: Ema::new(period),
+            lag,
+            history: VecDeque::with_capacity(lag + 1),
+            current_value: 0.0,
+            count: 0,
+        }
+    }
+
+    pub fn value(&self) -> f32 {
+        self.current_value
+    }
+
+    pub fn reset(&mut self) {
+        self.ema.reset();
+        self.history.clear();
+        self.current_value = 0.0;
+        self.count = 0;
+    }
+}
+
+impl Period for ZeroLagExponentialMovingAverage {
+    fn period(&self) -> usize {
+        self.ema.period()
+    }
+}
+
+impl Next<f32> for ZeroLagExponentialMovingAverage {
+    type Output = f32;
+
+    fn next(&mut self, input: f32) -> Self::Output {
+        self.count += 1;
+        self.history.push_back(input);
+        // until enough values were received the oldest one is used
+        let lagged = if self.history.len() > self.lag { self.history.pop_front().unwrap() } else { self.history[0] };
+        self.current_value = self.ema.next(2.0 * input - lagged);
+        self.current_value
+    }
+}
+
+impl<T: Close> Next<&T> for ZeroLagExponentialMovingAverage {
+    type Output = f32;
+
+    fn next(&mut self, input: &T) -> Self::Output {
+        self.next(input.close())
+    }
+}
+
+impl Indicator<f32> for ZeroLagExponentialMovingAverage {
+    fn value(&self) -> f32 {
+        self.current_value
+    }
+
+    fn is_ready(&self) -> bool {
+        self.count >= self.warmup_period()
+    }
+
+    fn warmup_period(&self) -> usize {
+        self.ema.period() + self.lag
+    }
+}
+
+impl MovingAverage for ZeroLagExponentialMovingAverage {}
+
+impl Reset for ZeroLagExponentialMovingAverage {
+    fn reset(&mut self) {
+        self.reset();
+    }
+}
+
+impl fmt::Display for ZeroLagExponentialMovingAverage {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "ZLEMA({})", self.ema.period())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_next() {
+        // lag of 1 value
+        let mut zlema = ZeroLagExponentialMovingAverage::new(3);
+        assert_eq!(zlema.next(2.0), 2.0);
+        assert_eq!(zlema.next(5.0), 5.0);
+        assert_eq!(zlema.next(1.0), 1.0);
+        assert_eq!(zlema.next(6.25), 6.25);
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(format!("{}", ZeroLagExponentialMovingAverage::new(9)), "ZLEMA(9)");
+    }
+}
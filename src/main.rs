@@ -3,11 +3,17 @@ pub mod candlestick;
 pub mod indicators;
 pub mod backtest;
 pub mod brkga;
+pub mod genetic_programming;
+pub mod paper_trading;
+pub mod exchange;
+pub mod rules;
 mod utils;
 
 use candlestick::Candlestick;
 use backtest::{Backtest, RunMode};
+use backtest::strategy::{DirectionMode, SingleStrategy};
 use brkga::{BRKGA, BrkgaConfig, FitnessExecutor};
+use genetic_programming::{GeneticProgramming, GpConfig, RuleFitness};
 
 fn main() {
     let csv_path = "scripts/data_collector/ETHUSDT-5m.csv";
@@ -22,20 +28,52 @@ fn main() {
     println!("found {} candles inside {}", candles.len(), csv_path);
 
     let seed: u64 = 18988547;
+
+    // `--gp` evolves the entry rules themselves instead of tuning the thresholds of the fixed rule
+    if std::env::args().any(|argument| argument == "--gp") {
+        run_rule_search(candles, seed, GpConfig::default());
+        return;
+    }
+
     let frac_bot: f32 = 0.3;
     let frac_top: f32 = 0.1;
     let pop_size : usize = 10000;
     let max_iter: usize = 100;
     let elit_rate : f32 = 0.6;
 
-    run_experiment(candles, seed, (frac_top, frac_bot, pop_size,  max_iter, elit_rate));
+    // `--long-only` and `--short-only` only optimize the rule of one direction, `--symmetric` a rule mirrored for shorts
+    let direction_mode = std::env::args().find_map(|argument| match argument.as_str() {
+        "--long-only" => Some(DirectionMode::LongOnly),
+        "--short-only" => Some(DirectionMode::ShortOnly),
+        "--symmetric" => Some(DirectionMode::Symmetric),
+        _ => None,
+    }).unwrap_or_default();
+    // `--legacy-rules` reproduces the first experiments: no structure genes and the rules of `RuleStructure::legacy`
+    let legacy_rules = std::env::args().any(|argument| argument == "--legacy-rules");
+
+    run_experiment(candles, seed, (frac_top, frac_bot, pop_size,  max_iter, elit_rate), direction_mode, legacy_rules);
 }
 
-fn run_experiment(candles: Vec<Candlestick>, seed: u64, config: BrkgaConfig) {
+fn run_experiment(candles: Vec<Candlestick>, seed: u64, config: BrkgaConfig, direction_mode: DirectionMode, legacy_rules: bool) {
     println!("Running backtest with {} divisions", 12);
     
     let backtest_engine = Backtest::new(candles, 12, 0.005, 0.02);
-    let mut brkga = BRKGA::new(seed, 36, config, 
-        FitnessExecutor::new(backtest_engine, RunMode::Training));
+    let mut fitness_executor = FitnessExecutor::new(backtest_engine, RunMode::Training).direction_mode(direction_mode);
+    if legacy_rules {
+        fitness_executor = fitness_executor.legacy_structure();
+    }
+    let mut brkga = BRKGA::new(seed, SingleStrategy::schema(direction_mode, !legacy_rules, false).size(), config, fitness_executor);
     brkga.run();
+}
+
+fn run_rule_search(candles: Vec<Candlestick>, seed: u64, config: GpConfig) {
+    println!("Running backtest with {} divisions", 12);
+
+    let backtest_engine = Backtest::new(candles, 12, 0.005, 0.02);
+    let mut search = GeneticProgramming::new(seed, config, RuleFitness::new(backtest_engine, RunMode::Training));
+    let best = search.run();
+    println!("best long entry: {}", best.long_entry);
+    if let Some(short_entry) = best.short_entry {
+        println!("best short entry: {}", short_entry);
+    }
 }
\ No newline at end of file
@@ -0,0 +1,287 @@
+pub mod candle_source;
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use serde::{Serialize, Deserialize};
+
+use crate::candlestick::Candlestick;
+use crate::backtest::BacktestReport;
+use crate::backtest::engine::{Engine, EngineConfig, Observer};
+use crate::backtest::position_manager::PositionManager;
+use crate::backtest::strategy::{SingleStrategy, TradingStrategy};
+use candle_source::CandleSource;
+
+// snapshot of a paper trading session, rewritten after every candle. The candles themselves are appended to a journal
+// next to it, so the snapshot keeps the same size however long the session runs
+#[derive(Serialize, Deserialize)]
+struct PaperState {
+    cromossome: Vec<f32>,
+    warm_up_candles: usize, // the first candles only initialize the strategy, like the warm-up candles of a backtest
+    candles: usize,
+    last_open_time: Option<u64>,
+    journal_length: u64, // bytes of the journal holding the candles of the snapshot, later ones are from a crash
+    balance: f32,        // balance after the last candle, the replayed session must reach the same one
+}
+
+/// Runs a decoded `SingleStrategy` forward on candlesticks received one at a time, with orders simulated
+/// by the same `Engine` used by the backtest.
+///
+/// The session is saved as a snapshot of the cromossome and the balance plus a journal with one json line per candle
+/// received, resuming it replays the journal through a new engine to rebuild the same balance, open trades and pending orders
+pub struct PaperTrader<'o> {
+    strategy: SingleStrategy,
+    engine: Engine<'o>,
+    state: PaperState,
+    state_path: Option<PathBuf>, // file where the session is saved after every candle
+    unsaved: Vec<Candlestick>,   // candles processed but not appended to the journal yet
+}
+
+impl<'o> PaperTrader<'o> {
+    /// starts a session that only lives in memory
+    pub fn new(cromossome: &[f32], config: EngineConfig) -> Self {
+        let engine = Engine::new(config);
+        Self {
+            strategy: SingleStrategy::decode(cromossome),
+            state: PaperState {
+                cromossome: cromossome.to_vec(),
+                warm_up_candles: 0,
+                candles: 0,
+                last_open_time: None,
+                journal_length: 0,
+                balance: engine.balance(),
+            },
+            engine,
+            state_path: None,
+            unsaved: Vec::new(),
+        }
+    }
+
+    /// resumes the session saved at `path`, or starts a new one that will be saved there
+    pub fn resume<P: AsRef<Path>>(path: P, cromossome: &[f32], config: EngineConfig) -> io::Result<Self> {
+        let mut trader = Self::new(cromossome, config);
+        trader.state_path = Some(path.as_ref().to_path_buf());
+        let journal_path = journal_path(path.as_ref());
+        if !path.as_ref().exists() {
+            // a journal without a snapshot only holds candles of a session that never saved one
+            if journal_path.exists() {
+                File::create(&journal_path)?;
+            }
+            return Ok(trader);
+        }
+
+        let saved: PaperState = serde_json::from_reader(BufReader::new(File::open(path)?))?;
+        if saved.cromossome != cromossome {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "the saved session was started by a different cromossome"));
+        }
+        // the journal is only written with the first candle, a session saved before any has none
+        let journal = OpenOptions::new().create(true).truncate(false).write(true).open(&journal_path)?;
+        if journal.metadata()?.len() < saved.journal_length {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "the journal of the saved session is missing candles"));
+        }
+        journal.set_len(saved.journal_length)?;
+
+        for (i, line) in BufReader::new(File::open(&journal_path)?).lines().enumerate() {
+            let candle: Candlestick = serde_json::from_str(&line?)?;
+            if i < saved.warm_up_candles {
+                trader.warm_up_candle(candle);
+            } else {
+                trader.trade_candle(candle);
+            }
+        }
+        trader.unsaved.clear();
+        trader.state.journal_length = saved.journal_length;
+        if trader.state.candles != saved.candles || trader.state.balance != saved.balance {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "replaying the journal doesn't rebuild the saved session"));
+        }
+        Ok(trader)
+    }
+
+    /// observers only receive the events of candles processed after they were added, not the ones replayed by `resume`
+    pub fn add_observer(&mut self, observer: &'o mut dyn Observer) {
+        self.engine.add_observer(observer);
+    }
+
+    /// initializes the indicators of the strategy with historical candles, only possible before the first trading candle
+    pub fn warm_up(&mut self, candles: Vec<Candlestick>) -> io::Result<()> {
+        if self.state.candles > self.state.warm_up_candles {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "the session already started trading"));
+        }
+        for candle in candles {
+            if self.is_new(&candle) {
+                self.warm_up_candle(candle);
+            }
+        }
+        self.save()
+    }
+
+    /// processes a new candlestick and saves the session, returns false for candles older than the last one received,
+    /// e.g. the rows a restarted file tail reads again
+    pub fn on_candle(&mut self, candle: Candlestick) -> io::Result<bool> {
+        if !self.is_new(&candle) {
+            return Ok(false);
+        }
+        self.trade_candle(candle);
+        self.save()?;
+        Ok(true)
+    }
+
+    /// trades every candle of the source until the feed ends
+    pub fn run<C: CandleSource>(&mut self, source: &mut C) -> io::Result<()> {
+        while let Some(candle) = source.next_candle()? {
+            self.on_candle(candle)?;
+        }
+        Ok(())
+    }
+
+    pub fn balance(&self) -> f32 {
+        self.engine.balance()
+    }
+
+    pub fn positions(&self) -> &PositionManager {
+        self.engine.positions()
+    }
+
+    pub fn report(&self) -> BacktestReport {
+        self.engine.report()
+    }
+
+    // candles must arrive ordered by their open time
+    fn is_new(&self, candle: &Candlestick) -> bool {
+        self.state.last_open_time.is_none_or(|last| candle.open_time > last)
+    }
+
+    fn warm_up_candle(&mut self, candle: Candlestick) {
+        self.strategy.new_candlestick(&candle);
+        self.state.warm_up_candles += 1;
+        self.record(candle);
+    }
+
+    fn trade_candle(&mut self, candle: Candlestick) {
+        self.engine.on_candle(&mut self.strategy, &candle);
+        self.record(candle);
+    }
+
+    fn record(&mut self, candle: Candlestick) {
+        self.state.candles += 1;
+        self.state.last_open_time = Some(candle.open_time);
+        self.state.balance = self.engine.balance();
+        if self.state_path.is_some() {
+            self.unsaved.push(candle);
+        }
+    }
+
+    // the new candles are appended to the journal before the snapshot counts them, and the snapshot is written to a
+    // temporary file first, so a crash never leaves a partially written state
+    fn save(&mut self) -> io::Result<()> {
+        let Some(path) = &self.state_path else {
+            return Ok(());
+        };
+        if !self.unsaved.is_empty() {
+            let mut journal = BufWriter::new(OpenOptions::new().create(true).append(true).open(journal_path(path))?);
+            for candle in self.unsaved.drain(..) {
+                let mut line = serde_json::to_vec(&candle)?;
+                line.push(b'\n');
+                journal.write_all(&line)?;
+                self.state.journal_length += line.len() as u64;
+            }
+            journal.flush()?;
+        }
+        let temporary_path = path.with_extension("tmp");
+        serde_json::to_writer(BufWriter::new(File::create(&temporary_path)?), &self.state)?;
+        fs::rename(temporary_path, path)
+    }
+}
+
+// candles of the session saved at `path`, e.g. `session.candles.jsonl` for `session.json`
+fn journal_path(path: &Path) -> PathBuf {
+    path.with_extension("candles.jsonl")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backtest::position_manager::PositionLimits;
+    use crate::backtest::risk_manager::RiskLimits;
+    use crate::backtest::strategy::{STRATEGY_GENES, STRUCTURE_GENES};
+    use candle_source::ReplaySource;
+
+    fn config() -> EngineConfig {
+        EngineConfig {
+            initial_usd_balance: 10_000.0,
+            slipage_percentage: 0.0,
+            fee_percentage: 0.0,
+            position_limits: PositionLimits::default(),
+            risk_limits: RiskLimits::default(),
+        }
+    }
+
+    // uptrend with oscillations, one candle every 5 minutes
+    fn candles(range: std::ops::Range<u64>) -> Vec<Candlestick> {
+        range.map(|i| {
+            let price = 100.0 * 1.01_f32.powi(i as i32) * (1.0 + 0.05 * (i as f32 / 3.0).sin());
+            let mut candle = Candlestick::new().open(price).high(price + 2.0).low(price - 2.0).close(price + 1.0);
+            candle.open_time = i * 300_000;
+            candle
+        }).collect()
+    }
+
+    #[test]
+    fn resumed_session_matches_an_uninterrupted_one() {
+        let path = std::env::temp_dir().join(format!("paper_trading_{}.json", std::process::id()));
+        // both rules only check the distance between their sma and ema: the long rule accepts any distance and has
+        // a close takeprofit, while the short rule needs a fast sma close to a slow ema, which the trend never allows
+        let mut cromossome = vec![0.5; STRATEGY_GENES + STRUCTURE_GENES];
+        cromossome[STRATEGY_GENES..].copy_from_slice(&[0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0]);
+        cromossome[0] = 0.0;
+        cromossome[1] = 0.0;
+        cromossome[15] = 1.0;
+        cromossome[28] = 1.0;
+        cromossome[29] = 1.0;
+        cromossome[31] = 0.0;
+        cromossome[32] = 0.0;
+        // plain ema and sma of the close on both rules
+        cromossome[36] = 0.15;
+        cromossome[37] = 0.0;
+        cromossome[38] = 0.15;
+        cromossome[39] = 0.0;
+        cromossome[40] = 0.4;
+        cromossome[41] = 0.4;
+
+        let mut uninterrupted = PaperTrader::new(&cromossome, config());
+        uninterrupted.warm_up(candles(0..50)).unwrap();
+        uninterrupted.run(&mut ReplaySource::new(candles(50..300))).unwrap();
+
+        // an empty warm-up saves a session without any candle in its journal
+        let mut empty = PaperTrader::resume(&path, &cromossome, config()).unwrap();
+        empty.warm_up(Vec::new()).unwrap();
+        drop(empty);
+        assert!(path.exists() && !journal_path(&path).exists());
+
+        let mut first_run = PaperTrader::resume(&path, &cromossome, config()).unwrap();
+        first_run.warm_up(candles(0..50)).unwrap();
+        first_run.run(&mut ReplaySource::new(candles(50..150))).unwrap();
+        drop(first_run);
+        let journal = journal_path(&path);
+        assert_eq!(fs::read_to_string(&journal).unwrap().lines().count(), 150);
+        assert!(!fs::read_to_string(&path).unwrap().contains("close"));
+        // a crash after appending a candle and before saving the snapshot leaves a candle the snapshot doesn't count
+        let mut crashed = OpenOptions::new().append(true).open(&journal).unwrap();
+        crashed.write_all(b"{\"open_time\":").unwrap();
+        drop(crashed);
+
+        // the restarted feed sends the last candles again
+        let mut resumed = PaperTrader::resume(&path, &cromossome, config()).unwrap();
+        assert!(!resumed.on_candle(candles(149..150).pop().unwrap()).unwrap());
+        resumed.run(&mut ReplaySource::new(candles(100..300))).unwrap();
+
+        assert_eq!(resumed.report(), uninterrupted.report());
+        assert_eq!(resumed.balance(), uninterrupted.balance());
+        assert!(uninterrupted.report().trade_count > 1);
+        assert!(resumed.warm_up(candles(0..10)).is_err());
+        assert!(PaperTrader::resume(&path, &[0.1; STRATEGY_GENES], config()).is_err());
+        assert_eq!(fs::read_to_string(&journal).unwrap().lines().count(), 300);
+        fs::remove_file(&path).unwrap();
+        fs::remove_file(&journal).unwrap();
+    }
+}
@@ -0,0 +1,163 @@
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+use std::path::Path;
+use std::sync::mpsc::Receiver;
+use std::thread;
+use std::time::Duration;
+
+use crate::candlestick::Candlestick;
+
+/// Feed of closed candlesticks received one at a time
+pub trait CandleSource {
+    /// waits until the next candlestick is available, `None` when the feed ended
+    fn next_candle(&mut self) -> io::Result<Option<Candlestick>>;
+}
+
+/// Replays candlesticks already in memory, e.g. the ones loaded with `load_candlesticks`
+pub struct ReplaySource {
+    candles: std::vec::IntoIter<Candlestick>,
+}
+
+impl ReplaySource {
+    pub fn new(candles: Vec<Candlestick>) -> Self {
+        Self { candles: candles.into_iter() }
+    }
+}
+
+impl CandleSource for ReplaySource {
+    fn next_candle(&mut self) -> io::Result<Option<Candlestick>> {
+        Ok(self.candles.next())
+    }
+}
+
+/// Candlesticks sent by another thread, e.g. a websocket client or a local mock of one.
+/// The feed ends when every sender is dropped
+pub struct ChannelSource {
+    receiver: Receiver<Candlestick>,
+}
+
+impl ChannelSource {
+    pub fn new(receiver: Receiver<Candlestick>) -> Self {
+        Self { receiver }
+    }
+}
+
+impl CandleSource for ChannelSource {
+    fn next_candle(&mut self) -> io::Result<Option<Candlestick>> {
+        Ok(self.receiver.recv().ok())
+    }
+}
+
+/// Follows a csv file with the structure read by `load_candlesticks` while new rows are appended to it
+pub struct FileTailSource {
+    reader: BufReader<File>,
+    headers: Option<csv::StringRecord>,
+    partial_line: String, // row still being written to the file
+    poll_interval: Duration,
+    follow: bool,
+}
+
+impl FileTailSource {
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        Ok(Self {
+            reader: BufReader::new(File::open(path)?),
+            headers: None,
+            partial_line: String::new(),
+            poll_interval: Duration::from_secs(1),
+            follow: true,
+        })
+    }
+
+    // time waited before looking for new rows at the end of the file
+    pub fn poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+
+    // waits for new rows at the end of the file, otherwise the feed ends with the file
+    pub fn follow(mut self, follow: bool) -> Self {
+        self.follow = follow;
+        self
+    }
+
+    fn parse_row(&mut self, line: &str) -> io::Result<Option<Candlestick>> {
+        let mut reader = csv::ReaderBuilder::new().has_headers(false).from_reader(line.as_bytes());
+        let mut record = csv::StringRecord::new();
+        if !reader.read_record(&mut record)? {
+            return Ok(None);
+        }
+
+        match &self.headers {
+            None => {
+                self.headers = Some(record);
+                Ok(None)
+            }
+            Some(headers) => Ok(Some(record.deserialize(Some(headers))?)),
+        }
+    }
+}
+
+impl CandleSource for FileTailSource {
+    fn next_candle(&mut self) -> io::Result<Option<Candlestick>> {
+        loop {
+            let read = self.reader.read_line(&mut self.partial_line)?;
+            if read == 0 || !self.partial_line.ends_with('\n') {
+                if self.follow {
+                    thread::sleep(self.poll_interval);
+                    continue;
+                }
+                if self.partial_line.is_empty() {
+                    return Ok(None);
+                }
+            }
+
+            let line = std::mem::take(&mut self.partial_line);
+            if let Some(candle) = self.parse_row(line.trim_end())? {
+                return Ok(Some(candle));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use std::sync::mpsc;
+
+    const HEADER: &str = "OpenTime,Open,High,Low,Close,Volume,CloseTime,QuoteAssetVolume,NumTrades\n";
+
+    #[test]
+    fn file_tail_reads_appended_rows() {
+        let path = std::env::temp_dir().join(format!("candle_source_tail_{}.csv", std::process::id()));
+        let mut file = File::create(&path).unwrap();
+        file.write_all(HEADER.as_bytes()).unwrap();
+        file.write_all(b"0,1.0,2.0,0.5,1.5,10.0,299999,15.0,3\n300000,1.5").unwrap();
+        file.flush().unwrap();
+
+        let mut source = FileTailSource::open(&path).unwrap().poll_interval(Duration::from_millis(1));
+        assert_eq!(source.next_candle().unwrap().unwrap().close, 1.5);
+
+        // the second row is only read once it is complete
+        file.write_all(b",2.0,1.0,1.8,12.0,599999,20.0,4\n").unwrap();
+        file.flush().unwrap();
+        let candle = source.next_candle().unwrap().unwrap();
+        assert_eq!((candle.open_time, candle.close), (300000, 1.8));
+
+        let mut source = source.follow(false);
+        assert!(source.next_candle().unwrap().is_none());
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn channel_ends_when_the_sender_is_dropped() {
+        let (sender, receiver) = mpsc::channel();
+        let mut source = ChannelSource::new(receiver);
+        thread::spawn(move || {
+            sender.send(Candlestick::new().close(10.0)).unwrap();
+        });
+
+        assert_eq!(source.next_candle().unwrap().unwrap().close, 10.0);
+        assert!(source.next_candle().unwrap().is_none());
+    }
+}
@@ -0,0 +1,34 @@
+pub mod lexer;
+pub mod parser;
+pub mod compiler;
+
+use std::fmt;
+
+/// Reasons a rule written in the rule language can't be used
+#[derive(Debug, Clone, PartialEq)]
+pub enum RuleError {
+    /// the text isn't a valid expression, `position` is the byte offset of the error in the text
+    Syntax { position: usize, message: String },
+    /// a function that isn't part of the language
+    UnknownFunction(String),
+    /// a function called with the wrong arguments
+    Argument { function: String, message: String },
+    /// a number used where a condition is expected or the opposite
+    Type(String),
+    /// a strategy configuration that can't be read
+    Config(String),
+}
+
+impl fmt::Display for RuleError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RuleError::Syntax { position, message } => write!(f, "syntax error at {}: {}", position, message),
+            RuleError::UnknownFunction(name) => write!(f, "unknown function '{}'", name),
+            RuleError::Argument { function, message } => write!(f, "invalid arguments of '{}': {}", function, message),
+            RuleError::Type(message) => write!(f, "type error: {}", message),
+            RuleError::Config(message) => write!(f, "invalid config: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for RuleError {}
@@ -0,0 +1,647 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::candlestick::Candlestick;
+use crate::indicators::traits::{Indicator, MovingAverage, Next, Reset};
+use crate::indicators::moving_average::{MovingAverageType, Smoothing};
+use crate::indicators::price_source::PriceSource;
+use crate::indicators::average_directional_index::{AverageDirectionalIndex as Adx, AverageDirectionalIndexOutput as AdxOutput};
+use crate::indicators::average_true_range::AverageTrueRange as Atr;
+use crate::indicators::bollinger_bands::{BollingerBands, BollingerBandsOutput};
+use crate::indicators::candlestick_patterns::{CandlestickPattern, PatternDetector};
+use crate::indicators::commodity_channel_index::CommodityChannelIndex as Cci;
+use crate::indicators::highest_lowest::{Highest, Lowest};
+use crate::indicators::moving_average_convergence_divergence::{
+    MovingAverageConvergenceDivergence as Macd, MovingAverageConvergenceDivergenceOutput as MacdOutput,
+};
+use crate::indicators::on_balance_volume::OnBalanceVolume as Obv;
+use crate::indicators::relative_strength_index::RelativeStrengthIndex as Rsi;
+use crate::indicators::stochastic_oscillator::{StochasticOscillator as Stochastic, StochasticOscillatorOutput};
+use crate::indicators::volume::{AverageTradeSize, RelativeVolume, VolumeProfile, VolumeProfileOutput};
+use crate::indicators::volume_weighted_average_price::{
+    VolumeWeightedAveragePrice as Vwap, VolumeWeightedAveragePriceBands as VwapBands, VolumeWeightedAveragePriceBandsOutput as VwapBandsOutput,
+};
+use crate::indicators::williams_r::WilliamsR;
+use crate::rules::parser::{Expression, Operator};
+use crate::rules::RuleError;
+
+/// Condition written in the rule language, compiled to the indicators of the crate and evaluated on every candle.
+///
+/// Numbers are prices (`open`, `high`, `low`, `close`, `hl2`, `hlc3`, `ohlc4`, `volume`), constants, indicators
+/// and arithmetic on them. Conditions compare numbers and combine with `and`, `or` and `not`:
+///
+/// - moving averages `sma`, `ema`, `wma`, `hma`, `dema`, `tema`, `kama`, `rma`, `zlema` (period[, source])
+/// - `rsi(period[, source])` and `atr(period)`, smoothed like TA-Lib and TradingView
+/// - `macd`, `macd_signal`, `macd_hist` (fast, slow, signal[, source])
+/// - `bb_upper`, `bb_lower` (period, deviations[, source]), the middle band is the `sma`
+/// - `highest(period[, source])` of the highs and `lowest(period[, source])` of the lows by default
+/// - `stoch_k`, `stoch_d` (k period, d period), `adx`, `plus_di`, `minus_di`, `cci`, `willr`, `rvol` (period)
+/// - `trade_size(period)`, the average quote volume of a trade
+/// - `poc`, `va_high`, `va_low` (period, levels), the point of control and value area of the volume profile
+/// - `obv()`, `vwap()` and its bands `vwap_upper`, `vwap_lower` (deviations)
+/// - candlestick patterns `engulfing()`, `hammer()`, `doji()`, `star()`, `inside_bar()`, `outside_bar()`, `three_soldiers()`,
+///   the strength of the pattern on the candle, positive when bullish and negative when bearish
+/// - `crosses_above(a, b)` and `crosses_below(a, b)`, true on the candle `a` goes from at or below `b` to above it, or the opposite
+///
+/// Periods are whole numbers of candles up to `MAX_PERIOD`. The same indicator used many times in a rule is computed once per candle
+pub struct Rule {
+    expression: Expression,
+    series: Vec<Series>,
+    values: Vec<f32>, // last value of each series
+    condition: Condition,
+}
+
+impl Rule {
+    pub fn parse(text: &str) -> Result<Self, RuleError> {
+        Self::compile(&Expression::parse(text)?)
+    }
+
+    /// fails when the expression is a number instead of a condition or uses functions the language doesn't have
+    pub fn compile(expression: &Expression) -> Result<Self, RuleError> {
+        let mut compiler = Compiler { series: Vec::new(), names: HashMap::new() };
+        let condition = compiler.condition(expression)?;
+        Ok(Self {
+            expression: expression.clone(),
+            values: vec![0.0; compiler.series.len()],
+            series: compiler.series,
+            condition,
+        })
+    }
+
+    pub fn expression(&self) -> &Expression {
+        &self.expression
+    }
+
+    /// updates the indicators of the rule with the candle and checks the condition
+    pub fn evaluate(&mut self, candle: &Candlestick) -> bool {
+        for (series, value) in self.series.iter_mut().zip(self.values.iter_mut()) {
+            *value = series.next(candle);
+        }
+        self.condition.evaluate(candle, &self.values)
+    }
+
+    pub fn reset(&mut self) {
+        self.series.iter_mut().for_each(Series::reset);
+        self.values.iter_mut().for_each(|value| *value = 0.0);
+        self.condition.reset();
+    }
+
+    /// candles needed until every indicator of the rule is ready, plus one to compare crosses with the previous candle
+    pub fn warmup_period(&self) -> usize {
+        let indicators = self.series.iter().map(Series::warmup_period).max().unwrap_or(0);
+        indicators + self.condition.has_cross() as usize
+    }
+}
+
+impl FromStr for Rule {
+    type Err = RuleError;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        Rule::parse(text)
+    }
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.expression)
+    }
+}
+
+// indicator updated on every candle whose value is read by the rule, the functions pick one line of the indicators with many
+enum Series {
+    Average(Box<dyn MovingAverage>, PriceSource),
+    Rsi(Rsi, PriceSource),
+    Macd(Macd, PriceSource, fn(&MacdOutput) -> f32),
+    Bands(BollingerBands, PriceSource, fn(&BollingerBandsOutput) -> f32),
+    Highest(Highest, PriceSource),
+    Lowest(Lowest, PriceSource),
+    Stochastic(Stochastic, fn(&StochasticOscillatorOutput) -> f32),
+    Adx(Adx, fn(&AdxOutput) -> f32),
+    Atr(Atr),
+    Cci(Cci),
+    WilliamsR(WilliamsR),
+    Obv(Obv),
+    Vwap(Vwap),
+    RelativeVolume(RelativeVolume),
+    TradeSize(AverageTradeSize),
+    Profile(VolumeProfile, fn(&VolumeProfileOutput) -> f32),
+    VwapBands(VwapBands, fn(&VwapBandsOutput) -> f32),
+    Pattern(PatternDetector),
+}
+
+impl Series {
+    fn next(&mut self, candle: &Candlestick) -> f32 {
+        match self {
+            Series::Average(average, source) => average.next(source.of(candle)),
+            Series::Rsi(rsi, source) => rsi.next(source.of(candle)),
+            Series::Macd(macd, source, line) => line(&macd.next(source.of(candle))),
+            Series::Bands(bands, source, line) => line(&bands.next(source.of(candle))),
+            Series::Highest(highest, source) => highest.next(source.of(candle)),
+            Series::Lowest(lowest, source) => lowest.next(source.of(candle)),
+            Series::Stochastic(stochastic, line) => line(&stochastic.next(candle)),
+            Series::Adx(adx, line) => line(&adx.next(candle)),
+            Series::Atr(atr) => atr.next(candle),
+            Series::Cci(cci) => cci.next(candle),
+            Series::WilliamsR(williams_r) => williams_r.next(candle),
+            Series::Obv(obv) => obv.next(candle),
+            Series::Vwap(vwap) => vwap.next(candle),
+            Series::RelativeVolume(relative_volume) => relative_volume.next(candle),
+            Series::TradeSize(trade_size) => trade_size.next(candle),
+            Series::Profile(profile, line) => line(&profile.next(candle)),
+            Series::VwapBands(bands, line) => line(&bands.next(candle)),
+            Series::Pattern(pattern) => pattern.next(candle),
+        }
+    }
+
+    fn reset(&mut self) {
+        match self {
+            Series::Average(average, _) => average.reset(),
+            Series::Rsi(rsi, _) => Reset::reset(rsi),
+            Series::Macd(macd, _, _) => Reset::reset(macd),
+            Series::Bands(bands, _, _) => Reset::reset(bands),
+            Series::Highest(highest, _) => Reset::reset(highest),
+            Series::Lowest(lowest, _) => Reset::reset(lowest),
+            Series::Stochastic(stochastic, _) => Reset::reset(stochastic),
+            Series::Adx(adx, _) => Reset::reset(adx),
+            Series::Atr(atr) => Reset::reset(atr),
+            Series::Cci(cci) => Reset::reset(cci),
+            Series::WilliamsR(williams_r) => Reset::reset(williams_r),
+            Series::Obv(obv) => Reset::reset(obv),
+            Series::Vwap(vwap) => Reset::reset(vwap),
+            Series::RelativeVolume(relative_volume) => Reset::reset(relative_volume),
+            Series::TradeSize(trade_size) => Reset::reset(trade_size),
+            Series::Profile(profile, _) => Reset::reset(profile),
+            Series::VwapBands(bands, _) => Reset::reset(bands),
+            Series::Pattern(pattern) => pattern.reset(),
+        }
+    }
+
+    fn warmup_period(&self) -> usize {
+        type Candle<'a> = &'a Candlestick;
+        match self {
+            Series::Average(average, _) => average.warmup_period(),
+            Series::Rsi(rsi, _) => Indicator::<f32>::warmup_period(rsi),
+            Series::Macd(macd, _, _) => Indicator::<f32>::warmup_period(macd),
+            Series::Bands(bands, _, _) => Indicator::<f32>::warmup_period(bands),
+            Series::Highest(highest, _) => Indicator::<f32>::warmup_period(highest),
+            Series::Lowest(lowest, _) => Indicator::<f32>::warmup_period(lowest),
+            Series::Stochastic(stochastic, _) => Indicator::<Candle>::warmup_period(stochastic),
+            Series::Adx(adx, _) => Indicator::<Candle>::warmup_period(adx),
+            Series::Atr(atr) => Indicator::<Candle>::warmup_period(atr),
+            Series::Cci(cci) => Indicator::<Candle>::warmup_period(cci),
+            Series::WilliamsR(williams_r) => Indicator::<Candle>::warmup_period(williams_r),
+            Series::Obv(obv) => Indicator::<Candle>::warmup_period(obv),
+            Series::Vwap(vwap) => vwap.warmup_period(),
+            Series::RelativeVolume(relative_volume) => Indicator::<Candle>::warmup_period(relative_volume),
+            Series::TradeSize(trade_size) => Indicator::<Candle>::warmup_period(trade_size),
+            Series::Profile(profile, _) => Indicator::<Candle>::warmup_period(profile),
+            Series::VwapBands(bands, _) => bands.warmup_period(),
+            Series::Pattern(pattern) => pattern.warmup_period(),
+        }
+    }
+}
+
+// number computed from the current candle and the values of the series
+enum Value {
+    Constant(f32),
+    Price(PriceSource),
+    Series(usize),
+    Negate(Box<Value>),
+    Arithmetic(Operator, Box<Value>, Box<Value>),
+}
+
+impl Value {
+    fn get(&self, candle: &Candlestick, values: &[f32]) -> f32 {
+        match self {
+            Value::Constant(value) => *value,
+            Value::Price(source) => source.of(candle),
+            Value::Series(index) => values[*index],
+            Value::Negate(value) => -value.get(candle, values),
+            Value::Arithmetic(operator, left, right) => {
+                let (left, right) = (left.get(candle, values), right.get(candle, values));
+                match operator {
+                    Operator::Add => left + right,
+                    Operator::Subtract => left - right,
+                    Operator::Multiply => left * right,
+                    _ => left / right,
+                }
+            }
+        }
+    }
+}
+
+enum Condition {
+    Compare(Operator, Value, Value),
+    And(Box<Condition>, Box<Condition>),
+    Or(Box<Condition>, Box<Condition>),
+    Not(Box<Condition>),
+    // difference of the two values on the previous candle, none on the first candle
+    Cross { above: bool, first: Value, second: Value, previous: Option<f32> },
+}
+
+impl Condition {
+    fn evaluate(&mut self, candle: &Candlestick, values: &[f32]) -> bool {
+        match self {
+            Condition::Compare(operator, left, right) => {
+                let (left, right) = (left.get(candle, values), right.get(candle, values));
+                match operator {
+                    Operator::Less => left < right,
+                    Operator::LessOrEqual => left <= right,
+                    Operator::Greater => left > right,
+                    Operator::GreaterOrEqual => left >= right,
+                    Operator::Equal => left == right,
+                    _ => left != right,
+                }
+            }
+            // both sides are always evaluated, so the crosses inside them see every candle
+            Condition::And(left, right) => {
+                let left = left.evaluate(candle, values);
+                right.evaluate(candle, values) && left
+            }
+            Condition::Or(left, right) => {
+                let left = left.evaluate(candle, values);
+                right.evaluate(candle, values) || left
+            }
+            Condition::Not(condition) => !condition.evaluate(candle, values),
+            Condition::Cross { above, first, second, previous } => {
+                let difference = first.get(candle, values) - second.get(candle, values);
+                let crossed = match previous {
+                    Some(previous) if *above => *previous <= 0.0 && difference > 0.0,
+                    Some(previous) => *previous >= 0.0 && difference < 0.0,
+                    None => false,
+                };
+                *previous = Some(difference);
+                crossed
+            }
+        }
+    }
+
+    fn reset(&mut self) {
+        match self {
+            Condition::Compare(..) => {}
+            Condition::And(left, right) | Condition::Or(left, right) => {
+                left.reset();
+                right.reset();
+            }
+            Condition::Not(condition) => condition.reset(),
+            Condition::Cross { previous, .. } => *previous = None,
+        }
+    }
+
+    fn has_cross(&self) -> bool {
+        match self {
+            Condition::Compare(..) => false,
+            Condition::And(left, right) | Condition::Or(left, right) => left.has_cross() || right.has_cross(),
+            Condition::Not(condition) => condition.has_cross(),
+            Condition::Cross { .. } => true,
+        }
+    }
+}
+
+// builds the series and the values of a rule, reusing the series of calls with the same canonical text
+struct Compiler {
+    series: Vec<Series>,
+    names: HashMap<String, usize>,
+}
+
+impl Compiler {
+    fn condition(&mut self, expression: &Expression) -> Result<Condition, RuleError> {
+        match expression {
+            Expression::Binary(Operator::And, left, right) => {
+                Ok(Condition::And(Box::new(self.condition(left)?), Box::new(self.condition(right)?)))
+            }
+            Expression::Binary(Operator::Or, left, right) => {
+                Ok(Condition::Or(Box::new(self.condition(left)?), Box::new(self.condition(right)?)))
+            }
+            Expression::Binary(operator, left, right) if operator.is_comparison() => {
+                Ok(Condition::Compare(*operator, self.value(left)?, self.value(right)?))
+            }
+            Expression::Not(condition) => Ok(Condition::Not(Box::new(self.condition(condition)?))),
+            Expression::Call(name, arguments) if name == "crosses_above" || name == "crosses_below" => {
+                if arguments.len() != 2 {
+                    return Err(RuleError::Argument { function: name.clone(), message: format!("expected 2 values, found {}", arguments.len()) });
+                }
+                Ok(Condition::Cross {
+                    above: name == "crosses_above",
+                    first: self.value(&arguments[0])?,
+                    second: self.value(&arguments[1])?,
+                    previous: None,
+                })
+            }
+            expression => Err(RuleError::Type(format!("'{}' is a number, expected a condition", expression))),
+        }
+    }
+
+    fn value(&mut self, expression: &Expression) -> Result<Value, RuleError> {
+        if expression.is_condition() {
+            return Err(RuleError::Type(format!("'{}' is a condition, expected a number", expression)));
+        }
+        match expression {
+            Expression::Number(value) => Ok(Value::Constant(*value)),
+            Expression::Price(source) => Ok(Value::Price(*source)),
+            Expression::Negate(value) => Ok(Value::Negate(Box::new(self.value(value)?))),
+            Expression::Binary(operator, left, right) => {
+                Ok(Value::Arithmetic(*operator, Box::new(self.value(left)?), Box::new(self.value(right)?)))
+            }
+            Expression::Call(name, arguments) => {
+                let key = expression.to_string();
+                if let Some(index) = self.names.get(&key) {
+                    return Ok(Value::Series(*index));
+                }
+                let series = build_series(name, arguments)?;
+                self.series.push(series);
+                self.names.insert(key, self.series.len() - 1);
+                Ok(Value::Series(self.series.len() - 1))
+            }
+            Expression::Not(_) => unreachable!("not is always a condition"),
+        }
+    }
+}
+
+// arguments of a call, `required` of them followed by up to `optional` more
+struct Arguments<'a> {
+    function: &'a str,
+    arguments: &'a [Expression],
+}
+
+impl<'a> Arguments<'a> {
+    fn new(function: &'a str, arguments: &'a [Expression], required: usize, optional: usize) -> Result<Self, RuleError> {
+        if arguments.len() < required || arguments.len() > required + optional {
+            let expected = if optional == 0 { required.to_string() } else { format!("{} to {}", required, required + optional) };
+            return Err(RuleError::Argument {
+                function: function.to_string(),
+                message: format!("expected {} arguments, found {}", expected, arguments.len()),
+            });
+        }
+        Ok(Self { function, arguments })
+    }
+
+    fn error<T>(&self, message: String) -> Result<T, RuleError> {
+        Err(RuleError::Argument { function: self.function.to_string(), message })
+    }
+
+    fn number(&self, index: usize) -> Result<f32, RuleError> {
+        match &self.arguments[index] {
+            Expression::Number(value) => Ok(*value),
+            argument => self.error(format!("argument {} must be a number, found '{}'", index + 1, argument)),
+        }
+    }
+
+    // the indicators keep buffers as long as their period, so the period of a rule read from a config is limited
+    fn period(&self, index: usize) -> Result<usize, RuleError> {
+        let value = self.number(index)?;
+        if value < 1.0 || value.fract() != 0.0 {
+            return self.error(format!("argument {} must be a whole number of candles, found {}", index + 1, value));
+        }
+        if value > MAX_PERIOD as f32 {
+            return self.error(format!("argument {} must be at most {} candles, found {}", index + 1, MAX_PERIOD, value));
+        }
+        Ok(value as usize)
+    }
+
+    // price source of an optional argument
+    fn source(&self, index: usize, default: PriceSource) -> Result<PriceSource, RuleError> {
+        match self.arguments.get(index) {
+            None => Ok(default),
+            Some(Expression::Price(source)) => Ok(*source),
+            Some(argument) => self.error(format!("argument {} must be a price, found '{}'", index + 1, argument)),
+        }
+    }
+}
+
+/// longest period of an indicator in the rule language
+pub const MAX_PERIOD: usize = 10_000;
+
+/// names of the moving averages in the rule language
+pub const MOVING_AVERAGES: [(&str, MovingAverageType); 9] = [
+    ("sma", MovingAverageType::Sma),
+    ("ema", MovingAverageType::Ema),
+    ("wma", MovingAverageType::Wma),
+    ("hma", MovingAverageType::Hma),
+    ("dema", MovingAverageType::Dema),
+    ("tema", MovingAverageType::Tema),
+    ("kama", MovingAverageType::Kama),
+    ("rma", MovingAverageType::Rma),
+    ("zlema", MovingAverageType::Zlema),
+];
+
+/// names of the candlestick patterns in the rule language
+pub const PATTERNS: [(&str, CandlestickPattern); 7] = [
+    ("engulfing", CandlestickPattern::Engulfing),
+    ("hammer", CandlestickPattern::Hammer),
+    ("doji", CandlestickPattern::Doji),
+    ("star", CandlestickPattern::Star),
+    ("inside_bar", CandlestickPattern::InsideBar),
+    ("outside_bar", CandlestickPattern::OutsideBar),
+    ("three_soldiers", CandlestickPattern::ThreeSoldiers),
+];
+
+fn build_series(name: &str, arguments: &[Expression]) -> Result<Series, RuleError> {
+    let average_type = MOVING_AVERAGES.iter().find(|(average, _)| *average == name).map(|(_, average_type)| *average_type);
+    if let Some(average_type) = average_type {
+        let arguments = Arguments::new(name, arguments, 1, 1)?;
+        return Ok(Series::Average(average_type.build(arguments.period(0)?), arguments.source(1, PriceSource::Close)?));
+    }
+    if let Some((_, pattern)) = PATTERNS.iter().find(|(pattern, _)| *pattern == name) {
+        Arguments::new(name, arguments, 0, 0)?;
+        return Ok(Series::Pattern(pattern.build()));
+    }
+
+    let series = match name {
+        "rsi" => {
+            let arguments = Arguments::new(name, arguments, 1, 1)?;
+            Series::Rsi(Rsi::new(arguments.period(0)?).smoothing(Smoothing::Wilder), arguments.source(1, PriceSource::Close)?)
+        }
+        "macd" | "macd_signal" | "macd_hist" => {
+            let arguments = Arguments::new(name, arguments, 3, 1)?;
+            let macd = Macd::new(arguments.period(0)?, arguments.period(1)?, arguments.period(2)?);
+            let line: fn(&MacdOutput) -> f32 = match name {
+                "macd" => |output| output.macd,
+                "macd_signal" => |output| output.signal,
+                _ => |output| output.histogram,
+            };
+            Series::Macd(macd, arguments.source(3, PriceSource::Close)?, line)
+        }
+        "bb_upper" | "bb_lower" => {
+            let arguments = Arguments::new(name, arguments, 2, 1)?;
+            let bands = BollingerBands::new(arguments.period(0)?, arguments.number(1)?);
+            let line: fn(&BollingerBandsOutput) -> f32 = if name == "bb_upper" { |output| output.upper } else { |output| output.lower };
+            Series::Bands(bands, arguments.source(2, PriceSource::Close)?, line)
+        }
+        "highest" => {
+            let arguments = Arguments::new(name, arguments, 1, 1)?;
+            Series::Highest(Highest::new(arguments.period(0)?), arguments.source(1, PriceSource::High)?)
+        }
+        "lowest" => {
+            let arguments = Arguments::new(name, arguments, 1, 1)?;
+            Series::Lowest(Lowest::new(arguments.period(0)?), arguments.source(1, PriceSource::Low)?)
+        }
+        "stoch_k" | "stoch_d" => {
+            let arguments = Arguments::new(name, arguments, 2, 0)?;
+            let line: fn(&StochasticOscillatorOutput) -> f32 = if name == "stoch_k" { |output| output.k } else { |output| output.d };
+            Series::Stochastic(Stochastic::new(arguments.period(0)?, arguments.period(1)?), line)
+        }
+        "adx" | "plus_di" | "minus_di" => {
+            let arguments = Arguments::new(name, arguments, 1, 0)?;
+            let line: fn(&AdxOutput) -> f32 = match name {
+                "adx" => |output| output.adx,
+                "plus_di" => |output| output.plus_di,
+                _ => |output| output.minus_di,
+            };
+            Series::Adx(Adx::new(arguments.period(0)?), line)
+        }
+        "atr" => Series::Atr(Atr::new(Arguments::new(name, arguments, 1, 0)?.period(0)?).smoothing(Smoothing::Wilder)),
+        "cci" => Series::Cci(Cci::new(Arguments::new(name, arguments, 1, 0)?.period(0)?)),
+        "willr" => Series::WilliamsR(WilliamsR::new(Arguments::new(name, arguments, 1, 0)?.period(0)?)),
+        "rvol" => Series::RelativeVolume(RelativeVolume::new(Arguments::new(name, arguments, 1, 0)?.period(0)?)),
+        "trade_size" => Series::TradeSize(AverageTradeSize::new(Arguments::new(name, arguments, 1, 0)?.period(0)?)),
+        "poc" | "va_high" | "va_low" => {
+            let arguments = Arguments::new(name, arguments, 2, 0)?;
+            let line: fn(&VolumeProfileOutput) -> f32 = match name {
+                "poc" => |output| output.point_of_control,
+                "va_high" => |output| output.value_area_high,
+                _ => |output| output.value_area_low,
+            };
+            Series::Profile(VolumeProfile::new(arguments.period(0)?, arguments.period(1)?), line)
+        }
+        "vwap_upper" | "vwap_lower" => {
+            let arguments = Arguments::new(name, arguments, 1, 0)?;
+            let line: fn(&VwapBandsOutput) -> f32 = if name == "vwap_upper" { |output| output.upper } else { |output| output.lower };
+            Series::VwapBands(VwapBands::new(Vwap::new(), arguments.number(0)?), line)
+        }
+        "obv" => {
+            Arguments::new(name, arguments, 0, 0)?;
+            Series::Obv(Obv::new())
+        }
+        "vwap" => {
+            Arguments::new(name, arguments, 0, 0)?;
+            Series::Vwap(Vwap::new())
+        }
+        _ => return Err(RuleError::UnknownFunction(name.to_string())),
+    };
+    Ok(series)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indicators::exponential_moving_average::ExponentialMovingAverage as Ema;
+
+    fn candle(close: f32) -> Candlestick {
+        Candlestick::new().open(close).high(close + 1.0).low(close - 1.0).close(close).volume(100.0)
+    }
+
+    fn closes() -> Vec<f32> {
+        (0..60).map(|i| 100.0 + 10.0 * (i as f32 / 6.0).sin()).collect()
+    }
+
+    #[test]
+    fn test_matches_the_indicators() {
+        let mut rule = Rule::parse("rsi(14) < 45 and close > ema(9)").unwrap();
+        let mut rsi = Rsi::new(14).smoothing(Smoothing::Wilder);
+        let mut ema = Ema::new(9);
+        let mut signals = 0;
+        for close in closes() {
+            let (rsi, ema) = (rsi.next(close), ema.next(close));
+            let expected = rsi < 45.0 && close > ema;
+            assert_eq!(rule.evaluate(&candle(close)), expected);
+            signals += expected as usize;
+        }
+        assert!(signals > 0);
+        assert_eq!(rule.warmup_period(), 15);
+    }
+
+    #[test]
+    fn test_crosses() {
+        let mut above = Rule::parse("crosses_above(close, 10)").unwrap();
+        let mut below = Rule::parse("crosses_below(close, 10)").unwrap();
+        let signals: Vec<(bool, bool)> = [9.0, 10.0, 11.0, 12.0, 10.0, 9.0]
+            .iter()
+            .map(|close| (above.evaluate(&candle(*close)), below.evaluate(&candle(*close))))
+            .collect();
+        assert_eq!(signals, vec![(false, false), (false, false), (true, false), (false, false), (false, false), (false, true)]);
+
+        // a cross on the other side of an `and` is still tracked while the first side is false
+        let mut rule = Rule::parse("volume > 1000 and crosses_above(close, 10)").unwrap();
+        rule.evaluate(&candle(9.0));
+        assert!(rule.evaluate(&candle(11.0).volume(2000.0)));
+        assert_eq!(rule.warmup_period(), 1);
+    }
+
+    #[test]
+    fn test_volume_series() {
+        let mut rule = Rule::parse("trade_size(2) > 150 and close > va_low(3, 4) and close < vwap_upper(2)").unwrap();
+        let mut trade_size = AverageTradeSize::new(2);
+        let mut profile = VolumeProfile::new(3, 4);
+        let mut bands = VwapBands::new(Vwap::new(), 2.0);
+        let mut signals = 0;
+        for (i, close) in closes().into_iter().enumerate() {
+            let candle = candle(close).quote_asset_volume(close * 100.0).num_trades(40 + (i as u64 % 5) * 10);
+            let (trade_size, profile, bands) = (trade_size.next(&candle), profile.next(&candle), bands.next(&candle));
+            let expected = trade_size > 150.0 && close > profile.value_area_low && close < bands.upper;
+            assert_eq!(rule.evaluate(&candle), expected);
+            signals += expected as usize;
+        }
+        assert!(signals > 0);
+        assert_eq!(rule.warmup_period(), 3);
+        assert!(Rule::parse("poc(20, 10) > close or va_high(20, 10) < close or vwap_lower(1.5) > close").is_ok());
+    }
+
+    #[test]
+    fn test_patterns() {
+        let candles = [(10.0, 10.5, 8.5, 9.0), (8.5, 11.0, 8.0, 10.5), (10.5, 11.0, 7.5, 8.0), (9.0, 10.0, 6.0, 10.0)]
+            .map(|(open, high, low, close)| Candlestick::new().open(open).high(high).low(low).close(close));
+        for (name, pattern) in PATTERNS {
+            let mut rule = Rule::parse(&format!("{}() != 0", name)).unwrap();
+            let mut detector = pattern.build();
+            for candle in &candles {
+                assert_eq!(rule.evaluate(candle), detector.next(candle) != 0.0, "{}", name);
+            }
+            assert_eq!(rule.warmup_period(), detector.warmup_period());
+        }
+
+        let mut rule = Rule::parse("engulfing() > 0.5 or hammer() > 0.5").unwrap();
+        let signals: Vec<bool> = candles.iter().map(|candle| rule.evaluate(candle)).collect();
+        assert_eq!(signals, vec![false, true, false, true]);
+    }
+
+    #[test]
+    fn test_shared_series() {
+        let rule = Rule::parse("ema(9) > ema(21) and close > ema(9) and ema(9) > 1").unwrap();
+        assert_eq!(rule.series.len(), 2);
+    }
+
+    #[test]
+    fn test_reset() {
+        let mut rule = Rule::parse("crosses_above(close, sma(2))").unwrap();
+        let run = |rule: &mut Rule| -> Vec<bool> { [3.0, 1.0, 5.0, 4.0].iter().map(|close| rule.evaluate(&candle(*close))).collect() };
+        let first = run(&mut rule);
+        rule.reset();
+        assert_eq!(run(&mut rule), first);
+        assert_eq!(first, vec![false, false, true, false]);
+    }
+
+    #[test]
+    fn test_errors() {
+        let error = |text: &str| Rule::parse(text).err().unwrap().to_string();
+        assert_eq!(error("rsi(14)"), "type error: 'rsi(14)' is a number, expected a condition");
+        assert_eq!(error("close > (open < 3)"), "type error: 'open < 3' is a condition, expected a number");
+        assert_eq!(error("foo(3) > 1"), "unknown function 'foo'");
+        assert_eq!(error("ema(9, 3) > 1"), "invalid arguments of 'ema': argument 2 must be a price, found '3'");
+        assert_eq!(error("sma(2.5) > 1"), "invalid arguments of 'sma': argument 1 must be a whole number of candles, found 2.5");
+        assert_eq!(error("sma(1000000000000) > close"), "invalid arguments of 'sma': argument 1 must be at most 10000 candles, found 1000000000000");
+        assert_eq!(error("macd(12, 26, 20000) > 0"), "invalid arguments of 'macd': argument 3 must be at most 10000 candles, found 20000");
+        assert!(Rule::parse("sma(10000) > close").is_ok());
+        assert_eq!(error("hammer(3) > 0"), "invalid arguments of 'hammer': expected 0 arguments, found 1");
+        assert_eq!(error("poc(20) > close"), "invalid arguments of 'poc': expected 2 arguments, found 1");
+        assert_eq!(error("macd(12, 26) > 0"), "invalid arguments of 'macd': expected 3 to 4 arguments, found 2");
+        assert_eq!(error("crosses_above(close) and close > 1"), "invalid arguments of 'crosses_above': expected 2 values, found 1");
+    }
+
+    #[test]
+    fn test_display() {
+        let rule: Rule = "RSI(14)<30 && Crosses_Above(EMA(9), EMA(21))".parse().unwrap();
+        assert_eq!(rule.to_string(), "rsi(14) < 30 and crosses_above(ema(9), ema(21))");
+    }
+}
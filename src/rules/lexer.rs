@@ -0,0 +1,136 @@
+use crate::rules::RuleError;
+
+/// Smallest unit of the rule language
+#[derive(Debug, Clone, PartialEq)]
+pub enum Token {
+    Number(f32),
+    /// function or price name, always lowercase
+    Identifier(String),
+    LeftParenthesis,
+    RightParenthesis,
+    Comma,
+    Plus,
+    Minus,
+    Star,
+    Slash,
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+    Equal,
+    NotEqual,
+    And,
+    Or,
+    Not,
+}
+
+/// splits `text` in tokens, each one with the byte offset where it starts. Names are case insensitive and
+/// the logical operators can be written as words (`and`, `or`, `not`) or symbols (`&&`, `||`, `!`)
+pub fn tokenize(text: &str) -> Result<Vec<(Token, usize)>, RuleError> {
+    let mut tokens = Vec::new();
+    let mut chars = text.char_indices().peekable();
+
+    while let Some((position, c)) = chars.next() {
+        let next = chars.peek().map(|(_, next)| *next);
+        let token = match c {
+            c if c.is_whitespace() => continue,
+            c if c.is_ascii_digit() || c == '.' => {
+                let mut end = position + c.len_utf8();
+                while let Some((index, digit)) = chars.peek().copied() {
+                    if !digit.is_ascii_digit() && digit != '.' {
+                        break;
+                    }
+                    end = index + digit.len_utf8();
+                    chars.next();
+                }
+                let number = text[position..end].parse().map_err(|_| RuleError::Syntax {
+                    position,
+                    message: format!("invalid number '{}'", &text[position..end]),
+                })?;
+                Token::Number(number)
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                let mut end = position + c.len_utf8();
+                while let Some((index, letter)) = chars.peek().copied() {
+                    if !letter.is_alphanumeric() && letter != '_' {
+                        break;
+                    }
+                    end = index + letter.len_utf8();
+                    chars.next();
+                }
+                match text[position..end].to_lowercase().as_str() {
+                    "and" => Token::And,
+                    "or" => Token::Or,
+                    "not" => Token::Not,
+                    name => Token::Identifier(name.to_string()),
+                }
+            }
+            '(' => Token::LeftParenthesis,
+            ')' => Token::RightParenthesis,
+            ',' => Token::Comma,
+            '+' => Token::Plus,
+            '-' => Token::Minus,
+            '*' => Token::Star,
+            '/' => Token::Slash,
+            '<' | '>' | '=' | '!' if next == Some('=') => {
+                chars.next();
+                match c {
+                    '<' => Token::LessOrEqual,
+                    '>' => Token::GreaterOrEqual,
+                    '=' => Token::Equal,
+                    _ => Token::NotEqual,
+                }
+            }
+            '<' => Token::Less,
+            '>' => Token::Greater,
+            '=' => Token::Equal,
+            '!' => Token::Not,
+            '&' | '|' if next == Some(c) => {
+                chars.next();
+                if c == '&' { Token::And } else { Token::Or }
+            }
+            c => {
+                return Err(RuleError::Syntax { position, message: format!("unexpected character '{}'", c) });
+            }
+        };
+        tokens.push((token, position));
+    }
+    Ok(tokens)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kinds(text: &str) -> Vec<Token> {
+        tokenize(text).unwrap().into_iter().map(|(token, _)| token).collect()
+    }
+
+    #[test]
+    fn test_tokenize() {
+        assert_eq!(kinds("RSI(14) <= 30.5 && !x"), vec![
+            Token::Identifier("rsi".to_string()),
+            Token::LeftParenthesis,
+            Token::Number(14.0),
+            Token::RightParenthesis,
+            Token::LessOrEqual,
+            Token::Number(30.5),
+            Token::And,
+            Token::Not,
+            Token::Identifier("x".to_string()),
+        ]);
+        assert_eq!(kinds("a or b != c"), kinds("a || b != c"));
+    }
+
+    #[test]
+    fn test_positions() {
+        let positions: Vec<usize> = tokenize("close > ema(9)").unwrap().into_iter().map(|(_, position)| position).collect();
+        assert_eq!(positions, vec![0, 6, 8, 11, 12, 13]);
+    }
+
+    #[test]
+    fn test_invalid_input() {
+        assert_eq!(tokenize("close > 1.2.3"), Err(RuleError::Syntax { position: 8, message: "invalid number '1.2.3'".to_string() }));
+        assert_eq!(tokenize("close # 3"), Err(RuleError::Syntax { position: 6, message: "unexpected character '#'".to_string() }));
+    }
+}
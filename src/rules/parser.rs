@@ -0,0 +1,447 @@
+use std::fmt;
+use std::str::FromStr;
+
+use crate::indicators::price_source::PriceSource;
+use crate::rules::lexer::{tokenize, Token};
+use crate::rules::RuleError;
+
+/// Syntax tree of a rule, e.g. `rsi(14) < 30 and crosses_above(ema(9), ema(21))`.
+/// Displaying an expression gives its canonical text, which parses back to the same expression
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expression {
+    Number(f32),
+    /// value of the current candle
+    Price(PriceSource),
+    /// indicator or function with its arguments, e.g. `ema(9, hl2)`
+    Call(String, Vec<Expression>),
+    Negate(Box<Expression>),
+    Not(Box<Expression>),
+    Binary(Operator, Box<Expression>, Box<Expression>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Operator {
+    Or,
+    And,
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+    Equal,
+    NotEqual,
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+}
+
+// `not` binds tighter than `and` but looser than the comparisons, `not rsi(14) < 30` is `not (rsi(14) < 30)`
+const NOT_PRECEDENCE: u8 = 3;
+const NEGATE_PRECEDENCE: u8 = 7;
+
+impl Operator {
+    /// higher values are applied first
+    pub fn precedence(&self) -> u8 {
+        match self {
+            Operator::Or => 1,
+            Operator::And => 2,
+            Operator::Less | Operator::LessOrEqual | Operator::Greater | Operator::GreaterOrEqual
+            | Operator::Equal | Operator::NotEqual => 4,
+            Operator::Add | Operator::Subtract => 5,
+            Operator::Multiply | Operator::Divide => 6,
+        }
+    }
+
+    /// `and` and `or`, taking and giving conditions
+    pub fn is_logical(&self) -> bool {
+        matches!(self, Operator::And | Operator::Or)
+    }
+
+    /// compares two numbers giving a condition
+    pub fn is_comparison(&self) -> bool {
+        self.precedence() == 4
+    }
+
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            Operator::Or => "or",
+            Operator::And => "and",
+            Operator::Less => "<",
+            Operator::LessOrEqual => "<=",
+            Operator::Greater => ">",
+            Operator::GreaterOrEqual => ">=",
+            Operator::Equal => "==",
+            Operator::NotEqual => "!=",
+            Operator::Add => "+",
+            Operator::Subtract => "-",
+            Operator::Multiply => "*",
+            Operator::Divide => "/",
+        }
+    }
+
+    fn from_token(token: &Token) -> Option<Self> {
+        match token {
+            Token::Or => Some(Operator::Or),
+            Token::And => Some(Operator::And),
+            Token::Less => Some(Operator::Less),
+            Token::LessOrEqual => Some(Operator::LessOrEqual),
+            Token::Greater => Some(Operator::Greater),
+            Token::GreaterOrEqual => Some(Operator::GreaterOrEqual),
+            Token::Equal => Some(Operator::Equal),
+            Token::NotEqual => Some(Operator::NotEqual),
+            Token::Plus => Some(Operator::Add),
+            Token::Minus => Some(Operator::Subtract),
+            Token::Star => Some(Operator::Multiply),
+            Token::Slash => Some(Operator::Divide),
+            _ => None,
+        }
+    }
+}
+
+/// functions giving a condition instead of a number
+pub const BOOLEAN_FUNCTIONS: [&str; 2] = ["crosses_above", "crosses_below"];
+
+impl Expression {
+    pub fn parse(text: &str) -> Result<Self, RuleError> {
+        let mut parser = Parser { tokens: tokenize(text)?, index: 0, end: text.len() };
+        let expression = parser.or()?;
+        match parser.tokens.get(parser.index) {
+            Some((token, position)) => Err(RuleError::Syntax { position: *position, message: format!("unexpected {}", describe(token)) }),
+            None => Ok(expression),
+        }
+    }
+
+    pub fn binary(operator: Operator, left: Expression, right: Expression) -> Self {
+        Expression::Binary(operator, Box::new(left), Box::new(right))
+    }
+
+    pub fn call(name: &str, arguments: Vec<Expression>) -> Self {
+        Expression::Call(name.to_string(), arguments)
+    }
+
+    /// true when the expression is a condition, false when it is a number
+    pub fn is_condition(&self) -> bool {
+        match self {
+            Expression::Not(_) => true,
+            Expression::Binary(operator, _, _) => operator.is_logical() || operator.is_comparison(),
+            Expression::Call(name, _) => BOOLEAN_FUNCTIONS.contains(&name.as_str()),
+            _ => false,
+        }
+    }
+
+    /// amount of nodes of the tree, numbers and prices included
+    pub fn size(&self) -> usize {
+        match self {
+            Expression::Number(_) | Expression::Price(_) => 1,
+            Expression::Call(_, arguments) => 1 + arguments.iter().map(Expression::size).sum::<usize>(),
+            Expression::Negate(operand) | Expression::Not(operand) => 1 + operand.size(),
+            Expression::Binary(_, left, right) => 1 + left.size() + right.size(),
+        }
+    }
+
+    /// levels of the tree, 1 for a single number or price
+    pub fn depth(&self) -> usize {
+        match self {
+            Expression::Number(_) | Expression::Price(_) => 1,
+            Expression::Call(_, arguments) => 1 + arguments.iter().map(Expression::depth).max().unwrap_or(0),
+            Expression::Negate(operand) | Expression::Not(operand) => 1 + operand.depth(),
+            Expression::Binary(_, left, right) => 1 + left.depth().max(right.depth()),
+        }
+    }
+
+    fn precedence(&self) -> u8 {
+        match self {
+            Expression::Binary(operator, _, _) => operator.precedence(),
+            Expression::Not(_) => NOT_PRECEDENCE,
+            Expression::Negate(_) => NEGATE_PRECEDENCE,
+            _ => u8::MAX,
+        }
+    }
+}
+
+impl FromStr for Expression {
+    type Err = RuleError;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        Expression::parse(text)
+    }
+}
+
+// writes `expression`, between parentheses when it would otherwise bind to its neighbours differently
+fn write_operand(f: &mut fmt::Formatter, expression: &Expression, parenthesized: bool) -> fmt::Result {
+    if parenthesized {
+        write!(f, "({})", expression)
+    } else {
+        write!(f, "{}", expression)
+    }
+}
+
+impl fmt::Display for Expression {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Expression::Number(number) => write!(f, "{}", number),
+            Expression::Price(source) => write!(f, "{}", source),
+            Expression::Call(name, arguments) => {
+                write!(f, "{}(", name)?;
+                for (index, argument) in arguments.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", argument)?;
+                }
+                write!(f, ")")
+            }
+            Expression::Negate(operand) => {
+                write!(f, "-")?;
+                write_operand(f, operand, operand.precedence() <= NEGATE_PRECEDENCE)
+            }
+            Expression::Not(operand) => {
+                write!(f, "not ")?;
+                write_operand(f, operand, operand.precedence() < NOT_PRECEDENCE)
+            }
+            Expression::Binary(operator, left, right) => {
+                // the operators are left associative, so only a right operand of the same precedence needs parentheses
+                write_operand(f, left, left.precedence() < operator.precedence())?;
+                write!(f, " {} ", operator.symbol())?;
+                write_operand(f, right, right.precedence() <= operator.precedence())
+            }
+        }
+    }
+}
+
+fn describe(token: &Token) -> String {
+    match token {
+        Token::Number(number) => format!("number {}", number),
+        Token::Identifier(name) => format!("'{}'", name),
+        Token::LeftParenthesis => "'('".to_string(),
+        Token::RightParenthesis => "')'".to_string(),
+        Token::Comma => "','".to_string(),
+        Token::Not => "'not'".to_string(),
+        token => format!("'{}'", Operator::from_token(token).map_or("", |operator| operator.symbol())),
+    }
+}
+
+// recursive descent parser, one method per precedence level from the loosest to the tightest
+struct Parser {
+    tokens: Vec<(Token, usize)>,
+    index: usize,
+    end: usize, // length of the text, the position reported for a missing token at the end
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.index).map(|(token, _)| token)
+    }
+
+    fn position(&self) -> usize {
+        self.tokens.get(self.index).map_or(self.end, |(_, position)| *position)
+    }
+
+    fn error<T>(&self, expected: &str) -> Result<T, RuleError> {
+        let found = self.peek().map_or_else(|| "end of the rule".to_string(), describe);
+        Err(RuleError::Syntax { position: self.position(), message: format!("expected {}, found {}", expected, found) })
+    }
+
+    fn expect(&mut self, token: Token, expected: &str) -> Result<(), RuleError> {
+        if self.peek() == Some(&token) {
+            self.index += 1;
+            Ok(())
+        } else {
+            self.error(expected)
+        }
+    }
+
+    // next operator if it is one of `operators`
+    fn operator(&mut self, operators: &[Operator]) -> Option<Operator> {
+        let operator = self.peek().and_then(Operator::from_token).filter(|operator| operators.contains(operator));
+        if operator.is_some() {
+            self.index += 1;
+        }
+        operator
+    }
+
+    fn or(&mut self) -> Result<Expression, RuleError> {
+        let mut expression = self.and()?;
+        while let Some(operator) = self.operator(&[Operator::Or]) {
+            expression = Expression::binary(operator, expression, self.and()?);
+        }
+        Ok(expression)
+    }
+
+    fn and(&mut self) -> Result<Expression, RuleError> {
+        let mut expression = self.not()?;
+        while let Some(operator) = self.operator(&[Operator::And]) {
+            expression = Expression::binary(operator, expression, self.not()?);
+        }
+        Ok(expression)
+    }
+
+    fn not(&mut self) -> Result<Expression, RuleError> {
+        if self.peek() == Some(&Token::Not) {
+            self.index += 1;
+            return Ok(Expression::Not(Box::new(self.not()?)));
+        }
+        self.comparison()
+    }
+
+    // comparisons can't be chained, `a < b < c` is a syntax error
+    fn comparison(&mut self) -> Result<Expression, RuleError> {
+        let left = self.additive()?;
+        let comparisons = [
+            Operator::Less, Operator::LessOrEqual, Operator::Greater,
+            Operator::GreaterOrEqual, Operator::Equal, Operator::NotEqual,
+        ];
+        match self.operator(&comparisons) {
+            Some(operator) => Ok(Expression::binary(operator, left, self.additive()?)),
+            None => Ok(left),
+        }
+    }
+
+    fn additive(&mut self) -> Result<Expression, RuleError> {
+        let mut expression = self.multiplicative()?;
+        while let Some(operator) = self.operator(&[Operator::Add, Operator::Subtract]) {
+            expression = Expression::binary(operator, expression, self.multiplicative()?);
+        }
+        Ok(expression)
+    }
+
+    fn multiplicative(&mut self) -> Result<Expression, RuleError> {
+        let mut expression = self.unary()?;
+        while let Some(operator) = self.operator(&[Operator::Multiply, Operator::Divide]) {
+            expression = Expression::binary(operator, expression, self.unary()?);
+        }
+        Ok(expression)
+    }
+
+    fn unary(&mut self) -> Result<Expression, RuleError> {
+        if self.peek() != Some(&Token::Minus) {
+            return self.primary();
+        }
+        self.index += 1;
+        // negative numbers are kept as numbers
+        match self.unary()? {
+            Expression::Number(number) => Ok(Expression::Number(-number)),
+            operand => Ok(Expression::Negate(Box::new(operand))),
+        }
+    }
+
+    fn primary(&mut self) -> Result<Expression, RuleError> {
+        let position = self.position();
+        match self.peek().cloned() {
+            Some(Token::Number(number)) => {
+                self.index += 1;
+                Ok(Expression::Number(number))
+            }
+            Some(Token::LeftParenthesis) => {
+                self.index += 1;
+                let expression = self.or()?;
+                self.expect(Token::RightParenthesis, "')'")?;
+                Ok(expression)
+            }
+            Some(Token::Identifier(name)) => {
+                self.index += 1;
+                if self.peek() == Some(&Token::LeftParenthesis) {
+                    self.index += 1;
+                    return Ok(Expression::Call(name, self.arguments()?));
+                }
+                match PriceSource::ALL.iter().find(|source| source.to_string() == name) {
+                    Some(source) => Ok(Expression::Price(*source)),
+                    None => Err(RuleError::Syntax { position, message: format!("unknown value '{}'", name) }),
+                }
+            }
+            _ => self.error("a number, a price or a function"),
+        }
+    }
+
+    // arguments of a call after its opening parenthesis
+    fn arguments(&mut self) -> Result<Vec<Expression>, RuleError> {
+        let mut arguments = Vec::new();
+        if self.peek() == Some(&Token::RightParenthesis) {
+            self.index += 1;
+            return Ok(arguments);
+        }
+        loop {
+            arguments.push(self.or()?);
+            if self.peek() == Some(&Token::Comma) {
+                self.index += 1;
+            } else {
+                self.expect(Token::RightParenthesis, "',' or ')'")?;
+                return Ok(arguments);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn number(value: f32) -> Expression {
+        Expression::Number(value)
+    }
+
+    #[test]
+    fn test_parse() {
+        let expression = Expression::parse("rsi(14) < 30 and crosses_above(ema(9), ema(21, hl2))").unwrap();
+        let expected = Expression::binary(Operator::And,
+            Expression::binary(Operator::Less, Expression::call("rsi", vec![number(14.0)]), number(30.0)),
+            Expression::call("crosses_above", vec![
+                Expression::call("ema", vec![number(9.0)]),
+                Expression::call("ema", vec![number(21.0), Expression::Price(PriceSource::Hl2)]),
+            ]));
+        assert_eq!(expression, expected);
+        assert!(expression.is_condition());
+    }
+
+    #[test]
+    fn test_precedence() {
+        let expression = Expression::parse("close > 2 * sma(3) - 1 or not volume > 5 and open < 3").unwrap();
+        assert_eq!(expression.to_string(), "close > 2 * sma(3) - 1 or not volume > 5 and open < 3");
+        match expression {
+            Expression::Binary(Operator::Or, left, right) => {
+                assert_eq!(*left, Expression::binary(Operator::Greater, Expression::Price(PriceSource::Close),
+                    Expression::binary(Operator::Subtract,
+                        Expression::binary(Operator::Multiply, number(2.0), Expression::call("sma", vec![number(3.0)])),
+                        number(1.0))));
+                assert!(matches!(*right, Expression::Binary(Operator::And, ref not, _) if matches!(**not, Expression::Not(_))));
+            }
+            expression => panic!("parsed {:?}", expression),
+        }
+    }
+
+    #[test]
+    fn test_display_round_trip() {
+        let texts = [
+            "(close - open) / (high - low) > 0.5",
+            "close - (open - low) > 1",
+            "not (rsi(14) > 70 or rsi(14) < 30)",
+            "-(close - open) > -2",
+            "macd_hist(12, 26, 9) > 0 and (close > vwap() or volume > 2 * sma(20, volume))",
+        ];
+        for text in texts {
+            let expression = Expression::parse(text).unwrap();
+            assert_eq!(expression.to_string(), text);
+            assert_eq!(Expression::parse(&expression.to_string()).unwrap(), expression);
+        }
+        // redundant parentheses and symbols are dropped
+        assert_eq!(Expression::parse("((RSI(14)) <= 30) && !(close = open)").unwrap().to_string(), "rsi(14) <= 30 and not close == open");
+    }
+
+    #[test]
+    fn test_size_and_depth() {
+        let expression = Expression::parse("rsi(14) < 30 and not close > ema(9, hl2)").unwrap();
+        assert_eq!(expression.size(), 11);
+        assert_eq!(expression.depth(), 5);
+        assert_eq!(Expression::parse("obv()").unwrap().depth(), 1);
+    }
+
+    #[test]
+    fn test_syntax_errors() {
+        let error = |text: &str| Expression::parse(text).unwrap_err().to_string();
+        assert_eq!(error("rsi(14) <"), "syntax error at 9: expected a number, a price or a function, found end of the rule");
+        assert_eq!(error("rsi(14 < 30"), "syntax error at 11: expected ',' or ')', found end of the rule");
+        assert_eq!(error("price > 3"), "syntax error at 0: unknown value 'price'");
+        assert_eq!(error("close < open < high"), "syntax error at 13: unexpected '<'");
+        assert_eq!(error("close open"), "syntax error at 6: unexpected 'open'");
+    }
+}